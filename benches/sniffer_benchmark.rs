@@ -1,17 +1,67 @@
-use std::env;
+use std::{env, time::Duration};
 
+use arxiv::Arxiv;
 use async_openai::config::OPENAI_API_BASE;
-use criterion::{criterion_group, criterion_main, Criterion};
+use chrono::NaiveDate;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use langchain_rust::llm::{OpenAI, OpenAIConfig};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use sciffer_rs::{
-    analyzers::simple::SimpleArixvTrendingAnalyzer,
-    extracters::topic::{TopicExtracter, TopicExtracterBuilder},
-    fetchers::arxiv::{ArxivFetcher, ArxivFetcherBuilder},
+    analyzers::{
+        simple::SimpleArixvTrendingAnalyzerBuilder, weighted::WeightedTrendingAnalyzer, TopK,
+        TrendingAnalyzer,
+    },
+    config::TrendingConfig,
+    extracters::topic::{ArxivTopicData, TopicExtracter, TopicExtracterBuilder},
+    fetchers::{
+        arxiv::{ArxivFetcher, ArxivFetcherBuilder},
+        Fetcher, FetcherError,
+    },
+    llms::mock::{MockLlm, MockResponse},
     sciffer::{ArxivSciffer, ArxivScifferBuilder, Sniffer},
 };
 
 use tokio::runtime::Runtime;
 
+#[derive(Clone)]
+struct StaticFetcher {
+    number: usize,
+}
+
+impl Fetcher for StaticFetcher {
+    type Output = Arxiv;
+
+    async fn fetch(&self) -> Result<Vec<Self::Output>, FetcherError> {
+        Ok((0..self.number)
+            .map(|i| Arxiv {
+                id: format!("http://arxiv.org/abs/2502.{:05}v1", i),
+                title: format!("paper {}", i),
+                summary: "A dummy abstract.".to_string(),
+                ..Default::default()
+            })
+            .collect())
+    }
+}
+
+fn setup_mock_sciffer() -> ArxivSciffer<StaticFetcher, TopicExtracter> {
+    let llm = MockLlm::new().with_default(MockResponse::delayed(
+        Duration::from_millis(10),
+        MockResponse::text(
+            r#"{"title": "t", "solved_problem": ["p"], "research_field": ["f"], "techniques_used": ["t"]}"#,
+        ),
+    ));
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm))
+        .build()
+        .unwrap();
+
+    ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher { number: 100 })
+        .extracter(extracter)
+        .build()
+        .unwrap()
+}
+
 fn setup_sciffer() -> ArxivSciffer<ArxivFetcher, TopicExtracter> {
     let _ = dotenv::dotenv();
     let fetcher = ArxivFetcherBuilder::default()
@@ -46,9 +96,20 @@ fn benchmark_sniffer(c: &mut Criterion) {
     let mut group = c.benchmark_group("sciffer-process");
     group.sample_size(10);
 
-    let sciffer = setup_sciffer();
-
     let runtime = Runtime::new().unwrap();
+
+    let sciffer = setup_mock_sciffer();
+    group.bench_function("sniffer_parallel_mock", |b| {
+        b.iter(|| {
+            runtime.block_on(async { sciffer.sniffer_parallel().await.unwrap() });
+        })
+    });
+
+    let _ = dotenv::dotenv();
+    if env::var("API_KEY").is_err() {
+        return;
+    }
+    let sciffer = setup_sciffer();
     group.bench_function("sniffer_parallel", |b| {
         b.iter(|| {
             runtime.block_on(async { sciffer.sniffer_parallel().await.unwrap() });
@@ -56,5 +117,70 @@ fn benchmark_sniffer(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmark_sniffer);
+/// `n` extracted papers over a year, with techniques drawn from 5000 keywords,
+/// the first ones far more often.
+fn extracted_papers(n: usize) -> Vec<(Arxiv, ArxivTopicData)> {
+    let mut rng = StdRng::seed_from_u64(42);
+    (0..n)
+        .map(|i| {
+            let raw = Arxiv {
+                id: format!("http://arxiv.org/abs/2504.{:05}v1", i),
+                published: format!(
+                    "2025-{:02}-{:02}T12:00:00Z",
+                    rng.gen_range(1..=12),
+                    rng.gen_range(1..=28)
+                ),
+                authors: vec![format!("author {}", rng.gen_range(0..n / 4))],
+                ..Default::default()
+            };
+            let techniques = (0..rng.gen_range(1..=5))
+                .map(|_| {
+                    let x: f64 = rng.gen();
+                    format!("technique {}", (x * x * x * 5000.0) as usize)
+                })
+                .collect();
+            let ctx = ArxivTopicData {
+                techniques_used: techniques,
+                ..Default::default()
+            };
+            (raw, ctx)
+        })
+        .collect()
+}
+
+fn benchmark_analyzers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("trending-analyzers");
+    group.sample_size(10);
+
+    let data = extracted_papers(100_000);
+    let f = |d: &ArxivTopicData| d.techniques_used.clone();
+    let simple = SimpleArixvTrendingAnalyzerBuilder::default()
+        .build()
+        .unwrap();
+    group.bench_function("simple_top_k_100k", |b| {
+        b.iter(|| black_box(simple.top_k(&data, f, 20)))
+    });
+    group.bench_function("simple_trends_ids_100k", |b| {
+        b.iter(|| {
+            let papers = data.iter().enumerate().map(|(i, (raw, ctx))| (i, raw, ctx));
+            black_box(simple.trends(papers, f, TopK::new(20)))
+        })
+    });
+
+    let config = TrendingConfig {
+        half_life_days: Some(30.0),
+        position_decay: Some(0.5),
+        ..Default::default()
+    };
+    let weighted = WeightedTrendingAnalyzer::from_config(
+        &config,
+        NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+    )
+    .unwrap();
+    group.bench_function("weighted_top_k_100k", |b| {
+        b.iter(|| black_box(weighted.top_k(&data, f, 20)))
+    });
+}
+
+criterion_group!(benches, benchmark_sniffer, benchmark_analyzers);
 criterion_main!(benches);
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS papers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    title TEXT NOT NULL,
+    abstract_text TEXT,
+    publish_date TEXT,
+    insert_date TEXT NOT NULL,
+    url TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS keywords (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    keyword TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS paper_keywords (
+    paper_id INTEGER NOT NULL REFERENCES papers (id),
+    keyword_id INTEGER NOT NULL REFERENCES keywords (id)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS failed_extractions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    arxiv_id TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    publish_date TEXT NOT NULL,
+    url TEXT NOT NULL,
+    error_kind TEXT NOT NULL,
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    last_attempt TEXT NOT NULL
+);
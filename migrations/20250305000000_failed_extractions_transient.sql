@@ -0,0 +1 @@
+ALTER TABLE failed_extractions ADD COLUMN transient BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS extractions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    paper_id INTEGER NOT NULL REFERENCES papers (id),
+    extracter TEXT NOT NULL,
+    prompt_name TEXT NOT NULL,
+    prompt_version TEXT NOT NULL,
+    prompt_hash TEXT NOT NULL,
+    model TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+ALTER TABLE paper_keywords ADD COLUMN extraction_id INTEGER REFERENCES extractions (id);
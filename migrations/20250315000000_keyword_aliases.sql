@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS keyword_aliases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    alias TEXT NOT NULL,
+    keyword_id INTEGER NOT NULL REFERENCES keywords (id),
+    -- 'auto' merges are made by the normalizer, 'pinned' and 'rejected' by users
+    status TEXT NOT NULL DEFAULT 'auto',
+    score REAL,
+    UNIQUE (alias, keyword_id)
+);
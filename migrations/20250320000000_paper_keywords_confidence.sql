@@ -0,0 +1,2 @@
+-- share of ensemble members that agreed on the keyword, NULL for single extracters
+ALTER TABLE paper_keywords ADD COLUMN confidence REAL;
@@ -0,0 +1,23 @@
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    created_at TEXT NOT NULL,
+    elapsed_ms INTEGER NOT NULL,
+    successes INTEGER NOT NULL,
+    failures INTEGER NOT NULL,
+    unfinished INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS run_usage (
+    run_id INTEGER NOT NULL REFERENCES runs (id),
+    model TEXT NOT NULL,
+    extractions INTEGER NOT NULL,
+    prompt_tokens INTEGER NOT NULL,
+    completion_tokens INTEGER NOT NULL,
+    cost REAL,
+    PRIMARY KEY (run_id, model)
+);
+
+ALTER TABLE extractions ADD COLUMN run_id INTEGER REFERENCES runs (id);
+ALTER TABLE extractions ADD COLUMN prompt_tokens INTEGER;
+ALTER TABLE extractions ADD COLUMN completion_tokens INTEGER;
+ALTER TABLE extractions ADD COLUMN cost REAL;
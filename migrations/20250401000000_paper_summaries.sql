@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS paper_summaries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    paper_id INTEGER NOT NULL UNIQUE REFERENCES papers (id),
+    extraction_id INTEGER REFERENCES extractions (id),
+    tldr TEXT NOT NULL,
+    -- JSON arrays of strings
+    contributions TEXT NOT NULL,
+    results TEXT NOT NULL,
+    limitations TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
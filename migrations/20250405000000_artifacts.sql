@@ -0,0 +1,32 @@
+-- datasets, benchmarks and metrics, one row per normalized name
+CREATE TABLE IF NOT EXISTS artifacts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    key TEXT NOT NULL,
+    UNIQUE (kind, key)
+);
+
+CREATE TABLE IF NOT EXISTS paper_artifacts (
+    paper_id INTEGER NOT NULL REFERENCES papers (id),
+    artifact_id INTEGER NOT NULL REFERENCES artifacts (id),
+    extraction_id INTEGER REFERENCES extractions (id),
+    PRIMARY KEY (paper_id, artifact_id)
+);
+
+CREATE TABLE IF NOT EXISTS artifact_scores (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    paper_id INTEGER NOT NULL REFERENCES papers (id),
+    benchmark_id INTEGER NOT NULL REFERENCES artifacts (id),
+    metric_id INTEGER REFERENCES artifacts (id),
+    -- as reported, e.g. `3.57%`
+    value TEXT NOT NULL,
+    extraction_id INTEGER REFERENCES extractions (id)
+);
+
+CREATE TABLE IF NOT EXISTS code_links (
+    paper_id INTEGER NOT NULL REFERENCES papers (id),
+    url TEXT NOT NULL,
+    extraction_id INTEGER REFERENCES extractions (id),
+    PRIMARY KEY (paper_id, url)
+);
@@ -0,0 +1,2 @@
+-- everything the extracter produced for the paper, as JSON
+ALTER TABLE extractions ADD COLUMN output TEXT;
@@ -0,0 +1,12 @@
+-- relevance of each scored paper to the subscription's topic, kept for
+-- auditing the threshold: dropped papers are never stored in `papers`
+CREATE TABLE IF NOT EXISTS paper_relevance (
+    arxiv_id TEXT PRIMARY KEY NOT NULL,
+    title TEXT NOT NULL,
+    score REAL NOT NULL,
+    relevant BOOLEAN NOT NULL,
+    -- whether the paper was extracted, off-topic ones are when flagged
+    kept BOOLEAN NOT NULL,
+    run_id INTEGER REFERENCES runs (id),
+    created_at TEXT NOT NULL
+);
@@ -0,0 +1,26 @@
+-- topics found by topic modeling of the stored abstracts, kept to be
+-- compared with the keywords extracted by the LLM
+CREATE TABLE IF NOT EXISTS topic_models (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    method TEXT NOT NULL,
+    papers INTEGER NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS topics (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    model_id INTEGER NOT NULL REFERENCES topic_models (id),
+    -- rank of the topic in its model, the most prevalent first
+    position INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    -- JSON array of the top terms
+    terms TEXT NOT NULL,
+    prevalence REAL NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS paper_topics (
+    paper_id INTEGER NOT NULL REFERENCES papers (id),
+    topic_id INTEGER NOT NULL REFERENCES topics (id),
+    weight REAL NOT NULL,
+    PRIMARY KEY (paper_id, topic_id)
+);
@@ -0,0 +1,13 @@
+-- authors as spelled on arXiv, told apart or merged when analyzed
+CREATE TABLE IF NOT EXISTS authors (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS paper_authors (
+    paper_id INTEGER NOT NULL REFERENCES papers (id),
+    author_id INTEGER NOT NULL REFERENCES authors (id),
+    -- 0 for the first author
+    position INTEGER NOT NULL,
+    PRIMARY KEY (paper_id, author_id)
+);
@@ -0,0 +1,10 @@
+-- a paper extracted again reports the same scores, keep the first of each
+DELETE FROM artifact_scores
+WHERE id NOT IN (
+    SELECT MIN(id) FROM artifact_scores
+    GROUP BY paper_id, benchmark_id, IFNULL(metric_id, 0), value
+);
+
+-- metric_id is NULL for scores without a metric, NULLs never collide in UNIQUE
+CREATE UNIQUE INDEX IF NOT EXISTS artifact_scores_unique
+ON artifact_scores (paper_id, benchmark_id, IFNULL(metric_id, 0), value);
@@ -0,0 +1,2 @@
+-- the arXiv comment, e.g. "Accepted at ICLR 2025", read by the venue weight
+ALTER TABLE papers ADD COLUMN comment TEXT;
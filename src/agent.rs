@@ -1,26 +1,35 @@
 use langchain_rust::{
-    chain::{Chain, ChainError, LLMChainBuilder},
-    language_models::llm::LLM,
+    chain::{Chain, ChainError, LLMChain, LLMChainBuilder},
+    language_models::{llm::LLM, GenerateResult},
     prompt::{FormatPrompter, PromptArgs},
 };
 
 pub trait AgentApp {
     fn get_prompt(&self) -> Box<dyn FormatPrompter>;
     fn get_llm(&self) -> Box<dyn LLM>;
+
+    fn get_chain(&self) -> LLMChain {
+        LLMChainBuilder::new()
+            .prompt(self.get_prompt())
+            .llm(self.get_llm())
+            .build()
+            .unwrap()
+    }
+
     fn invoke(
         &self,
         args: PromptArgs,
     ) -> impl std::future::Future<Output = Result<String, ChainError>> {
-        let llm = self.get_llm();
-
-        let prompt = self.get_prompt();
-
-        let chain = LLMChainBuilder::new()
-            .prompt(prompt)
-            .llm(llm)
-            .build()
-            .unwrap();
+        let chain = self.get_chain();
+        async move { chain.invoke(args).await }
+    }
 
-        async move { chain.invoke(args).await.map_err(|e| e.into()) }
+    /// Like `invoke`, but keeps the token usage reported by the LLM.
+    fn call(
+        &self,
+        args: PromptArgs,
+    ) -> impl std::future::Future<Output = Result<GenerateResult, ChainError>> {
+        let chain = self.get_chain();
+        async move { chain.call(args).await }
     }
 }
@@ -0,0 +1,22 @@
+use sqlx::SqlitePool;
+
+use crate::{dao::ArtifactDAO, extracters::artifact::ArtifactKind};
+
+/// The `k` stored artifacts of `kind` most papers published since `since`
+/// evaluate with, e.g. the trending benchmarks, with their paper counts.
+/// Spellings sharing a [`rule_key`](crate::normalizers::keyword::rule_key)
+/// count as one artifact.
+pub async fn trending_artifacts(
+    pool: &SqlitePool,
+    kind: ArtifactKind,
+    since: &str,
+    k: usize,
+) -> Result<Vec<(String, usize)>, sqlx::Error> {
+    Ok(
+        ArtifactDAO::top_artifacts(pool, kind.as_str(), since, k as i64)
+            .await?
+            .into_iter()
+            .map(|a| (a.name, a.papers as usize))
+            .collect(),
+    )
+}
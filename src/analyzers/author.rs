@@ -0,0 +1,659 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Display,
+};
+
+use arxiv::Arxiv;
+use chrono::NaiveDate;
+use derive_builder::Builder;
+use sqlx::SqlitePool;
+
+use crate::{
+    dao::{AuthorDAO, PaperKeywordDAO},
+    extracters::topic::ArxivTopicData,
+    tools::similarity::cosine,
+};
+
+use super::{
+    graph::{CooccurrenceAnalyzerBuilder, CooccurrenceGraph, EdgeWeight},
+    trend::{parse_day, Granularity},
+};
+
+/// A paper as the author analyzers see it.
+#[derive(Debug, Clone, Default)]
+pub struct AuthoredPaper {
+    pub day: Option<NaiveDate>,
+    /// As spelled on the paper, in order.
+    pub authors: Vec<String>,
+    pub keywords: Vec<String>,
+}
+
+/// Papers of `data` with the keywords `f` picks.
+pub fn authored_of<F: Fn(&ArxivTopicData) -> Vec<String>>(
+    data: &[(Arxiv, ArxivTopicData)],
+    f: F,
+) -> Vec<AuthoredPaper> {
+    data.iter()
+        .map(|(raw, ctx)| AuthoredPaper {
+            day: parse_day(&raw.published),
+            authors: raw.authors.clone(),
+            keywords: f(ctx),
+        })
+        .collect()
+}
+
+/// Stored papers published since `since`, e.g. `2025-03-01`, with their
+/// stored authors and keywords. Papers stored without authors are left out.
+pub async fn authored_stored(
+    pool: &SqlitePool,
+    since: &str,
+) -> Result<Vec<AuthoredPaper>, sqlx::Error> {
+    let mut papers: BTreeMap<i64, AuthoredPaper> = BTreeMap::new();
+    for author in AuthorDAO::authors_by_paper(pool, since).await? {
+        let paper = papers
+            .entry(author.paper_id)
+            .or_insert_with(|| AuthoredPaper {
+                day: parse_day(&author.published),
+                ..Default::default()
+            });
+        paper.authors.push(author.name);
+    }
+    for keyword in PaperKeywordDAO::keywords_by_paper(pool, since).await? {
+        if let Some(paper) = papers.get_mut(&keyword.paper_id) {
+            paper.keywords.push(keyword.keyword);
+        }
+    }
+    Ok(papers.into_values().collect())
+}
+
+/// `c` without its accent, for the Latin letters.
+fn fold(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'č' => 'c',
+        'ď' | 'đ' => 'd',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => 'e',
+        'ğ' => 'g',
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ı' => 'i',
+        'ł' | 'ľ' => 'l',
+        'ñ' | 'ń' | 'ň' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => 'o',
+        'ř' => 'r',
+        'ś' | 'š' | 'ş' => 's',
+        'ť' | 'ţ' => 't',
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        c => c,
+    }
+}
+
+/// Lowercase words of a family name that belong to the family name after it.
+const PARTICLES: &[&str] = &[
+    "da", "de", "del", "della", "der", "di", "du", "la", "le", "van", "von",
+];
+
+/// Given names and family name of `name`, lowercase without accents or
+/// punctuation: `Smith, John A.` and `John A. Smith` are both
+/// `(["john", "a"], "smith")`, `Jan van der Berg` is `(["jan"], "van der berg")`.
+pub fn split_name(name: &str) -> (Vec<String>, String) {
+    let words = |s: &str| -> Vec<String> {
+        s.split(|c: char| c.is_whitespace() || c == '.' || c == '-')
+            .map(|w| {
+                w.chars()
+                    .flat_map(char::to_lowercase)
+                    .map(fold)
+                    .filter(|c| c.is_alphanumeric())
+                    .collect::<String>()
+            })
+            .filter(|w| !w.is_empty())
+            .collect()
+    };
+    if let Some((family, given)) = name.split_once(',') {
+        return (words(given), words(family).join(" "));
+    }
+    // the last word and the particles before it, which are lowercase in the
+    // original spelling
+    let original: Vec<&str> = name.split_whitespace().collect();
+    let mut start = original.len().saturating_sub(1);
+    while start > 1 && PARTICLES.contains(&original[start - 1]) {
+        start -= 1;
+    }
+    (
+        words(&original[..start].join(" ")),
+        words(&original[start..].join(" ")).join(" "),
+    )
+}
+
+/// Initial of the first given name and the family name, e.g. `j smith`,
+/// shared by every spelling of an author.
+pub fn author_key(name: &str) -> String {
+    let (given, family) = split_name(name);
+    match given.first().and_then(|g| g.chars().next()) {
+        Some(initial) => format!("{} {}", initial, family),
+        None => family,
+    }
+}
+
+/// Whether two lists of given names can be the same person's: every given
+/// name of the shorter list equal to the other's, or an initial of it.
+fn compatible(a: &[String], b: &[String]) -> bool {
+    a.iter().zip(b.iter()).all(|(a, b)| {
+        a == b
+            || (a.chars().count() == 1 && b.starts_with(a.as_str()))
+            || (b.chars().count() == 1 && a.starts_with(b.as_str()))
+    })
+}
+
+/// A disambiguated author.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Author {
+    /// The most complete spelling.
+    pub name: String,
+    /// Every spelling met, the most complete first.
+    pub variants: Vec<String>,
+    pub papers: usize,
+}
+
+/// Papers with their authors told apart.
+///
+/// Spellings are grouped by [`author_key`]. Within a group, spellings with
+/// compatible given names, `J. Smith` and `John Smith`, are the same author
+/// if they share a co-author or if only one spelling could be meant. Two
+/// incompatible spellings, `John Smith` and `Jane Smith`, are never merged.
+#[derive(Debug, Clone)]
+pub struct AuthorIndex {
+    papers: Vec<AuthoredPaper>,
+    authors: Vec<Author>,
+    /// Authors of each paper, in order.
+    paper_authors: Vec<Vec<usize>>,
+}
+
+/// A spelling of a name and what is known about it.
+struct Variant {
+    name: String,
+    given: Vec<String>,
+    key: String,
+    coauthors: BTreeSet<String>,
+    papers: usize,
+}
+
+impl AuthorIndex {
+    pub fn new(papers: Vec<AuthoredPaper>) -> Self {
+        let mut variants: Vec<Variant> = Vec::new();
+        let mut by_spelling: HashMap<String, usize> = HashMap::new();
+        let mut occurrences: Vec<Vec<usize>> = Vec::with_capacity(papers.len());
+        for paper in papers.iter() {
+            let keys: Vec<String> = paper.authors.iter().map(|a| author_key(a)).collect();
+            let mut authors = Vec::new();
+            for (name, key) in paper.authors.iter().zip(keys.iter()) {
+                let (given, family) = split_name(name);
+                let spelling = format!("{} {}", given.join(" "), family);
+                let v = *by_spelling.entry(spelling).or_insert_with(|| {
+                    variants.push(Variant {
+                        name: name.trim().to_string(),
+                        given,
+                        key: key.clone(),
+                        coauthors: BTreeSet::new(),
+                        papers: 0,
+                    });
+                    variants.len() - 1
+                });
+                variants[v].papers += 1;
+                variants[v]
+                    .coauthors
+                    .extend(keys.iter().filter(|k| *k != key).cloned());
+                if !authors.contains(&v) {
+                    authors.push(v);
+                }
+            }
+            occurrences.push(authors);
+        }
+
+        // clusters of variants, merged when every pair of their variants is
+        // compatible
+        let mut clusters: Vec<Vec<usize>> = (0..variants.len()).map(|v| vec![v]).collect();
+        let mut cluster_of: Vec<usize> = (0..variants.len()).collect();
+        let mut by_key: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (v, variant) in variants.iter().enumerate() {
+            by_key.entry(variant.key.as_str()).or_default().push(v);
+        }
+        for group in by_key.values() {
+            for &u in group.iter() {
+                let candidates: Vec<usize> = group
+                    .iter()
+                    .copied()
+                    .filter(|&v| v != u && compatible(&variants[u].given, &variants[v].given))
+                    .collect();
+                let shared: Vec<usize> = candidates
+                    .iter()
+                    .copied()
+                    .filter(|&v| !variants[u].coauthors.is_disjoint(&variants[v].coauthors))
+                    .collect();
+                let merge = match (shared.is_empty(), candidates.len()) {
+                    (false, _) => shared,
+                    (true, 1) => candidates,
+                    _ => Vec::new(),
+                };
+                for v in merge {
+                    let (a, b) = (cluster_of[u], cluster_of[v]);
+                    if a == b {
+                        continue;
+                    }
+                    let all_compatible = clusters[a].iter().all(|&x| {
+                        clusters[b]
+                            .iter()
+                            .all(|&y| compatible(&variants[x].given, &variants[y].given))
+                    });
+                    if !all_compatible {
+                        continue;
+                    }
+                    let moved = std::mem::take(&mut clusters[b]);
+                    for &x in moved.iter() {
+                        cluster_of[x] = a;
+                    }
+                    clusters[a].extend(moved);
+                }
+            }
+        }
+
+        let mut authors = Vec::new();
+        let mut author_of = vec![0; variants.len()];
+        for cluster in clusters.iter_mut().filter(|c| !c.is_empty()) {
+            cluster.sort_by(|&a, &b| {
+                let (a, b) = (&variants[a], &variants[b]);
+                let letters = |v: &Variant| v.given.iter().map(String::len).sum::<usize>();
+                letters(b)
+                    .cmp(&letters(a))
+                    .then(b.papers.cmp(&a.papers))
+                    .then(a.name.cmp(&b.name))
+            });
+            for &v in cluster.iter() {
+                author_of[v] = authors.len();
+            }
+            authors.push(Author {
+                name: variants[cluster[0]].name.clone(),
+                variants: cluster.iter().map(|&v| variants[v].name.clone()).collect(),
+                papers: 0,
+            });
+        }
+        let paper_authors: Vec<Vec<usize>> = occurrences
+            .into_iter()
+            .map(|vs| {
+                let mut ids: Vec<usize> = Vec::new();
+                for a in vs.into_iter().map(|v| author_of[v]) {
+                    if !ids.contains(&a) {
+                        ids.push(a);
+                    }
+                }
+                ids
+            })
+            .collect();
+        for a in paper_authors.iter().flatten() {
+            authors[*a].papers += 1;
+        }
+
+        Self {
+            papers,
+            authors,
+            paper_authors,
+        }
+    }
+
+    pub fn papers(&self) -> &[AuthoredPaper] {
+        &self.papers
+    }
+
+    pub fn authors(&self) -> &[Author] {
+        &self.authors
+    }
+
+    /// Authors of paper `i`, in order.
+    pub fn authors_of(&self, i: usize) -> &[usize] {
+        &self.paper_authors[i]
+    }
+
+    /// The author spelled `name`, or its only author with a compatible
+    /// spelling.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        if let Some(a) = self
+            .authors
+            .iter()
+            .position(|a| a.variants.iter().any(|v| v == name))
+        {
+            return Some(a);
+        }
+        let (given, _) = split_name(name);
+        let key = author_key(name);
+        let mut found = self.authors.iter().enumerate().filter(|(_, a)| {
+            author_key(&a.name) == key && compatible(&given, &split_name(&a.name).0)
+        });
+        match (found.next(), found.next()) {
+            (Some((a, _)), None) => Some(a),
+            _ => None,
+        }
+    }
+}
+
+/// Papers of an author on a keyword.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorCount {
+    pub name: String,
+    pub papers: usize,
+    /// Of `papers`, those the author is first author of.
+    pub first_author: usize,
+}
+
+impl Display for AuthorCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} papers, {} as first author",
+            self.name, self.papers, self.first_author
+        )
+    }
+}
+
+/// An author whose first paper on a keyword is recent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entrant {
+    pub name: String,
+    /// Day of the author's first paper on the keyword.
+    pub first: NaiveDate,
+    /// Papers on the keyword since entering.
+    pub papers: usize,
+    /// Papers on other keywords before entering, 0 for newcomers to the field.
+    pub prior_papers: usize,
+}
+
+impl Display for Entrant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: since {}, {} papers, {} before",
+            self.name, self.first, self.papers, self.prior_papers
+        )
+    }
+}
+
+/// How an author's keywords changed over time.
+#[derive(Debug, Clone)]
+pub struct AuthorDrift {
+    pub name: String,
+    /// Keywords of the author's papers per bucket, the most frequent first.
+    pub windows: Vec<(NaiveDate, Vec<(String, usize)>)>,
+    /// `1 - cosine` of the keywords of the first and the last window, 0 for
+    /// an author sticking to their topics, 1 for a complete change.
+    pub drift: f64,
+}
+
+impl Display for AuthorDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let top = |i: usize| -> String {
+            let keywords: Vec<&str> = self.windows[i]
+                .1
+                .iter()
+                .take(3)
+                .map(|(k, _)| k.as_str())
+                .collect();
+            keywords.join(", ")
+        };
+        write!(
+            f,
+            "{}: drift {:.2}, from {} ({}) to {} ({})",
+            self.name,
+            self.drift,
+            top(0),
+            self.windows[0].0,
+            top(self.windows.len() - 1),
+            self.windows[self.windows.len() - 1].0
+        )
+    }
+}
+
+/// Who drives each trend, over an [`AuthorIndex`].
+#[derive(Builder, Clone, Debug)]
+pub struct AuthorAnalyzer {
+    /// Papers an author needs to be reported.
+    #[builder(default = "2")]
+    min_papers: usize,
+    /// Bucket length of the drift windows.
+    #[builder(default)]
+    granularity: Granularity,
+}
+
+impl AuthorAnalyzer {
+    /// Authors with the most papers on `keyword`.
+    pub fn top_authors(&self, index: &AuthorIndex, keyword: &str) -> Vec<AuthorCount> {
+        let mut counts: HashMap<usize, (usize, usize)> = HashMap::new();
+        for (i, paper) in index.papers().iter().enumerate() {
+            if !paper.keywords.iter().any(|k| k == keyword) {
+                continue;
+            }
+            for (position, a) in index.authors_of(i).iter().enumerate() {
+                let (papers, first) = counts.entry(*a).or_default();
+                *papers += 1;
+                *first += (position == 0) as usize;
+            }
+        }
+        let mut top: Vec<AuthorCount> = counts
+            .into_iter()
+            .filter(|(_, (papers, _))| *papers >= self.min_papers)
+            .map(|(a, (papers, first_author))| AuthorCount {
+                name: index.authors()[a].name.clone(),
+                papers,
+                first_author,
+            })
+            .collect();
+        top.sort_by(|a, b| {
+            b.papers
+                .cmp(&a.papers)
+                .then(b.first_author.cmp(&a.first_author))
+                .then(a.name.cmp(&b.name))
+        });
+        top
+    }
+
+    /// Authors whose first paper on `keyword` is on or after `since`, the
+    /// most prolific first. Papers without a date are ignored.
+    pub fn new_entrants(
+        &self,
+        index: &AuthorIndex,
+        keyword: &str,
+        since: NaiveDate,
+    ) -> Vec<Entrant> {
+        let mut first: HashMap<usize, NaiveDate> = HashMap::new();
+        for (i, paper) in index.papers().iter().enumerate() {
+            let Some(day) = paper.day else {
+                continue;
+            };
+            if paper.keywords.iter().any(|k| k == keyword) {
+                for a in index.authors_of(i) {
+                    let first = first.entry(*a).or_insert(day);
+                    *first = (*first).min(day);
+                }
+            }
+        }
+        let mut entrants: HashMap<usize, Entrant> = first
+            .into_iter()
+            .filter(|(_, day)| *day >= since)
+            .map(|(a, day)| {
+                let entrant = Entrant {
+                    name: index.authors()[a].name.clone(),
+                    first: day,
+                    papers: 0,
+                    prior_papers: 0,
+                };
+                (a, entrant)
+            })
+            .collect();
+        for (i, paper) in index.papers().iter().enumerate() {
+            let Some(day) = paper.day else {
+                continue;
+            };
+            let on_keyword = paper.keywords.iter().any(|k| k == keyword);
+            for a in index.authors_of(i) {
+                if let Some(entrant) = entrants.get_mut(a) {
+                    if on_keyword {
+                        entrant.papers += 1;
+                    } else if day < entrant.first {
+                        entrant.prior_papers += 1;
+                    }
+                }
+            }
+        }
+        let mut entrants: Vec<Entrant> = entrants
+            .into_values()
+            .filter(|e| e.papers >= self.min_papers)
+            .collect();
+        entrants.sort_by(|a, b| {
+            b.papers
+                .cmp(&a.papers)
+                .then(a.first.cmp(&b.first))
+                .then(a.name.cmp(&b.name))
+        });
+        entrants
+    }
+
+    /// Co-authorship graph of the authors with `min_papers` papers, with
+    /// its research groups as communities, see
+    /// [`CooccurrenceAnalyzer`](super::graph::CooccurrenceAnalyzer). Nodes
+    /// are named by author.
+    pub fn collaborations(&self, index: &AuthorIndex, weight: EdgeWeight) -> CooccurrenceGraph {
+        let papers: Vec<Vec<&str>> = (0..index.papers().len())
+            .map(|i| {
+                index
+                    .authors_of(i)
+                    .iter()
+                    .map(|a| index.authors()[*a].name.as_str())
+                    .collect()
+            })
+            .collect();
+        CooccurrenceAnalyzerBuilder::default()
+            .weight(weight)
+            .min_papers(self.min_papers)
+            .min_cooccurrence(1)
+            .build()
+            .unwrap()
+            .graph(&papers)
+    }
+
+    /// Authors with `min_papers` papers in at least two buckets, the ones
+    /// whose keywords changed the most first.
+    pub fn drift(&self, index: &AuthorIndex) -> Vec<AuthorDrift> {
+        // keywords of each author per bucket
+        let mut windows: HashMap<usize, BTreeMap<NaiveDate, BTreeMap<&str, usize>>> =
+            HashMap::new();
+        for (i, paper) in index.papers().iter().enumerate() {
+            let Some(day) = paper.day else {
+                continue;
+            };
+            let bucket = self.granularity.bucket(day);
+            for a in index.authors_of(i) {
+                let keywords = windows.entry(*a).or_default().entry(bucket).or_default();
+                for keyword in paper.keywords.iter() {
+                    *keywords.entry(keyword.as_str()).or_default() += 1;
+                }
+            }
+        }
+
+        let mut drifts: Vec<AuthorDrift> = windows
+            .into_iter()
+            .filter(|(a, windows)| {
+                windows.len() >= 2 && index.authors()[*a].papers >= self.min_papers
+            })
+            .map(|(a, windows)| {
+                let (first, last) = (
+                    windows.values().next().unwrap(),
+                    windows.values().next_back().unwrap(),
+                );
+                let vocabulary: BTreeSet<&str> = first.keys().chain(last.keys()).copied().collect();
+                let vector = |keywords: &BTreeMap<&str, usize>| -> Vec<f64> {
+                    vocabulary
+                        .iter()
+                        .map(|k| keywords.get(k).copied().unwrap_or(0) as f64)
+                        .collect()
+                };
+                let drift = 1.0 - cosine(&vector(first), &vector(last));
+                let windows = windows
+                    .into_iter()
+                    .map(|(bucket, keywords)| {
+                        let mut keywords: Vec<(String, usize)> = keywords
+                            .into_iter()
+                            .map(|(k, n)| (k.to_string(), n))
+                            .collect();
+                        keywords.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+                        (bucket, keywords)
+                    })
+                    .collect();
+                AuthorDrift {
+                    name: index.authors()[a].name.clone(),
+                    windows,
+                    drift,
+                }
+            })
+            .collect();
+        drifts.sort_by(|a, b| {
+            b.drift
+                .partial_cmp(&a.drift)
+                .unwrap_or(Ordering::Equal)
+                .then(a.name.cmp(&b.name))
+        });
+        drifts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{author_key, split_name, AuthorIndex, AuthoredPaper};
+
+    #[test]
+    fn test_split_name() {
+        let name = |given: &[&str], family: &str| {
+            (
+                given.iter().map(|g| g.to_string()).collect::<Vec<_>>(),
+                family.to_string(),
+            )
+        };
+        assert_eq!(split_name("John A. Smith"), name(&["john", "a"], "smith"));
+        assert_eq!(split_name("Smith, John A."), name(&["john", "a"], "smith"));
+        assert_eq!(
+            split_name("Jan van der Berg"),
+            name(&["jan"], "van der berg")
+        );
+        assert_eq!(split_name("J.-P. Éluard"), name(&["j", "p"], "eluard"));
+        assert_eq!(author_key("José  Muñoz"), "j munoz");
+        assert_eq!(author_key("Plato"), "plato");
+    }
+
+    #[test]
+    fn test_disambiguation() {
+        let paper = |authors: &[&str]| AuthoredPaper {
+            authors: authors.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        };
+        let index = AuthorIndex::new(vec![
+            paper(&["John Smith", "Ada Lovelace"]),
+            paper(&["J. Smith", "A. Lovelace"]),
+            paper(&["Jane Smith", "Grace Hopper"]),
+            paper(&["J. R. Smith", "Alan Turing"]),
+            paper(&["Kurt Gödel"]),
+            paper(&["K. Godel"]),
+        ]);
+        let find = |name: &str| index.find(name).unwrap();
+
+        // a shared co-author tells which J. Smith is meant, J. R. Smith
+        // could be either
+        assert_eq!(find("John Smith"), index.authors_of(1)[0]);
+        assert_ne!(find("Jane Smith"), find("John Smith"));
+        assert_ne!(index.authors_of(3)[0], find("John Smith"));
+        assert_ne!(index.authors_of(3)[0], find("Jane Smith"));
+        assert_eq!(find("A. Lovelace"), find("Ada Lovelace"));
+        assert_eq!(index.authors()[find("A. Lovelace")].name, "Ada Lovelace");
+        // the only compatible spelling
+        assert_eq!(find("K. Godel"), find("Kurt Gödel"));
+        assert_eq!(index.authors()[find("Kurt Gödel")].papers, 2);
+        assert!(index.find("J. Smith").is_some());
+    }
+}
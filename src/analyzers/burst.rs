@@ -0,0 +1,228 @@
+use std::{cmp::Ordering, collections::BTreeMap, fmt::Display};
+
+use arxiv::Arxiv;
+use chrono::NaiveDate;
+use derive_builder::Builder;
+use sqlx::SqlitePool;
+
+use crate::{dao::PaperDAO, extracters::topic::ArxivTopicData};
+
+use super::trend::{daily_counts, parse_day, Granularity, KeywordSeries};
+
+/// Papers per day published since `since`, from the database.
+pub async fn daily_totals(
+    pool: &SqlitePool,
+    since: NaiveDate,
+) -> Result<Vec<(NaiveDate, usize)>, sqlx::Error> {
+    Ok(
+        PaperDAO::daily_counts(pool, &since.format("%Y-%m-%d").to_string())
+            .await?
+            .into_iter()
+            .filter_map(|c| Some((parse_day(&c.day)?, c.papers as usize)))
+            .collect(),
+    )
+}
+
+/// Papers per day published, from extracted papers.
+pub fn daily_totals_of(data: &[(Arxiv, ArxivTopicData)]) -> Vec<(NaiveDate, usize)> {
+    let mut totals: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    for day in data.iter().filter_map(|(raw, _)| parse_day(&raw.published)) {
+        *totals.entry(day).or_default() += 1;
+    }
+    totals.into_iter().collect()
+}
+
+/// Days a keyword appeared in unusually many papers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Burst {
+    pub keyword: String,
+    pub start: NaiveDate,
+    /// Last day of the burst, included.
+    pub end: NaiveDate,
+    /// Papers with the keyword during the burst.
+    pub papers: usize,
+    /// How much better the burst state explains these days than the base
+    /// state, Kleinberg's weight of the burst.
+    pub intensity: f64,
+}
+
+impl Display for Burst {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} to {}, {} papers, intensity {:.1}",
+            self.keyword, self.start, self.end, self.papers, self.intensity
+        )
+    }
+}
+
+/// Kleinberg's two-state burst detection over daily counts (Kleinberg,
+/// "Bursty and Hierarchical Structure in Streams", 2002, batched arrivals).
+///
+/// Each day, the share of papers with a keyword is drawn from a base state at
+/// the keyword's overall share or from a burst state `s` times higher. The
+/// cheapest state sequence is found with Viterbi, entering the burst state
+/// costing `gamma` times the log of the number of days; runs in the burst
+/// state are the bursts.
+#[derive(Builder, Clone, Debug)]
+pub struct BurstDetector {
+    /// How many times more frequent a keyword is in a burst.
+    #[builder(default = "2.0")]
+    s: f64,
+    /// Cost of entering a burst, higher for fewer and longer bursts.
+    #[builder(default = "1.0")]
+    gamma: f64,
+    /// Papers a burst needs to be reported.
+    #[builder(default = "3")]
+    min_papers: usize,
+}
+
+impl BurstDetector {
+    /// Bursts of a keyword appearing in `counts[t]` of the `totals[t]` papers
+    /// of day `t`, as first and last day indices with their intensities.
+    pub fn detect_series(&self, counts: &[usize], totals: &[usize]) -> Vec<(usize, usize, f64)> {
+        let n = counts.len().min(totals.len());
+        let (relevant, total) = (
+            counts[..n].iter().sum::<usize>(),
+            totals[..n].iter().sum::<usize>(),
+        );
+        if n == 0 || relevant == 0 || total == 0 {
+            return Vec::new();
+        }
+        let p0 = relevant as f64 / total as f64;
+        let p1 = (self.s * p0).min(0.9999);
+        if p1 <= p0 {
+            return Vec::new();
+        }
+        // -ln of the likelihood of the day, the binomial coefficient is the
+        // same in both states and left out
+        let cost = |p: f64, t: usize| {
+            let (r, d) = (counts[t] as f64, totals[t].max(counts[t]) as f64);
+            -(r * p.ln() + (d - r) * (1.0 - p).ln())
+        };
+        let enter = self.gamma * (n as f64).ln();
+
+        // cheapest cost of ending each day in each state, and the state of
+        // the day before on that path; leaving a burst is free
+        let mut best = [cost(p0, 0), enter + cost(p1, 0)];
+        let mut from = vec![[0usize; 2]; n];
+        for (t, from) in from.iter_mut().enumerate().skip(1) {
+            let base = if best[0] <= best[1] {
+                (best[0], 0)
+            } else {
+                (best[1], 1)
+            };
+            let burst = if best[1] <= best[0] + enter {
+                (best[1], 1)
+            } else {
+                (best[0] + enter, 0)
+            };
+            best = [base.0 + cost(p0, t), burst.0 + cost(p1, t)];
+            *from = [base.1, burst.1];
+        }
+
+        let mut states = vec![0usize; n];
+        let mut state = if best[1] < best[0] { 1 } else { 0 };
+        for t in (0..n).rev() {
+            states[t] = state;
+            state = from[t][state];
+        }
+
+        let mut bursts = Vec::new();
+        let mut t = 0;
+        while t < n {
+            if states[t] == 0 {
+                t += 1;
+                continue;
+            }
+            let start = t;
+            while t < n && states[t] == 1 {
+                t += 1;
+            }
+            let intensity = (start..t).map(|t| cost(p0, t) - cost(p1, t)).sum();
+            bursts.push((start, t - 1, intensity));
+        }
+        bursts
+    }
+
+    /// Bursts of every keyword of `counts`, the most intense first. Days
+    /// run from the first to the last day of `totals`.
+    pub fn detect(
+        &self,
+        counts: &[(String, NaiveDate, usize)],
+        totals: &[(NaiveDate, usize)],
+    ) -> Vec<Burst> {
+        let (Some(from), Some(until)) = (
+            totals.iter().map(|(day, _)| *day).min(),
+            totals.iter().map(|(day, _)| *day).max(),
+        ) else {
+            return Vec::new();
+        };
+        let series = KeywordSeries::new(Granularity::Day, from, until, counts);
+        let days = series.buckets();
+        let mut daily = vec![0; days.len()];
+        for (day, papers) in totals {
+            if let Ok(i) = days.binary_search(day) {
+                daily[i] += papers;
+            }
+        }
+
+        let mut bursts: Vec<Burst> = series
+            .iter()
+            .flat_map(|(keyword, counts)| {
+                self.detect_series(counts, &daily).into_iter().map(
+                    move |(start, end, intensity)| Burst {
+                        keyword: keyword.to_string(),
+                        start: days[start],
+                        end: days[end],
+                        papers: counts[start..=end].iter().sum(),
+                        intensity,
+                    },
+                )
+            })
+            .filter(|burst| burst.papers >= self.min_papers)
+            .collect();
+        bursts.sort_by(|a, b| {
+            b.intensity
+                .partial_cmp(&a.intensity)
+                .unwrap_or(Ordering::Equal)
+                .then(a.keyword.cmp(&b.keyword))
+                .then(a.start.cmp(&b.start))
+        });
+        bursts
+    }
+
+    /// Bursts among the papers stored since `since`.
+    pub async fn detect_stored(
+        &self,
+        pool: &SqlitePool,
+        since: NaiveDate,
+    ) -> Result<Vec<Burst>, sqlx::Error> {
+        let counts = daily_counts(pool, since).await?;
+        let totals = daily_totals(pool, since).await?;
+        Ok(self.detect(&counts, &totals))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BurstDetectorBuilder;
+
+    #[test]
+    fn test_detect_series() {
+        let detector = BurstDetectorBuilder::default().build().unwrap();
+        let totals = vec![20; 30];
+        let mut counts = vec![1; 30];
+        counts[12..17].copy_from_slice(&[8, 10, 9, 11, 7]);
+
+        let bursts = detector.detect_series(&counts, &totals);
+        assert_eq!(bursts.len(), 1);
+        assert_eq!((bursts[0].0, bursts[0].1), (12, 16));
+        assert!(bursts[0].2 > 0.0);
+
+        // steady keywords and missing ones never burst
+        assert!(detector.detect_series(&[2; 30], &totals).is_empty());
+        assert!(detector.detect_series(&[0; 30], &totals).is_empty());
+        assert!(detector.detect_series(&[], &[]).is_empty());
+    }
+}
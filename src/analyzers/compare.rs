@@ -0,0 +1,372 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Display,
+    str::FromStr,
+};
+
+use arxiv::Arxiv;
+use chrono::{Datelike, Days, Months, NaiveDate};
+use derive_builder::Builder;
+use sqlx::SqlitePool;
+
+use crate::dao::PaperKeywordDAO;
+
+use super::{trend::parse_day, TopK, TrendingAnalyzer, TrendingKeyword};
+
+/// Days from `from` to `until`, both included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub from: NaiveDate,
+    pub until: NaiveDate,
+}
+
+impl Period {
+    pub fn new(from: NaiveDate, until: NaiveDate) -> Self {
+        Self { from, until }
+    }
+
+    pub fn contains(&self, day: NaiveDate) -> bool {
+        self.from <= day && day <= self.until
+    }
+
+    /// Papers of `data` published during the period.
+    pub fn select<C: Clone>(&self, data: &[(Arxiv, C)]) -> Vec<(Arxiv, C)> {
+        data.iter()
+            .filter(|(raw, _)| parse_day(&raw.published).is_some_and(|day| self.contains(day)))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.from, self.until)
+    }
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    /// A year `2025`, a month `2025-03`, a day `2025-03-14` or a range of
+    /// days `2025-03-01..2025-03-14`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let day = |s: &str| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| format!("invalid day {:?}", s))
+        };
+        let period = if let Some((from, until)) = s.split_once("..") {
+            Self::new(day(from)?, day(until)?)
+        } else if let Ok(year) = s.parse::<i32>() {
+            let from = NaiveDate::from_ymd_opt(year, 1, 1)
+                .ok_or_else(|| format!("invalid year {:?}", s))?;
+            Self::new(from, from.with_month(12).unwrap().with_day(31).unwrap())
+        } else if let Ok(from) = day(&format!("{}-01", s)) {
+            Self::new(from, from + Months::new(1) - Days::new(1))
+        } else {
+            let day = day(s)?;
+            Self::new(day, day)
+        };
+        if period.from > period.until {
+            return Err(format!("period {:?} ends before it starts", s));
+        }
+        Ok(period)
+    }
+}
+
+/// How a keyword changed from one period to the other.
+#[derive(Debug, Clone)]
+pub struct KeywordChange {
+    pub keyword: String,
+    /// Papers with the keyword in the first period.
+    pub before: usize,
+    /// Papers with the keyword in the second period.
+    pub after: usize,
+    /// Share of the second period's papers minus share of the first's.
+    pub change: f64,
+    /// Papers with the keyword, from the second period if it is there,
+    /// from the first otherwise.
+    pub papers: Vec<Arxiv>,
+}
+
+impl Display for KeywordChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {} papers ({:+.1}%)",
+            self.keyword,
+            self.before,
+            self.after,
+            self.change * 100.0
+        )
+    }
+}
+
+/// Keywords of two periods compared.
+#[derive(Debug, Clone, Default)]
+pub struct Comparison {
+    /// Papers in the first period.
+    pub before_papers: usize,
+    /// Papers in the second period.
+    pub after_papers: usize,
+    /// Keywords only in the second period, the most frequent first.
+    pub new: Vec<KeywordChange>,
+    /// Keywords only in the first period, the most frequent first.
+    pub vanished: Vec<KeywordChange>,
+    /// Keywords in both periods whose share grew, the most first.
+    pub risers: Vec<KeywordChange>,
+    /// Keywords in both periods whose share shrank, the most first.
+    pub fallers: Vec<KeywordChange>,
+}
+
+impl Comparison {
+    /// The report with at most `k` keywords of each kind.
+    pub fn truncated(mut self, k: usize) -> Self {
+        self.new.truncate(k);
+        self.vanished.truncate(k);
+        self.risers.truncate(k);
+        self.fallers.truncate(k);
+        self
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} -> {} papers", self.before_papers, self.after_papers)?;
+        for (title, changes) in [
+            ("New", &self.new),
+            ("Vanished", &self.vanished),
+            ("Risers", &self.risers),
+            ("Fallers", &self.fallers),
+        ] {
+            if changes.is_empty() {
+                continue;
+            }
+            writeln!(f, "{}:", title)?;
+            for change in changes {
+                writeln!(f, "  {}", change)?;
+                for paper in change.papers.iter() {
+                    writeln!(f, "    - {}", paper.title)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares the keywords of two periods, this month and the last one or
+/// two editions of a conference, from the output of a [`TrendingAnalyzer`]
+/// or the stored keywords.
+#[derive(Builder, Clone, Debug)]
+pub struct PeriodComparer {
+    /// Papers a keyword needs in one of the periods to be reported.
+    #[builder(default = "2")]
+    min_papers: usize,
+    /// Representative papers kept per keyword.
+    #[builder(default = "3")]
+    examples: usize,
+}
+
+impl PeriodComparer {
+    /// Compares keywords with their papers, as [`TrendingAnalyzer::trends`]
+    /// returns them, of periods of `before_papers` and `after_papers` papers.
+    pub fn compare<P: Borrow<Arxiv>>(
+        &self,
+        before: &[TrendingKeyword<P>],
+        before_papers: usize,
+        after: &[TrendingKeyword<P>],
+        after_papers: usize,
+    ) -> Comparison {
+        let share = |count: usize, papers: usize| match papers {
+            0 => 0.0,
+            papers => count as f64 / papers as f64,
+        };
+        let before: HashMap<&str, &Vec<P>> = before
+            .iter()
+            .map(|t| (t.keyword.as_str(), &t.papers))
+            .collect();
+        let after: HashMap<&str, &Vec<P>> = after
+            .iter()
+            .map(|t| (t.keyword.as_str(), &t.papers))
+            .collect();
+        let keywords: BTreeSet<&str> = before.keys().chain(after.keys()).copied().collect();
+
+        let mut comparison = Comparison {
+            before_papers,
+            after_papers,
+            ..Default::default()
+        };
+        for keyword in keywords {
+            let (b, a) = (
+                before.get(keyword).map_or(0, |p| p.len()),
+                after.get(keyword).map_or(0, |p| p.len()),
+            );
+            if b.max(a) < self.min_papers {
+                continue;
+            }
+            let papers = after.get(keyword).or(before.get(keyword)).unwrap();
+            let change = KeywordChange {
+                keyword: keyword.to_string(),
+                before: b,
+                after: a,
+                change: share(a, after_papers) - share(b, before_papers),
+                papers: papers
+                    .iter()
+                    .take(self.examples)
+                    .map(|p| p.borrow().clone())
+                    .collect(),
+            };
+            match (b, a) {
+                (0, _) => comparison.new.push(change),
+                (_, 0) => comparison.vanished.push(change),
+                _ if change.change > 0.0 => comparison.risers.push(change),
+                _ if change.change < 0.0 => comparison.fallers.push(change),
+                _ => {}
+            }
+        }
+
+        let by_change = |a: &KeywordChange, b: &KeywordChange| {
+            b.change
+                .abs()
+                .partial_cmp(&a.change.abs())
+                .unwrap_or(Ordering::Equal)
+                .then(a.keyword.cmp(&b.keyword))
+        };
+        comparison.new.sort_by(by_change);
+        comparison.vanished.sort_by(by_change);
+        comparison.risers.sort_by(by_change);
+        comparison.fallers.sort_by(by_change);
+        comparison
+    }
+
+    /// Compares the keywords `f` picks out of two sets of papers, counted by
+    /// `analyzer`.
+    pub fn compare_with<'a, A, F>(
+        &self,
+        analyzer: &A,
+        before: &'a [(Arxiv, A::Ctx)],
+        after: &'a [(Arxiv, A::Ctx)],
+        f: F,
+    ) -> Comparison
+    where
+        A: TrendingAnalyzer<Raw = Arxiv>,
+        F: Fn(&A::Ctx) -> Vec<String>,
+    {
+        let trends = |data: &'a [(Arxiv, A::Ctx)]| {
+            analyzer.trends(
+                data.iter().map(|(raw, ctx)| (raw, raw, ctx)),
+                &f,
+                TopK::default(),
+            )
+        };
+        self.compare(&trends(before), before.len(), &trends(after), after.len())
+    }
+
+    /// Compares the stored keywords of papers published during `before` and
+    /// `after`. Papers without keywords are not counted.
+    pub async fn compare_stored(
+        &self,
+        pool: &SqlitePool,
+        before: Period,
+        after: Period,
+    ) -> Result<Comparison, sqlx::Error> {
+        let (before, before_papers) = stored_keywords(pool, before).await?;
+        let (after, after_papers) = stored_keywords(pool, after).await?;
+        Ok(self.compare(&before, before_papers, &after, after_papers))
+    }
+}
+
+/// Stored keywords of the papers of `period` with the papers, and how many
+/// papers there are.
+async fn stored_keywords(
+    pool: &SqlitePool,
+    period: Period,
+) -> Result<(Vec<TrendingKeyword<Arxiv>>, usize), sqlx::Error> {
+    let rows = PaperKeywordDAO::keyword_papers(
+        pool,
+        &period.from.format("%Y-%m-%d").to_string(),
+        &period.until.format("%Y-%m-%d").to_string(),
+    )
+    .await?;
+    let papers = rows
+        .iter()
+        .map(|row| row.url.as_str())
+        .collect::<BTreeSet<_>>()
+        .len();
+    let mut keywords: BTreeMap<String, Vec<Arxiv>> = BTreeMap::new();
+    for row in rows {
+        keywords.entry(row.keyword).or_default().push(Arxiv {
+            id: row.url.clone(),
+            title: row.title,
+            published: row.published,
+            pdf_url: row.url,
+            ..Default::default()
+        });
+    }
+    let trends = keywords
+        .into_iter()
+        .map(|(keyword, papers)| TrendingKeyword {
+            keyword,
+            score: papers.len() as f64,
+            papers,
+        })
+        .collect();
+    Ok((trends, papers))
+}
+
+#[cfg(test)]
+mod test {
+    use arxiv::Arxiv;
+    use chrono::NaiveDate;
+
+    use super::{Period, PeriodComparerBuilder, TrendingKeyword};
+
+    #[test]
+    fn test_period() {
+        let day = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        assert_eq!("2024".parse(), Ok(Period::new(day(1, 1), day(12, 31))));
+        assert_eq!("2024-02".parse(), Ok(Period::new(day(2, 1), day(2, 29))));
+        assert_eq!("2024-12".parse(), Ok(Period::new(day(12, 1), day(12, 31))));
+        assert_eq!(
+            "2024-04-15".parse(),
+            Ok(Period::new(day(4, 15), day(4, 15)))
+        );
+        assert_eq!(
+            "2024-04-15..2024-04-19".parse(),
+            Ok(Period::new(day(4, 15), day(4, 19)))
+        );
+        assert!("2024-04-19..2024-04-15".parse::<Period>().is_err());
+        assert!("last month".parse::<Period>().is_err());
+    }
+
+    #[test]
+    fn test_compare() {
+        let trend = |keyword: &str, n: usize| TrendingKeyword {
+            keyword: keyword.to_string(),
+            score: n as f64,
+            papers: vec![Arxiv::default(); n],
+        };
+        let before = vec![
+            trend("LLM", 2),
+            trend("fuzzing", 5),
+            trend("SAT", 3),
+            trend("rare", 1),
+        ];
+        let after = vec![trend("LLM", 6), trend("fuzzing", 2), trend("agents", 4)];
+        let comparison = PeriodComparerBuilder::default()
+            .examples(2)
+            .build()
+            .unwrap()
+            .compare(&before, 10, &after, 10);
+        let keywords = |changes: &[super::KeywordChange]| -> Vec<String> {
+            changes.iter().map(|c| c.keyword.clone()).collect()
+        };
+        assert_eq!(keywords(&comparison.new), vec!["agents"]);
+        // rare is below min_papers
+        assert_eq!(keywords(&comparison.vanished), vec!["SAT"]);
+        assert_eq!(keywords(&comparison.risers), vec!["LLM"]);
+        assert_eq!(keywords(&comparison.fallers), vec!["fuzzing"]);
+        assert!((comparison.risers[0].change - 0.4).abs() < 1e-9);
+        assert_eq!(comparison.risers[0].papers.len(), 2);
+    }
+}
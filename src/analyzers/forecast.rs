@@ -0,0 +1,608 @@
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
+
+use chrono::{Local, NaiveDate};
+use derive_builder::Builder;
+use sqlx::SqlitePool;
+
+use super::trend::{daily_counts, Granularity, KeywordSeries};
+
+/// How the counts of a keyword are extrapolated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ForecastMethod {
+    /// Exponential smoothing of the level and the trend of the counts.
+    #[default]
+    Holt,
+    /// Holt's method with a seasonal component, a year of buckets long, or
+    /// a week for days. Falls back to Holt's method on less than two seasons.
+    HoltWinters,
+    /// A straight line through the log of the counts, constant growth.
+    LogLinear,
+}
+
+impl ForecastMethod {
+    pub const ALL: [ForecastMethod; 3] = [Self::Holt, Self::HoltWinters, Self::LogLinear];
+}
+
+impl FromStr for ForecastMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "holt" => Ok(Self::Holt),
+            "holt-winters" => Ok(Self::HoltWinters),
+            "log-linear" => Ok(Self::LogLinear),
+            _ => Err(format!(
+                "unknown forecast method {:?}, use holt, holt-winters or log-linear",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for ForecastMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Holt => write!(f, "holt"),
+            Self::HoltWinters => write!(f, "holt-winters"),
+            Self::LogLinear => write!(f, "log-linear"),
+        }
+    }
+}
+
+/// Buckets in a season of `granularity`.
+fn season(granularity: Granularity) -> usize {
+    match granularity {
+        Granularity::Day => 7,
+        Granularity::Week => 52,
+        Granularity::Month => 12,
+    }
+}
+
+/// Standard normal quantile of `p`, within 5e-4 (Abramowitz and Stegun
+/// 26.2.23).
+fn normal_quantile(p: f64) -> f64 {
+    let tail = |p: f64| {
+        let t = (-2.0 * p.ln()).sqrt();
+        t - (2.515517 + 0.802853 * t + 0.010328 * t * t)
+            / (1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t)
+    };
+    match p.partial_cmp(&0.5) {
+        Some(Ordering::Less) => -tail(p),
+        Some(Ordering::Greater) => tail(1.0 - p),
+        _ => 0.0,
+    }
+}
+
+/// Expected papers in one future bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForecastPoint {
+    /// First day of the bucket.
+    pub bucket: NaiveDate,
+    pub papers: f64,
+    /// Bounds of the prediction interval.
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Projected papers of a keyword over the next buckets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Forecast {
+    pub keyword: String,
+    pub method: ForecastMethod,
+    /// Papers in as many buckets as forecast, up to the last one counted.
+    pub recent: usize,
+    pub points: Vec<ForecastPoint>,
+}
+
+impl Forecast {
+    /// Papers forecast over all the buckets.
+    pub fn total(&self) -> f64 {
+        self.points.iter().map(|p| p.papers).sum()
+    }
+
+    /// Relative change of the forecast total over `recent`, `None` when the
+    /// keyword had no recent papers.
+    pub fn growth(&self) -> Option<f64> {
+        (self.recent > 0).then(|| self.total() / self.recent as f64 - 1.0)
+    }
+}
+
+impl Display for Forecast {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let growth = match self.growth() {
+            Some(growth) => format!("{:+.0}%", growth * 100.0),
+            None => "new".to_string(),
+        };
+        write!(
+            f,
+            "{}: {:.1} papers vs {}, {}",
+            self.keyword,
+            self.total(),
+            self.recent,
+            growth
+        )?;
+        for point in self.points.iter() {
+            write!(
+                f,
+                "\n  {} {:.1} [{:.1}, {:.1}]",
+                point.bucket, point.papers, point.lower, point.upper
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Accuracy of a method forecasting the buckets that followed past
+/// windows of the counts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backtest {
+    pub method: ForecastMethod,
+    /// Buckets forecast, over all keywords and windows.
+    pub forecasts: usize,
+    /// Mean absolute error, in papers per bucket.
+    pub mae: f64,
+    /// `mae` over the one of repeating the last count, lower than 1.0 for a
+    /// method beating that, `None` for counts that never change.
+    pub mase: Option<f64>,
+    /// Share of the counts within the prediction intervals.
+    pub coverage: f64,
+}
+
+impl Display for Backtest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mase = match self.mase {
+            Some(mase) => format!("{:.2}", mase),
+            None => "-".to_string(),
+        };
+        write!(
+            f,
+            "{}: MAE {:.2}, MASE {}, {:.0}% in intervals, {} forecasts",
+            self.method,
+            self.mae,
+            mase,
+            self.coverage * 100.0,
+            self.forecasts
+        )
+    }
+}
+
+/// A model fit to one series, predicting the mean and variance `h` buckets
+/// after its last count.
+trait Fitted {
+    fn mean(&self, h: usize) -> f64;
+    /// Prediction interval `z` standard deviations wide.
+    fn interval(&self, h: usize, z: f64) -> (f64, f64);
+}
+
+/// Additive exponential smoothing in error-correction form, with a trend and
+/// an optional season of `m` buckets.
+struct Smoothing {
+    alpha: f64,
+    beta: f64,
+    gamma: f64,
+    level: f64,
+    trend: f64,
+    /// Seasonal terms by bucket index modulo their number, empty without a
+    /// season.
+    season: Vec<f64>,
+    /// Buckets fit.
+    n: usize,
+    /// Variance of the one-step errors.
+    variance: f64,
+}
+
+impl Smoothing {
+    /// Parameters minimizing the one-step squared error over a small grid.
+    /// Needs 3 counts, or two seasons with `m`.
+    fn fit(y: &[f64], m: usize) -> Option<Self> {
+        if y.len() < 3 || (m > 0 && y.len() < 2 * m) {
+            return None;
+        }
+        let gammas: &[f64] = if m > 0 {
+            &[0.05, 0.1, 0.2, 0.4]
+        } else {
+            &[0.0]
+        };
+        let mut best: Option<(f64, Smoothing)> = None;
+        for a in 1..=9 {
+            let alpha = a as f64 / 10.0;
+            for k in [0.02, 0.1, 0.25, 0.5] {
+                for &gamma in gammas.iter().filter(|&&g| g < 1.0 - alpha) {
+                    let (sse, smoothing) = Self::run(y, m, alpha, alpha * k, gamma);
+                    if best.as_ref().is_none_or(|(min, _)| sse < *min) {
+                        best = Some((sse, smoothing));
+                    }
+                }
+            }
+        }
+        best.map(|(_, smoothing)| smoothing)
+    }
+
+    fn run(y: &[f64], m: usize, alpha: f64, beta: f64, gamma: f64) -> (f64, Self) {
+        let mean = |s: &[f64]| s.iter().sum::<f64>() / s.len() as f64;
+        let (start, mut level, mut trend, mut season) = if m > 0 {
+            let (first, second) = (mean(&y[..m]), mean(&y[m..2 * m]));
+            let trend = (second - first) / m as f64;
+            let season = y[..m].iter().map(|v| v - first).collect();
+            // the level of the first season's middle, moved to its end
+            (m, first + trend * (m - 1) as f64 / 2.0, trend, season)
+        } else {
+            (2, y[1], y[1] - y[0], Vec::new())
+        };
+        let mut sse = 0.0;
+        for (t, &value) in y.iter().enumerate().skip(start) {
+            let s = if m > 0 { season[t % m] } else { 0.0 };
+            let error = value - (level + trend + s);
+            sse += error * error;
+            level += trend + alpha * error;
+            trend += beta * error;
+            if m > 0 {
+                season[t % m] += gamma * error;
+            }
+        }
+        let smoothing = Self {
+            alpha,
+            beta,
+            gamma,
+            level,
+            trend,
+            season,
+            n: y.len(),
+            variance: sse / (y.len() - start) as f64,
+        };
+        (sse, smoothing)
+    }
+}
+
+impl Fitted for Smoothing {
+    fn mean(&self, h: usize) -> f64 {
+        let m = self.season.len();
+        let s = if m > 0 {
+            self.season[(self.n - 1 + h) % m]
+        } else {
+            0.0
+        };
+        self.level + h as f64 * self.trend + s
+    }
+
+    fn interval(&self, h: usize, z: f64) -> (f64, f64) {
+        let m = self.season.len();
+        let spread: f64 = (1..h)
+            .map(|j| {
+                let seasonal = if m > 0 && j % m == 0 { self.gamma } else { 0.0 };
+                (self.alpha + self.beta * j as f64 + seasonal).powi(2)
+            })
+            .sum();
+        let deviation = (self.variance * (1.0 + spread)).sqrt();
+        let mean = self.mean(h);
+        (mean - z * deviation, mean + z * deviation)
+    }
+}
+
+/// Least squares line through `ln(1 + count)`.
+struct LogLinear {
+    intercept: f64,
+    slope: f64,
+    n: usize,
+    /// Mean and spread of the bucket indices.
+    t_mean: f64,
+    t_squares: f64,
+    /// Variance of the residuals.
+    variance: f64,
+}
+
+impl LogLinear {
+    /// Needs 3 counts.
+    fn fit(y: &[f64]) -> Option<Self> {
+        let n = y.len();
+        if n < 3 {
+            return None;
+        }
+        let logs: Vec<f64> = y.iter().map(|v| v.max(0.0).ln_1p()).collect();
+        let t_mean = (n - 1) as f64 / 2.0;
+        let y_mean = logs.iter().sum::<f64>() / n as f64;
+        let t_squares: f64 = (0..n).map(|t| (t as f64 - t_mean).powi(2)).sum();
+        let slope = logs
+            .iter()
+            .enumerate()
+            .map(|(t, v)| (t as f64 - t_mean) * (v - y_mean))
+            .sum::<f64>()
+            / t_squares;
+        let intercept = y_mean - slope * t_mean;
+        let residuals: f64 = logs
+            .iter()
+            .enumerate()
+            .map(|(t, v)| (v - intercept - slope * t as f64).powi(2))
+            .sum();
+        Some(Self {
+            intercept,
+            slope,
+            n,
+            t_mean,
+            t_squares,
+            variance: residuals / (n - 2) as f64,
+        })
+    }
+
+    fn log_mean(&self, h: usize) -> f64 {
+        self.intercept + self.slope * (self.n - 1 + h) as f64
+    }
+}
+
+impl Fitted for LogLinear {
+    fn mean(&self, h: usize) -> f64 {
+        self.log_mean(h).exp_m1()
+    }
+
+    fn interval(&self, h: usize, z: f64) -> (f64, f64) {
+        let t = (self.n - 1 + h) as f64;
+        let deviation = (self.variance
+            * (1.0 + 1.0 / self.n as f64 + (t - self.t_mean).powi(2) / self.t_squares))
+            .sqrt();
+        let mean = self.log_mean(h);
+        (
+            (mean - z * deviation).exp_m1(),
+            (mean + z * deviation).exp_m1(),
+        )
+    }
+}
+
+/// Projects the paper counts of each keyword over the next `horizon`
+/// buckets, e.g. the next quarter with months, ranking the keywords
+/// expected to grow the most first.
+///
+/// Intervals assume normal errors, the log-linear ones on the log scale.
+/// Counts cannot be negative, so forecasts and bounds are floored at 0.
+#[derive(Builder, Clone, Debug)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct KeywordForecaster {
+    #[builder(default = "Granularity::Month")]
+    granularity: Granularity,
+    #[builder(default)]
+    method: ForecastMethod,
+    /// Buckets forecast, at least one.
+    #[builder(default = "3")]
+    horizon: usize,
+    /// Probability of a count falling within its prediction interval,
+    /// strictly between 0 and 1.
+    #[builder(default = "0.8")]
+    level: f64,
+    /// Papers a keyword needs over the counts to be forecast.
+    #[builder(default = "10")]
+    min_papers: usize,
+}
+
+impl KeywordForecasterBuilder {
+    fn validate(&self) -> Result<(), String> {
+        if self.horizon == Some(0) {
+            return Err("horizon must be at least 1".to_string());
+        }
+        if let Some(level) = self.level.filter(|l| !(0.0 < *l && *l < 1.0)) {
+            return Err(format!("level must be between 0 and 1, got {}", level));
+        }
+        Ok(())
+    }
+}
+
+impl KeywordForecaster {
+    fn fitted(&self, method: ForecastMethod, y: &[f64]) -> Option<Box<dyn Fitted>> {
+        match method {
+            ForecastMethod::Holt => Some(Box::new(Smoothing::fit(y, 0)?)),
+            ForecastMethod::HoltWinters => Smoothing::fit(y, season(self.granularity))
+                .or_else(|| Smoothing::fit(y, 0))
+                .map(|s| Box::new(s) as Box<dyn Fitted>),
+            ForecastMethod::LogLinear => Some(Box::new(LogLinear::fit(y)?)),
+        }
+    }
+
+    /// Papers and prediction intervals over the `horizon` buckets after
+    /// `counts`, `None` when they are too short for the method.
+    pub fn forecast_counts(
+        &self,
+        method: ForecastMethod,
+        counts: &[usize],
+    ) -> Option<Vec<(f64, f64, f64)>> {
+        let y: Vec<f64> = counts.iter().map(|&c| c as f64).collect();
+        let fitted = self.fitted(method, &y)?;
+        let z = normal_quantile((1.0 + self.level) / 2.0);
+        Some(
+            (1..=self.horizon)
+                .map(|h| {
+                    let (lower, upper) = fitted.interval(h, z);
+                    (fitted.mean(h).max(0.0), lower.max(0.0), upper.max(0.0))
+                })
+                .collect(),
+        )
+    }
+
+    /// Forecasts of the keywords of `series`, the ones growing the most
+    /// first.
+    pub fn forecast_series(&self, series: &KeywordSeries) -> Vec<Forecast> {
+        let Some(&last) = series.buckets().last() else {
+            return Vec::new();
+        };
+        let mut forecasts: Vec<Forecast> = series
+            .iter()
+            .filter(|(_, counts)| counts.iter().sum::<usize>() >= self.min_papers)
+            .filter_map(|(keyword, counts)| {
+                let predicted = self.forecast_counts(self.method, counts)?;
+                let points = predicted
+                    .into_iter()
+                    .enumerate()
+                    .map(|(h, (papers, lower, upper))| ForecastPoint {
+                        bucket: self.granularity.add(last, h as u32 + 1),
+                        papers,
+                        lower,
+                        upper,
+                    })
+                    .collect();
+                let recent = counts.len().saturating_sub(self.horizon);
+                Some(Forecast {
+                    keyword: keyword.to_string(),
+                    method: self.method,
+                    recent: counts[recent..].iter().sum(),
+                    points,
+                })
+            })
+            .collect();
+        forecasts.sort_by(|a, b| {
+            let growth = |f: &Forecast| f.growth().unwrap_or(f64::INFINITY);
+            growth(b)
+                .partial_cmp(&growth(a))
+                .unwrap_or(Ordering::Equal)
+                .then(b.total().partial_cmp(&a.total()).unwrap_or(Ordering::Equal))
+                .then(a.keyword.cmp(&b.keyword))
+        });
+        forecasts
+    }
+
+    /// Forecasts from papers per keyword and day, as
+    /// [`daily_counts`] returns them, after the bucket of their latest day.
+    pub fn forecast(&self, counts: &[(String, NaiveDate, usize)]) -> Vec<Forecast> {
+        self.forecast_series(&self.series(counts, None))
+    }
+
+    /// Forecasts from the stored papers published since `since`, after the
+    /// last complete bucket.
+    pub async fn forecast_stored(
+        &self,
+        pool: &SqlitePool,
+        since: NaiveDate,
+    ) -> Result<Vec<Forecast>, sqlx::Error> {
+        let counts = daily_counts(pool, since).await?;
+        let until = self.granularity.last_complete(Local::now().date_naive());
+        Ok(self.forecast_series(&self.series(&counts, Some(until))))
+    }
+
+    /// How well `method` forecast the `horizon` buckets following each of
+    /// the last `windows` cut-offs of `series`, one bucket apart.
+    pub fn backtest_series(
+        &self,
+        series: &KeywordSeries,
+        method: ForecastMethod,
+        windows: usize,
+    ) -> Backtest {
+        let z = normal_quantile((1.0 + self.level) / 2.0);
+        let (mut forecasts, mut error, mut naive_error, mut covered) = (0, 0.0, 0.0, 0);
+        for (_, counts) in series
+            .iter()
+            .filter(|(_, counts)| counts.iter().sum::<usize>() >= self.min_papers)
+        {
+            for w in 0..windows {
+                let Some(cut) = counts.len().checked_sub(self.horizon + w) else {
+                    break;
+                };
+                let y: Vec<f64> = counts[..cut].iter().map(|&c| c as f64).collect();
+                let Some(fitted) = self.fitted(method, &y) else {
+                    break;
+                };
+                let last = y[cut - 1];
+                for (h, &actual) in counts[cut..cut + self.horizon].iter().enumerate() {
+                    let actual = actual as f64;
+                    let (lower, upper) = fitted.interval(h + 1, z);
+                    forecasts += 1;
+                    error += (fitted.mean(h + 1).max(0.0) - actual).abs();
+                    naive_error += (last - actual).abs();
+                    covered += (lower.max(0.0) <= actual && actual <= upper) as usize;
+                }
+            }
+        }
+        let n = forecasts.max(1) as f64;
+        Backtest {
+            method,
+            forecasts,
+            mae: error / n,
+            mase: (naive_error > 0.0).then(|| error / naive_error),
+            coverage: covered as f64 / n,
+        }
+    }
+
+    /// [`KeywordForecaster::backtest_series`] of every method over the
+    /// stored papers published since `since`.
+    pub async fn backtest_stored(
+        &self,
+        pool: &SqlitePool,
+        since: NaiveDate,
+        windows: usize,
+    ) -> Result<Vec<Backtest>, sqlx::Error> {
+        let counts = daily_counts(pool, since).await?;
+        let until = self.granularity.last_complete(Local::now().date_naive());
+        let series = self.series(&counts, Some(until));
+        Ok(ForecastMethod::ALL
+            .iter()
+            .map(|&method| self.backtest_series(&series, method, windows))
+            .collect())
+    }
+
+    fn series(
+        &self,
+        counts: &[(String, NaiveDate, usize)],
+        until: Option<NaiveDate>,
+    ) -> KeywordSeries {
+        let days = || counts.iter().map(|(_, day, _)| *day);
+        match (days().min(), until.or_else(|| days().max())) {
+            (Some(from), Some(until)) => KeywordSeries::new(self.granularity, from, until, counts),
+            _ => KeywordSeries::new(self.granularity, NaiveDate::MAX, NaiveDate::MIN, &[]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{normal_quantile, ForecastMethod, KeywordForecasterBuilder};
+    use crate::analyzers::trend::Granularity;
+
+    #[test]
+    fn test_normal_quantile() {
+        assert!((normal_quantile(0.975) - 1.96).abs() < 1e-3);
+        assert!((normal_quantile(0.9) - 1.2816).abs() < 1e-3);
+        assert!((normal_quantile(0.1) + 1.2816).abs() < 1e-3);
+        assert_eq!(normal_quantile(0.5), 0.0);
+    }
+
+    #[test]
+    fn test_methods() {
+        let forecaster = KeywordForecasterBuilder::default()
+            .granularity(Granularity::Day)
+            .horizon(7)
+            .build()
+            .unwrap();
+
+        // a steady rise goes on
+        let linear: Vec<usize> = (0..12).map(|t| 10 + 2 * t).collect();
+        let holt = forecaster
+            .forecast_counts(ForecastMethod::Holt, &linear)
+            .unwrap();
+        assert!((holt[0].0 - 34.0).abs() < 1e-6);
+        assert!((holt[6].0 - 46.0).abs() < 1e-6);
+
+        // doubling every bucket
+        let doubling: Vec<usize> = (0..8).map(|t| (1 << t) - 1).collect();
+        let log_linear = forecaster
+            .forecast_counts(ForecastMethod::LogLinear, &doubling)
+            .unwrap();
+        assert!((log_linear[0].0 - 255.0).abs() < 1e-6);
+        // a perfect fit leaves no doubt
+        assert!(log_linear[0].2 - log_linear[0].1 < 1e-6);
+
+        // weekday papers, none on weekends
+        let weekly: Vec<usize> = (0..28).map(|t| if t % 7 < 5 { 10 } else { 0 }).collect();
+        let holt_winters = forecaster
+            .forecast_counts(ForecastMethod::HoltWinters, &weekly)
+            .unwrap();
+        let papers: Vec<usize> = holt_winters.iter().map(|p| p.0.round() as usize).collect();
+        assert_eq!(papers, vec![10, 10, 10, 10, 10, 0, 0]);
+        // intervals widen with the horizon
+        let noisy: Vec<usize> = (0..12).map(|t| 10 + 2 * t + t % 2 * 3).collect();
+        let holt = forecaster
+            .forecast_counts(ForecastMethod::Holt, &noisy)
+            .unwrap();
+        let width = |p: &(f64, f64, f64)| p.2 - p.1;
+        assert!(width(&holt[0]) > 0.0);
+        assert!(width(&holt[6]) > width(&holt[0]));
+
+        assert!(forecaster
+            .forecast_counts(ForecastMethod::Holt, &[1, 2])
+            .is_none());
+    }
+}
@@ -0,0 +1,626 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Write,
+    str::FromStr,
+};
+
+use arxiv::Arxiv;
+use chrono::NaiveDate;
+use derive_builder::Builder;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::{dao::PaperKeywordDAO, extracters::topic::ArxivTopicData};
+
+/// How strongly two keywords are tied by the papers they share.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EdgeWeight {
+    /// Papers with both.
+    Count,
+    /// Papers with both over papers with either.
+    Jaccard,
+    /// Pointwise mutual information, how much more often they meet than by
+    /// chance; pairs meeting less often than by chance are left out.
+    #[default]
+    Pmi,
+}
+
+impl FromStr for EdgeWeight {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "count" => Ok(Self::Count),
+            "jaccard" => Ok(Self::Jaccard),
+            "pmi" => Ok(Self::Pmi),
+            _ => Err(format!(
+                "unknown edge weight {:?}, use count, jaccard or pmi",
+                s
+            )),
+        }
+    }
+}
+
+/// File formats a graph exports to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    #[default]
+    GraphMl,
+    Gexf,
+    Json,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "graphml" => Ok(Self::GraphMl),
+            "gexf" => Ok(Self::Gexf),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "unknown graph format {:?}, use graphml, gexf or json",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeywordNode {
+    pub keyword: String,
+    /// Papers with the keyword.
+    pub papers: usize,
+    /// Index of the keyword's community, the largest community first.
+    pub community: usize,
+    /// Neighbours over the other keywords, in `0.0..=1.0`.
+    pub degree: f64,
+    /// Sum of the weights of the keyword's edges.
+    pub strength: f64,
+    pub pagerank: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeywordEdge {
+    pub source: String,
+    pub target: String,
+    /// Papers with both keywords.
+    pub papers: usize,
+    pub weight: f64,
+}
+
+/// Keywords linked by the papers they share, with their research clusters
+/// and how central they are.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CooccurrenceGraph {
+    /// Sorted by keyword.
+    pub nodes: Vec<KeywordNode>,
+    pub edges: Vec<KeywordEdge>,
+}
+
+/// Builds a [`CooccurrenceGraph`] from the keywords of each paper. Clusters
+/// are found with Louvain's modularity optimization, centrality is the
+/// degree, weighted degree and weighted PageRank of each keyword.
+#[derive(Builder, Clone, Debug)]
+pub struct CooccurrenceAnalyzer {
+    #[builder(default)]
+    weight: EdgeWeight,
+    /// Papers a keyword needs to be a node.
+    #[builder(default = "2")]
+    min_papers: usize,
+    /// Papers two keywords need to share to be linked.
+    #[builder(default = "2")]
+    min_cooccurrence: usize,
+}
+
+impl CooccurrenceAnalyzer {
+    /// Graph of the keywords of `papers`, one list of keywords per paper.
+    pub fn graph<S: AsRef<str>>(&self, papers: &[Vec<S>]) -> CooccurrenceGraph {
+        let papers: Vec<BTreeSet<&str>> = papers
+            .iter()
+            .map(|keywords| keywords.iter().map(AsRef::as_ref).collect())
+            .collect();
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for keyword in papers.iter().flatten() {
+            *counts.entry(keyword).or_default() += 1;
+        }
+        counts.retain(|_, papers| *papers >= self.min_papers);
+        let keywords: Vec<&str> = counts.keys().copied().collect();
+        let index: HashMap<&str, usize> = keywords
+            .iter()
+            .enumerate()
+            .map(|(i, keyword)| (*keyword, i))
+            .collect();
+
+        let mut pairs: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+        for paper in papers.iter() {
+            let ids: Vec<usize> = paper.iter().filter_map(|k| index.get(k).copied()).collect();
+            for (i, a) in ids.iter().enumerate() {
+                for b in ids[i + 1..].iter() {
+                    *pairs.entry((*a.min(b), *a.max(b))).or_default() += 1;
+                }
+            }
+        }
+
+        let total = papers.len() as f64;
+        let edges: Vec<(usize, usize, usize, f64)> = pairs
+            .into_iter()
+            .filter(|(_, both)| *both >= self.min_cooccurrence)
+            .filter_map(|((a, b), both)| {
+                let (na, nb) = (counts[keywords[a]] as f64, counts[keywords[b]] as f64);
+                let weight = match self.weight {
+                    EdgeWeight::Count => both as f64,
+                    EdgeWeight::Jaccard => both as f64 / (na + nb - both as f64),
+                    EdgeWeight::Pmi => (both as f64 * total / (na * nb)).ln(),
+                };
+                (weight > 0.0).then_some((a, b, both, weight))
+            })
+            .collect();
+
+        let weighted: Vec<(usize, usize, f64)> = edges.iter().map(|e| (e.0, e.1, e.3)).collect();
+        let communities = louvain(keywords.len(), &weighted);
+        let pagerank = pagerank(keywords.len(), &weighted);
+        let mut degree = vec![0usize; keywords.len()];
+        let mut strength = vec![0.0; keywords.len()];
+        for (a, b, weight) in weighted.iter() {
+            for node in [*a, *b] {
+                degree[node] += 1;
+                strength[node] += weight;
+            }
+        }
+        let others = keywords.len().saturating_sub(1).max(1) as f64;
+
+        CooccurrenceGraph {
+            nodes: keywords
+                .iter()
+                .enumerate()
+                .map(|(i, keyword)| KeywordNode {
+                    keyword: keyword.to_string(),
+                    papers: counts[keyword],
+                    community: communities[i],
+                    degree: degree[i] as f64 / others,
+                    strength: strength[i],
+                    pagerank: pagerank[i],
+                })
+                .collect(),
+            edges: edges
+                .into_iter()
+                .map(|(a, b, papers, weight)| KeywordEdge {
+                    source: keywords[a].to_string(),
+                    target: keywords[b].to_string(),
+                    papers,
+                    weight,
+                })
+                .collect(),
+        }
+    }
+
+    /// Graph of the keywords `f` returns for extracted papers.
+    pub fn graph_of<F: Fn(&ArxivTopicData) -> Vec<String>>(
+        &self,
+        data: &[(Arxiv, ArxivTopicData)],
+        f: F,
+    ) -> CooccurrenceGraph {
+        let papers: Vec<Vec<String>> = data.iter().map(|(_, ctx)| f(ctx)).collect();
+        self.graph(&papers)
+    }
+
+    /// Graph of the keywords of the papers stored since `since`.
+    pub async fn graph_stored(
+        &self,
+        pool: &SqlitePool,
+        since: NaiveDate,
+    ) -> Result<CooccurrenceGraph, sqlx::Error> {
+        let rows =
+            PaperKeywordDAO::keywords_by_paper(pool, &since.format("%Y-%m-%d").to_string()).await?;
+        let mut papers: BTreeMap<i64, Vec<String>> = BTreeMap::new();
+        for row in rows {
+            papers.entry(row.paper_id).or_default().push(row.keyword);
+        }
+        let papers: Vec<Vec<String>> = papers.into_values().collect();
+        Ok(self.graph(&papers))
+    }
+}
+
+impl CooccurrenceGraph {
+    /// Keywords of each community, the largest community first.
+    pub fn communities(&self) -> Vec<Vec<&str>> {
+        let mut communities: Vec<Vec<&str>> = Vec::new();
+        for node in self.nodes.iter() {
+            if communities.len() <= node.community {
+                communities.resize(node.community + 1, Vec::new());
+            }
+            communities[node.community].push(&node.keyword);
+        }
+        communities
+    }
+
+    /// Nodes by decreasing PageRank.
+    pub fn most_central(&self) -> Vec<&KeywordNode> {
+        let mut nodes: Vec<&KeywordNode> = self.nodes.iter().collect();
+        nodes.sort_by(|a, b| {
+            b.pagerank
+                .partial_cmp(&a.pagerank)
+                .unwrap_or(Ordering::Equal)
+                .then(a.keyword.cmp(&b.keyword))
+        });
+        nodes
+    }
+
+    pub fn export(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::GraphMl => self.to_graphml(),
+            GraphFormat::Gexf => self.to_gexf(),
+            GraphFormat::Json => self.to_json(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let index = self.index();
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        let keys = [
+            ("label", "node", "label", "string"),
+            ("papers", "node", "papers", "int"),
+            ("community", "node", "community", "int"),
+            ("degree", "node", "degree", "double"),
+            ("strength", "node", "strength", "double"),
+            ("pagerank", "node", "pagerank", "double"),
+            ("edge_papers", "edge", "papers", "int"),
+            ("weight", "edge", "weight", "double"),
+        ];
+        for (id, domain, name, kind) in keys {
+            let _ = writeln!(
+                out,
+                "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                id, domain, name, kind
+            );
+        }
+        out.push_str("  <graph id=\"cooccurrence\" edgedefault=\"undirected\">\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    <node id=\"n{}\"><data key=\"label\">{}</data><data key=\"papers\">{}</data><data key=\"community\">{}</data><data key=\"degree\">{}</data><data key=\"strength\">{}</data><data key=\"pagerank\">{}</data></node>",
+                i,
+                escape_xml(&node.keyword),
+                node.papers,
+                node.community,
+                node.degree,
+                node.strength,
+                node.pagerank
+            );
+        }
+        for edge in self.edges.iter() {
+            let _ = writeln!(
+                out,
+                "    <edge source=\"n{}\" target=\"n{}\"><data key=\"edge_papers\">{}</data><data key=\"weight\">{}</data></edge>",
+                index[edge.source.as_str()],
+                index[edge.target.as_str()],
+                edge.papers,
+                edge.weight
+            );
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    pub fn to_gexf(&self) -> String {
+        let index = self.index();
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+        out.push_str("  <graph defaultedgetype=\"undirected\">\n");
+        out.push_str("    <attributes class=\"node\">\n");
+        let attributes = [
+            ("papers", "integer"),
+            ("community", "integer"),
+            ("degree", "double"),
+            ("strength", "double"),
+            ("pagerank", "double"),
+        ];
+        for (id, (title, kind)) in attributes.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>",
+                id, title, kind
+            );
+        }
+        out.push_str("    </attributes>\n    <nodes>\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let values = [
+                node.papers.to_string(),
+                node.community.to_string(),
+                node.degree.to_string(),
+                node.strength.to_string(),
+                node.pagerank.to_string(),
+            ];
+            let _ = write!(
+                out,
+                "      <node id=\"{}\" label=\"{}\"><attvalues>",
+                i,
+                escape_xml(&node.keyword)
+            );
+            for (id, value) in values.iter().enumerate() {
+                let _ = write!(out, "<attvalue for=\"{}\" value=\"{}\"/>", id, value);
+            }
+            out.push_str("</attvalues></node>\n");
+        }
+        out.push_str("    </nodes>\n    <edges>\n");
+        for (i, edge) in self.edges.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "      <edge id=\"{}\" source=\"{}\" target=\"{}\" weight=\"{}\"/>",
+                i,
+                index[edge.source.as_str()],
+                index[edge.target.as_str()],
+                edge.weight
+            );
+        }
+        out.push_str("    </edges>\n  </graph>\n</gexf>\n");
+        out
+    }
+
+    fn index(&self) -> HashMap<&str, usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.keyword.as_str(), i))
+            .collect()
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Undirected weighted graph, edge weights within a node kept as a loop.
+struct Graph {
+    /// Neighbours of each node with the weights of the edges, loops left out.
+    adjacency: Vec<Vec<(usize, f64)>>,
+    loops: Vec<f64>,
+}
+
+impl Graph {
+    fn new(n: usize, edges: &[(usize, usize, f64)]) -> Self {
+        let mut adjacency = vec![Vec::new(); n];
+        for (a, b, weight) in edges {
+            adjacency[*a].push((*b, *weight));
+            adjacency[*b].push((*a, *weight));
+        }
+        Self {
+            adjacency,
+            loops: vec![0.0; n],
+        }
+    }
+
+    fn degree(&self, node: usize) -> f64 {
+        self.adjacency[node].iter().map(|(_, w)| w).sum::<f64>() + 2.0 * self.loops[node]
+    }
+
+    /// Graph of the communities, nodes of a community merged into one.
+    fn aggregate(&self, community: &[usize], n: usize) -> Self {
+        let mut weights: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); n];
+        let mut loops = vec![0.0; n];
+        for (node, neighbours) in self.adjacency.iter().enumerate() {
+            let c = community[node];
+            loops[c] += self.loops[node];
+            for (neighbour, weight) in neighbours {
+                let d = community[*neighbour];
+                if c == d {
+                    // each edge is seen from both of its ends
+                    loops[c] += weight / 2.0;
+                } else {
+                    *weights[c].entry(d).or_default() += weight;
+                }
+            }
+        }
+        Self {
+            adjacency: weights
+                .into_iter()
+                .map(|w| w.into_iter().collect())
+                .collect(),
+            loops,
+        }
+    }
+
+    /// Move nodes to the neighbouring community that most increases
+    /// modularity until none moves, communities numbered from 0. Returns
+    /// whether any node moved.
+    fn local_moves(&self) -> (bool, Vec<usize>) {
+        let n = self.adjacency.len();
+        let degree: Vec<f64> = (0..n).map(|node| self.degree(node)).collect();
+        let m2: f64 = degree.iter().sum();
+        let mut community: Vec<usize> = (0..n).collect();
+        if m2 == 0.0 {
+            return (false, community);
+        }
+        let mut total = degree.clone();
+        let mut moved = false;
+        for _ in 0..100 {
+            let mut improved = false;
+            for node in 0..n {
+                let current = community[node];
+                total[current] -= degree[node];
+                let mut links: BTreeMap<usize, f64> = BTreeMap::new();
+                links.insert(current, 0.0);
+                for (neighbour, weight) in self.adjacency[node].iter() {
+                    *links.entry(community[*neighbour]).or_default() += weight;
+                }
+                let gain = |c: usize, link: f64| link - total[c] * degree[node] / m2;
+                let mut best = (current, gain(current, links[&current]));
+                for (c, link) in links.iter() {
+                    let g = gain(*c, *link);
+                    if g > best.1 + 1e-12 {
+                        best = (*c, g);
+                    }
+                }
+                total[best.0] += degree[node];
+                if best.0 != current {
+                    community[node] = best.0;
+                    improved = true;
+                    moved = true;
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+        (moved, renumber(&community))
+    }
+}
+
+/// Communities numbered from 0 in order of first appearance.
+fn renumber(community: &[usize]) -> Vec<usize> {
+    let mut ids: HashMap<usize, usize> = HashMap::new();
+    community
+        .iter()
+        .map(|c| {
+            let next = ids.len();
+            *ids.entry(*c).or_insert(next)
+        })
+        .collect()
+}
+
+/// Community of each node by Louvain's method, the largest community first.
+fn louvain(n: usize, edges: &[(usize, usize, f64)]) -> Vec<usize> {
+    let mut graph = Graph::new(n, edges);
+    let mut community: Vec<usize> = (0..n).collect();
+    loop {
+        let (moved, level) = graph.local_moves();
+        for c in community.iter_mut() {
+            *c = level[*c];
+        }
+        if !moved {
+            break;
+        }
+        let communities = level.iter().max().map_or(0, |c| c + 1);
+        graph = graph.aggregate(&level, communities);
+    }
+
+    let mut sizes: BTreeMap<usize, usize> = BTreeMap::new();
+    for c in community.iter() {
+        *sizes.entry(*c).or_default() += 1;
+    }
+    let mut order: Vec<(usize, usize)> = sizes.into_iter().collect();
+    order.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let rank: HashMap<usize, usize> = order
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (c, _))| (c, rank))
+        .collect();
+    community.iter().map(|c| rank[c]).collect()
+}
+
+/// Weighted PageRank with the usual 0.85 damping, summing to 1.
+fn pagerank(n: usize, edges: &[(usize, usize, f64)]) -> Vec<f64> {
+    const DAMPING: f64 = 0.85;
+    if n == 0 {
+        return Vec::new();
+    }
+    let graph = Graph::new(n, edges);
+    let strength: Vec<f64> = (0..n).map(|node| graph.degree(node)).collect();
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..100 {
+        // keywords without edges spread their rank evenly
+        let dangling: f64 = (0..n)
+            .filter(|node| strength[*node] == 0.0)
+            .map(|node| rank[node])
+            .sum();
+        let mut next = vec![(1.0 - DAMPING + DAMPING * dangling) / n as f64; n];
+        for (node, neighbours) in graph.adjacency.iter().enumerate() {
+            for (neighbour, weight) in neighbours {
+                next[*neighbour] += DAMPING * rank[node] * weight / strength[node];
+            }
+        }
+        let delta: f64 = rank
+            .iter()
+            .zip(next.iter())
+            .map(|(a, b)| (a - b).abs())
+            .sum();
+        rank = next;
+        if delta < 1e-10 {
+            break;
+        }
+    }
+    rank
+}
+
+#[cfg(test)]
+mod test {
+    use super::{louvain, pagerank, CooccurrenceAnalyzerBuilder, EdgeWeight};
+
+    #[test]
+    fn test_louvain() {
+        // two triangles joined by one light edge
+        let edges = [
+            (0, 1, 1.0),
+            (1, 2, 1.0),
+            (0, 2, 1.0),
+            (3, 4, 1.0),
+            (4, 5, 1.0),
+            (3, 5, 1.0),
+            (2, 3, 0.1),
+        ];
+        let communities = louvain(7, &edges);
+        assert_eq!(communities[0], communities[1]);
+        assert_eq!(communities[1], communities[2]);
+        assert_eq!(communities[3], communities[5]);
+        assert_ne!(communities[0], communities[3]);
+        // the isolated node is alone, after the two triangles
+        assert_eq!(communities[6], 2);
+
+        let rank = pagerank(7, &edges);
+        assert!((rank.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(rank[2] > rank[0]);
+        assert!(rank[6] < rank[0]);
+    }
+
+    #[test]
+    fn test_edge_weights() {
+        let papers = vec![
+            vec!["LLM", "program repair"],
+            vec!["LLM", "program repair"],
+            vec!["LLM", "fuzzing"],
+            vec!["LLM", "fuzzing"],
+            vec!["GNN", "fuzzing"],
+            vec!["GNN"],
+        ];
+        let graph = |weight| {
+            CooccurrenceAnalyzerBuilder::default()
+                .weight(weight)
+                .build()
+                .unwrap()
+                .graph(&papers)
+        };
+        let jaccard = graph(EdgeWeight::Jaccard);
+        assert_eq!(jaccard.nodes.len(), 4);
+        let llm_repair = jaccard
+            .edges
+            .iter()
+            .find(|e| e.source == "LLM" && e.target == "program repair")
+            .unwrap();
+        assert_eq!(llm_repair.papers, 2);
+        assert_eq!(llm_repair.weight, 0.5);
+        let pmi = graph(EdgeWeight::Pmi);
+        let weight = |source: &str, target: &str| {
+            pmi.edges
+                .iter()
+                .find(|e| e.source == source && e.target == target)
+                .map(|e| e.weight)
+        };
+        assert!(weight("LLM", "program repair").unwrap() > 0.0);
+        // LLM is in most papers, it meets fuzzing no more often than by chance
+        assert_eq!(weight("LLM", "fuzzing"), None);
+        assert_eq!(weight("GNN", "fuzzing"), None);
+    }
+}
@@ -0,0 +1,396 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Display,
+};
+
+use arxiv::Arxiv;
+use chrono::NaiveDate;
+use derive_builder::Builder;
+
+use crate::extracters::topic::ArxivTopicData;
+
+use super::trend::{parse_day, Granularity};
+
+/// A problem and technique pair of a [`ContingencyMatrix`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub problem: String,
+    pub technique: String,
+    /// Papers applying the technique to the problem.
+    pub observed: usize,
+    /// Papers expected if techniques were picked regardless of the problem.
+    pub expected: f64,
+    /// Pearson residual, `(observed - expected) / sqrt(expected)`: positive
+    /// for over-explored pairs, negative for gaps.
+    pub residual: f64,
+}
+
+impl Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} x {}: {} papers, {:.1} expected ({:+.2})",
+            self.problem, self.technique, self.observed, self.expected, self.residual
+        )
+    }
+}
+
+/// How the share of papers in a cell changed between two windows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellChange {
+    pub problem: String,
+    pub technique: String,
+    pub before: usize,
+    pub after: usize,
+    /// Share of the window's papers after minus before.
+    pub change: f64,
+}
+
+/// Papers per `solved_problem` and `techniques_used` pair. A paper counts in
+/// every pair of its problems and techniques.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContingencyMatrix {
+    pub problems: Vec<String>,
+    pub techniques: Vec<String>,
+    /// `counts[problem][technique]`
+    pub counts: Vec<Vec<usize>>,
+    /// Papers with each problem.
+    pub problem_papers: Vec<usize>,
+    /// Papers with each technique.
+    pub technique_papers: Vec<usize>,
+    /// Papers with at least one of the problems and one of the techniques.
+    pub papers: usize,
+    /// Row of each problem.
+    problem_index: HashMap<String, usize>,
+    /// Column of each technique.
+    technique_index: HashMap<String, usize>,
+}
+
+impl ContingencyMatrix {
+    fn new(problems: Vec<String>, techniques: Vec<String>, data: &[&ArxivTopicData]) -> Self {
+        let problem_index: HashMap<String, usize> = problems
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.clone(), i))
+            .collect();
+        let technique_index: HashMap<String, usize> = techniques
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.clone(), i))
+            .collect();
+        let mut counts = vec![vec![0; techniques.len()]; problems.len()];
+        let mut problem_papers = vec![0; problems.len()];
+        let mut technique_papers = vec![0; techniques.len()];
+        let mut papers = 0;
+        for data in data {
+            let ps: BTreeSet<usize> = data
+                .solved_problem
+                .iter()
+                .filter_map(|p| problem_index.get(p).copied())
+                .collect();
+            let ts: BTreeSet<usize> = data
+                .techniques_used
+                .iter()
+                .filter_map(|t| technique_index.get(t).copied())
+                .collect();
+            if ps.is_empty() || ts.is_empty() {
+                continue;
+            }
+            papers += 1;
+            for p in ps.iter() {
+                problem_papers[*p] += 1;
+                for t in ts.iter() {
+                    counts[*p][*t] += 1;
+                }
+            }
+            for t in ts.iter() {
+                technique_papers[*t] += 1;
+            }
+        }
+        Self {
+            problems,
+            techniques,
+            counts,
+            problem_papers,
+            technique_papers,
+            papers,
+            problem_index,
+            technique_index,
+        }
+    }
+
+    pub fn get(&self, problem: &str, technique: &str) -> Option<usize> {
+        let p = self.problem_index.get(problem)?;
+        let t = self.technique_index.get(technique)?;
+        Some(self.counts[*p][*t])
+    }
+
+    /// Every pair with papers expected in it, row by row.
+    pub fn cells(&self) -> Vec<Cell> {
+        let mut cells = Vec::new();
+        if self.papers == 0 {
+            return cells;
+        }
+        for (p, problem) in self.problems.iter().enumerate() {
+            for (t, technique) in self.techniques.iter().enumerate() {
+                let expected =
+                    (self.problem_papers[p] * self.technique_papers[t]) as f64 / self.papers as f64;
+                if expected == 0.0 {
+                    continue;
+                }
+                let observed = self.counts[p][t];
+                cells.push(Cell {
+                    problem: problem.clone(),
+                    technique: technique.clone(),
+                    observed,
+                    expected,
+                    residual: (observed as f64 - expected) / expected.sqrt(),
+                });
+            }
+        }
+        cells
+    }
+
+    /// The `k` pairs most explored compared to their marginals.
+    pub fn over_explored(&self, k: usize) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = self
+            .cells()
+            .into_iter()
+            .filter(|c| c.residual > 0.0)
+            .collect();
+        cells.sort_by(|a, b| by_residual(b, a).then(by_name(a, b)));
+        cells.truncate(k);
+        cells
+    }
+
+    /// The `k` pairs least explored compared to their marginals, e.g.
+    /// a popular technique never tried on a popular problem.
+    pub fn gaps(&self, k: usize) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = self
+            .cells()
+            .into_iter()
+            .filter(|c| c.residual < 0.0)
+            .collect();
+        cells.sort_by(|a, b| by_residual(a, b).then(by_name(a, b)));
+        cells.truncate(k);
+        cells
+    }
+
+    /// Changes of every pair from `self` to `later`, the largest first. Both
+    /// matrices need the same problems and techniques, as windows of
+    /// [`MatrixAnalyzer::windows`] have.
+    pub fn compare(&self, later: &ContingencyMatrix) -> Vec<CellChange> {
+        let share = |count: usize, papers: usize| match papers {
+            0 => 0.0,
+            papers => count as f64 / papers as f64,
+        };
+        let mut changes = Vec::new();
+        for (p, problem) in self.problems.iter().enumerate() {
+            let Some(&lp) = later.problem_index.get(problem) else {
+                continue;
+            };
+            for (t, technique) in self.techniques.iter().enumerate() {
+                let before = self.counts[p][t];
+                let Some(&lt) = later.technique_index.get(technique) else {
+                    continue;
+                };
+                let after = later.counts[lp][lt];
+                if before == 0 && after == 0 {
+                    continue;
+                }
+                changes.push(CellChange {
+                    problem: problem.clone(),
+                    technique: technique.clone(),
+                    before,
+                    after,
+                    change: share(after, later.papers) - share(before, self.papers),
+                });
+            }
+        }
+        changes.sort_by(|a, b| {
+            b.change
+                .abs()
+                .partial_cmp(&a.change.abs())
+                .unwrap_or(Ordering::Equal)
+                .then(a.problem.cmp(&b.problem))
+                .then(a.technique.cmp(&b.technique))
+        });
+        changes
+    }
+}
+
+fn by_residual(a: &Cell, b: &Cell) -> Ordering {
+    a.residual
+        .partial_cmp(&b.residual)
+        .unwrap_or(Ordering::Equal)
+}
+
+fn by_name(a: &Cell, b: &Cell) -> Ordering {
+    a.problem
+        .cmp(&b.problem)
+        .then(a.technique.cmp(&b.technique))
+}
+
+impl Display for ContingencyMatrix {
+    /// One line per problem, the techniques numbered in the header.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (t, technique) in self.techniques.iter().enumerate() {
+            writeln!(f, "[{}] {}", t + 1, technique)?;
+        }
+        let width = self.problems.iter().map(|p| p.len()).max().unwrap_or(0);
+        write!(f, "{:width$}", "", width = width)?;
+        for t in 0..self.techniques.len() {
+            write!(f, " {:>4}", format!("[{}]", t + 1))?;
+        }
+        writeln!(f)?;
+        for (problem, row) in self.problems.iter().zip(self.counts.iter()) {
+            write!(f, "{:width$}", problem, width = width)?;
+            for count in row {
+                write!(f, " {:>4}", count)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds `solved_problem` x `techniques_used` contingency matrices of
+/// extracted papers, on their most frequent problems and techniques, to
+/// find which techniques go with which problems and where the gaps are.
+#[derive(Builder, Clone, Debug)]
+pub struct MatrixAnalyzer {
+    /// Rows of the matrix, the most frequent problems.
+    #[builder(default = "20")]
+    problems: usize,
+    /// Columns of the matrix, the most frequent techniques.
+    #[builder(default = "20")]
+    techniques: usize,
+    /// Papers a problem or technique needs to be in the matrix.
+    #[builder(default = "2")]
+    min_papers: usize,
+}
+
+impl MatrixAnalyzer {
+    /// The `k` most frequent keywords `f` returns with at least `min_papers` papers.
+    fn axis<F: Fn(&ArxivTopicData) -> &Vec<String>>(
+        &self,
+        data: &[&ArxivTopicData],
+        k: usize,
+        f: F,
+    ) -> Vec<String> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for keywords in data.iter().map(|d| f(d)) {
+            let keywords: BTreeSet<&str> = keywords.iter().map(String::as_str).collect();
+            for keyword in keywords {
+                *counts.entry(keyword).or_default() += 1;
+            }
+        }
+        let mut counts: Vec<(&str, usize)> = counts
+            .into_iter()
+            .filter(|(_, papers)| *papers >= self.min_papers)
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts
+            .into_iter()
+            .take(k)
+            .map(|(keyword, _)| keyword.to_string())
+            .collect()
+    }
+
+    fn axes(&self, data: &[&ArxivTopicData]) -> (Vec<String>, Vec<String>) {
+        (
+            self.axis(data, self.problems, |d| &d.solved_problem),
+            self.axis(data, self.techniques, |d| &d.techniques_used),
+        )
+    }
+
+    pub fn matrix(&self, data: &[(Arxiv, ArxivTopicData)]) -> ContingencyMatrix {
+        let data: Vec<&ArxivTopicData> = data.iter().map(|(_, ctx)| ctx).collect();
+        let (problems, techniques) = self.axes(&data);
+        ContingencyMatrix::new(problems, techniques, &data)
+    }
+
+    /// A matrix per bucket of publish dates, oldest first, all on the
+    /// problems and techniques most frequent over `data` so that their
+    /// cells can be compared. Papers without a publish date are left out.
+    pub fn windows(
+        &self,
+        data: &[(Arxiv, ArxivTopicData)],
+        granularity: Granularity,
+    ) -> Vec<(NaiveDate, ContingencyMatrix)> {
+        let all: Vec<&ArxivTopicData> = data.iter().map(|(_, ctx)| ctx).collect();
+        let (problems, techniques) = self.axes(&all);
+        let mut buckets: BTreeMap<NaiveDate, Vec<&ArxivTopicData>> = BTreeMap::new();
+        for (raw, ctx) in data {
+            if let Some(day) = parse_day(&raw.published) {
+                buckets
+                    .entry(granularity.bucket(day))
+                    .or_default()
+                    .push(ctx);
+            }
+        }
+        buckets
+            .into_iter()
+            .map(|(bucket, data)| {
+                let matrix = ContingencyMatrix::new(problems.clone(), techniques.clone(), &data);
+                (bucket, matrix)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arxiv::Arxiv;
+
+    use crate::extracters::topic::ArxivTopicData;
+
+    use super::MatrixAnalyzerBuilder;
+
+    fn paper(problems: &[&str], techniques: &[&str]) -> (Arxiv, ArxivTopicData) {
+        let ctx = ArxivTopicData {
+            solved_problem: problems.iter().map(|p| p.to_string()).collect(),
+            techniques_used: techniques.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        };
+        (Arxiv::default(), ctx)
+    }
+
+    #[test]
+    fn test_matrix() {
+        let mut data = Vec::new();
+        data.extend((0..4).map(|_| paper(&["program repair"], &["LLM"])));
+        data.extend((0..4).map(|_| paper(&["test generation"], &["search"])));
+        data.push(paper(&["test generation"], &["LLM"]));
+        data.push(paper(
+            &["program repair", "test generation"],
+            &["LLM", "search"],
+        ));
+        // neither problem nor technique is frequent enough
+        data.push(paper(&["proof synthesis"], &["SMT"]));
+
+        let matrix = MatrixAnalyzerBuilder::default()
+            .build()
+            .unwrap()
+            .matrix(&data);
+        assert_eq!(matrix.problems, vec!["test generation", "program repair"]);
+        assert_eq!(matrix.techniques, vec!["LLM", "search"]);
+        assert_eq!(matrix.papers, 10);
+        assert_eq!(matrix.get("program repair", "LLM"), Some(5));
+        assert_eq!(matrix.get("program repair", "search"), Some(1));
+        assert_eq!(matrix.get("proof synthesis", "SMT"), None);
+
+        let over = matrix.over_explored(1);
+        assert_eq!(
+            (over[0].problem.as_str(), over[0].technique.as_str()),
+            ("program repair", "LLM")
+        );
+        // 5 papers with repair and 6 with LLM expect 3 together
+        assert_eq!(over[0].expected, 3.0);
+        let gaps = matrix.gaps(10);
+        assert_eq!(gaps[0].problem, "program repair");
+        assert_eq!(gaps[0].technique, "search");
+        assert!(gaps.iter().all(|c| c.residual < 0.0));
+    }
+}
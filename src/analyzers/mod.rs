@@ -1,12 +1,391 @@
+use std::{borrow::Borrow, cmp::Ordering, collections::HashMap, future::Future, pin::pin};
+
+use arxiv::Arxiv;
+use futures::{Stream, TryStreamExt};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::{dao::ExtractionDAO, extracters::topic::ArxivTopicData, models::PaperOutput};
+
+use weighted::AuthorGroups;
+
+pub mod artifact;
+pub mod author;
+pub mod burst;
+pub mod compare;
+pub mod forecast;
+pub mod graph;
+pub mod matrix;
 pub mod simple;
+pub mod taxonomy;
+pub mod topic_model;
+pub mod trend;
+pub mod weighted;
+
+/// A keyword of [`PaperOutput::keywords`].
+#[derive(Deserialize)]
+struct StoredKeyword {
+    keyword: String,
+    confidence: Option<f64>,
+}
+
+/// `paper` as the analyzers take it, `None` if its output does not parse.
+/// Its techniques are the stored keywords, merged as the normalizer merged
+/// them, rather than the raw ones of the output.
+fn extracted_paper(paper: PaperOutput) -> Option<(Arxiv, ArxivTopicData)> {
+    let mut data: ArxivTopicData = serde_json::from_str(&paper.output).ok()?;
+    let keywords: Vec<StoredKeyword> = serde_json::from_str(&paper.keywords).unwrap_or_default();
+    if !keywords.is_empty() {
+        if let Some(confidence) = data.confidence.as_mut() {
+            confidence.techniques_used = keywords
+                .iter()
+                .filter_map(|k| k.confidence.map(|c| (k.keyword.clone(), c)))
+                .collect();
+        }
+        data.techniques_used = keywords.into_iter().map(|k| k.keyword).collect();
+    }
+    let raw = Arxiv {
+        id: paper.url.clone(),
+        title: paper.title,
+        summary: paper.abstract_text.unwrap_or_default(),
+        authors: serde_json::from_str(&paper.authors).unwrap_or_default(),
+        comment: paper.comment,
+        published: paper.publish_date.unwrap_or(paper.insert_date),
+        pdf_url: paper.url,
+        ..Default::default()
+    };
+    Some((raw, data))
+}
+
+/// Stored papers published since `since`, e.g. `2025-03-01`, with the output
+/// of their latest extraction, for the analyzers working on extracted papers.
+/// Papers extracted before outputs were stored are left out.
+pub async fn stored_papers(
+    pool: &SqlitePool,
+    since: &str,
+) -> Result<Vec<(Arxiv, ArxivTopicData)>, sqlx::Error> {
+    Ok(ExtractionDAO::latest_outputs(pool, since)
+        .await?
+        .into_iter()
+        .filter_map(extracted_paper)
+        .collect())
+}
+
+/// [`stored_papers`] one at a time, with their database ids, for
+/// [`TrendingAnalyzer::trends_stream`].
+pub fn stream_stored_papers<'a>(
+    pool: &'a SqlitePool,
+    since: &'a str,
+) -> impl Stream<Item = Result<(i64, Arxiv, ArxivTopicData), sqlx::Error>> + 'a {
+    ExtractionDAO::stream_outputs(pool, since).try_filter_map(|paper| async move {
+        let id = paper.id;
+        Ok(extracted_paper(paper).map(|(raw, ctx)| (id, raw, ctx)))
+    })
+}
+
+/// Order of the keywords with the same score and papers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Z to A.
+    #[default]
+    Descending,
+    /// A to Z.
+    Ascending,
+    /// The keyword met first comes first.
+    FirstSeen,
+}
+
+/// Which keywords [`TrendingAnalyzer::trends`] returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopK {
+    /// Keywords returned, all of them with `None`.
+    pub k: Option<usize>,
+    /// Papers a keyword needs.
+    pub min_support: usize,
+    pub ties: TieBreak,
+}
+
+impl Default for TopK {
+    fn default() -> Self {
+        Self {
+            k: None,
+            min_support: 1,
+            ties: TieBreak::default(),
+        }
+    }
+}
+
+impl TopK {
+    /// The `k` best keywords.
+    pub fn new(k: usize) -> Self {
+        Self {
+            k: Some(k),
+            ..Default::default()
+        }
+    }
+}
+
+/// A keyword with the papers it was found in, as they were handed to the
+/// analyzer: references, indices or database ids.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrendingKeyword<P> {
+    pub keyword: String,
+    /// Papers weighted by the analyzer, their number for a plain count.
+    pub score: f64,
+    pub papers: Vec<P>,
+}
+
+/// Keywords counted so far by a [`TrendingAnalyzer`], in the order they
+/// were met.
+#[derive(Debug, Clone)]
+pub struct Tally<P> {
+    index: HashMap<String, usize>,
+    trends: Vec<TrendingKeyword<P>>,
+    /// Papers of the keywords counted with `add_authored`, by keyword index.
+    authors: HashMap<usize, AuthorGroups>,
+}
+
+impl<P> Default for Tally<P> {
+    fn default() -> Self {
+        Self {
+            index: HashMap::new(),
+            trends: Vec::new(),
+            authors: HashMap::new(),
+        }
+    }
+}
+
+impl<P> Tally<P> {
+    /// Counts `paper` towards `keyword` with `weight`, 1.0 for a plain count.
+    pub fn add(&mut self, keyword: &str, paper: P, weight: f64) {
+        self.push(keyword, paper, weight);
+    }
+
+    /// Like `add`, but papers of `keyword` sharing authors count once, with
+    /// the weight of the heaviest of them.
+    pub fn add_authored(&mut self, keyword: &str, paper: P, weight: f64, authors: &[String]) {
+        let i = self.push(keyword, paper, weight);
+        self.authors.entry(i).or_default().add(authors, weight);
+    }
+
+    fn push(&mut self, keyword: &str, paper: P, weight: f64) -> usize {
+        let i = match self.index.get(keyword) {
+            Some(i) => *i,
+            None => {
+                self.index.insert(keyword.to_string(), self.trends.len());
+                self.trends.push(TrendingKeyword {
+                    keyword: keyword.to_string(),
+                    score: 0.0,
+                    papers: Vec::new(),
+                });
+                self.trends.len() - 1
+            }
+        };
+        self.trends[i].score += weight;
+        self.trends[i].papers.push(paper);
+        i
+    }
+
+    /// Keywords counted.
+    pub fn len(&self) -> usize {
+        self.trends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trends.is_empty()
+    }
+
+    /// The best keywords by score, then by papers, then by `options.ties`.
+    /// Only the `options.k` kept are sorted.
+    pub fn top_k(mut self, options: TopK) -> Vec<TrendingKeyword<P>> {
+        for (i, mut authors) in self.authors.drain() {
+            self.trends[i].score = authors.score().0;
+        }
+        let mut ranked: Vec<(usize, TrendingKeyword<P>)> = self
+            .trends
+            .into_iter()
+            .enumerate()
+            .filter(|(_, trend)| trend.papers.len() >= options.min_support)
+            .collect();
+        let order = |(i, a): &(usize, TrendingKeyword<P>), (j, b): &(usize, TrendingKeyword<P>)| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then(b.papers.len().cmp(&a.papers.len()))
+                .then_with(|| match options.ties {
+                    TieBreak::Descending => b.keyword.cmp(&a.keyword),
+                    TieBreak::Ascending => a.keyword.cmp(&b.keyword),
+                    TieBreak::FirstSeen => i.cmp(j),
+                })
+        };
+        match options.k {
+            Some(0) => ranked.clear(),
+            Some(k) if k < ranked.len() => {
+                ranked.select_nth_unstable_by(k - 1, order);
+                ranked.truncate(k);
+            }
+            _ => {}
+        }
+        ranked.sort_unstable_by(order);
+        ranked.into_iter().map(|(_, trend)| trend).collect()
+    }
+}
 
 pub trait TrendingAnalyzer {
     type Raw;
     type Ctx;
 
-    fn top_k<F: Fn(&Self::Ctx) -> Vec<String>>(
+    /// Counts the keywords `f` picks out of one paper into `tally`, the
+    /// paper being `id` in the trends.
+    fn count<P: Clone, F: Fn(&Self::Ctx) -> Vec<String>>(
+        &self,
+        tally: &mut Tally<P>,
+        id: P,
+        raw: &Self::Raw,
+        ctx: &Self::Ctx,
+        f: &F,
+    );
+
+    /// Keywords `f` picks out of `data`, `(id, raw, ctx)` triples, ranked.
+    /// The papers of each keyword are given as their ids: pass references
+    /// to get the papers back, or indices not to hold them.
+    fn trends<P, R, C, I, F>(&self, data: I, f: F, options: TopK) -> Vec<TrendingKeyword<P>>
+    where
+        P: Clone,
+        R: Borrow<Self::Raw>,
+        C: Borrow<Self::Ctx>,
+        I: IntoIterator<Item = (P, R, C)>,
+        F: Fn(&Self::Ctx) -> Vec<String>,
+    {
+        let mut tally = Tally::default();
+        for (id, raw, ctx) in data {
+            self.count(&mut tally, id, raw.borrow(), ctx.borrow(), &f);
+        }
+        tally.top_k(options)
+    }
+
+    /// The `k` keywords `f` picks out of `data`, with their papers.
+    fn top_k<'a, F: Fn(&Self::Ctx) -> Vec<String>>(
         &self,
-        data: &Vec<(Self::Raw, Self::Ctx)>,
+        data: &'a [(Self::Raw, Self::Ctx)],
         f: F,
-    ) -> Vec<(String, Vec<Self::Raw>)>;
+        k: usize,
+    ) -> Vec<TrendingKeyword<&'a Self::Raw>> {
+        self.trends(
+            data.iter().map(|(raw, ctx)| (raw, raw, ctx)),
+            f,
+            TopK::new(k),
+        )
+    }
+
+    /// [`TrendingAnalyzer::trends`] over a stream of papers, such as
+    /// [`stream_stored_papers`], holding one paper at a time.
+    fn trends_stream<P, S, E, F>(
+        &self,
+        stream: S,
+        f: F,
+        options: TopK,
+    ) -> impl Future<Output = Result<Vec<TrendingKeyword<P>>, E>>
+    where
+        P: Clone,
+        S: Stream<Item = Result<(P, Self::Raw, Self::Ctx), E>>,
+        F: Fn(&Self::Ctx) -> Vec<String>,
+    {
+        async move {
+            let mut tally = Tally::default();
+            let mut stream = pin!(stream);
+            while let Some((id, raw, ctx)) = stream.try_next().await? {
+                self.count(&mut tally, id, &raw, &ctx, &f);
+            }
+            Ok(tally.top_k(options))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::dao::add_paper_with_keywords;
+    use crate::extracters::topic::{ArxivTopicData, TopicConfidence};
+    use crate::models::{Keyword, Paper, PaperExtraction};
+
+    use super::stored_papers;
+
+    #[tokio::test]
+    async fn test_stored_canonical_keywords() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // both spellings merged into `RAG`, the second paper stored none
+        let raw = ["RAG", "retrieval-augmented generation"];
+        let papers: [(&str, &[(&str, f64)]); 2] = [("merged", &[("RAG", 1.0)]), ("unstored", &[])];
+        for (title, keywords) in papers {
+            let paper = Paper {
+                id: 0,
+                title: title.to_string(),
+                abstract_text: None,
+                publish_date: Some("2025-04-01".to_string()),
+                insert_date: "2025-04-01".to_string(),
+                url: format!("https://arxiv.org/pdf/{}", title),
+                comment: None,
+            };
+            let output = ArxivTopicData {
+                title: title.to_string(),
+                techniques_used: raw.iter().map(|k| k.to_string()).collect(),
+                confidence: Some(TopicConfidence {
+                    techniques_used: HashMap::from([
+                        (raw[0].to_string(), 1.0),
+                        (raw[1].to_string(), 0.5),
+                    ]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            let extraction = PaperExtraction {
+                id: 0,
+                paper_id: 0,
+                extracter: "topic".to_string(),
+                prompt_name: "topic".to_string(),
+                prompt_version: "1".to_string(),
+                prompt_hash: "hash".to_string(),
+                model: "mock".to_string(),
+                created_at: "2025-04-01".to_string(),
+                run_id: None,
+                prompt_tokens: None,
+                completion_tokens: None,
+                cost: None,
+                output: serde_json::to_string(&output).ok(),
+            };
+            let keywords: Vec<_> = keywords
+                .iter()
+                .map(|(keyword, confidence)| {
+                    let keyword = Keyword {
+                        id: 0,
+                        keyword: keyword.to_string(),
+                    };
+                    (keyword, Some(*confidence))
+                })
+                .collect();
+            add_paper_with_keywords(&pool, &paper, &keywords, Some(&extraction))
+                .await
+                .unwrap();
+        }
+
+        let data = stored_papers(&pool, "2025-03-01").await.unwrap();
+        let (_, merged) = &data[0];
+        assert_eq!(merged.techniques_used, vec!["RAG"]);
+        assert_eq!(merged.technique_confidence("RAG"), 1.0);
+        let confidence = merged.confidence.as_ref().unwrap();
+        assert_eq!(confidence.techniques_used.len(), 1);
+        let (_, unstored) = &data[1];
+        assert_eq!(unstored.techniques_used, raw);
+        assert_eq!(unstored.technique_confidence(raw[1]), 0.5);
+    }
 }
@@ -1,11 +1,9 @@
-use std::collections::HashMap;
-
 use arxiv::Arxiv;
 use derive_builder::Builder;
 
 use crate::extracters::topic::ArxivTopicData;
 
-use super::TrendingAnalyzer;
+use super::{Tally, TrendingAnalyzer};
 
 #[derive(Builder, Clone)]
 pub struct SimpleArixvTrendingAnalyzer {}
@@ -14,31 +12,16 @@ impl TrendingAnalyzer for SimpleArixvTrendingAnalyzer {
     type Raw = Arxiv;
     type Ctx = ArxivTopicData;
 
-    fn top_k<F: Fn(&Self::Ctx) -> Vec<String>>(
+    fn count<P: Clone, F: Fn(&Self::Ctx) -> Vec<String>>(
         &self,
-        data: &Vec<(Self::Raw, Self::Ctx)>,
-        f: F,
-    ) -> Vec<(String, Vec<Self::Raw>)> {
-        let mut cnt: HashMap<String, Vec<Self::Raw>> = HashMap::new();
-
-        for (raw, ctx) in data {
-            let problems = f(ctx).clone();
-            for problem in problems.iter() {
-                let entry = cnt.entry(problem.clone()).or_insert(Vec::new());
-                entry.push(raw.clone());
-            }
+        tally: &mut Tally<P>,
+        id: P,
+        _raw: &Self::Raw,
+        ctx: &Self::Ctx,
+        f: &F,
+    ) {
+        for keyword in f(ctx).iter() {
+            tally.add(keyword, id.clone(), 1.0);
         }
-
-        let mut sorted_problems: Vec<(String, Vec<Self::Raw>)> = cnt
-            .into_iter()
-            .map(|(problem, raws)| {
-                // println!("problem: {} = {}", problem, raws.len());
-                (problem, raws)
-            })
-            .collect();
-
-        sorted_problems.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(b.0.cmp(&a.0))); // Sort by the length of associated raws (i.e., count)
-
-        sorted_problems
     }
 }
@@ -0,0 +1,86 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, OnceLock},
+};
+
+use arxiv::Arxiv;
+use derive_builder::Builder;
+
+use crate::{
+    extracters::topic::ArxivTopicData,
+    normalizers::keyword::rule_key,
+    taxonomy::{mapper::TaxonomyMatch, Taxonomy},
+};
+
+use super::{Tally, TrendingAnalyzer};
+
+/// Trends of taxonomy nodes instead of raw keywords.
+///
+/// Keywords are looked up in `matches` (see
+/// [`TaxonomyMapper`](crate::taxonomy::mapper::TaxonomyMapper)) and every
+/// matched node is rolled up to its ancestor at `depth`, or with no `depth`
+/// counted on each node up to the root. A paper counts once per node, trends
+/// are keyed by node label.
+#[derive(Builder, Clone)]
+pub struct TaxonomyTrendingAnalyzer {
+    taxonomy: Arc<Taxonomy>,
+    matches: Vec<TaxonomyMatch>,
+    #[builder(default, setter(strip_option))]
+    depth: Option<usize>,
+    #[builder(setter(skip))]
+    assigned: OnceLock<HashMap<String, Vec<String>>>,
+}
+
+impl TaxonomyTrendingAnalyzer {
+    /// Nodes matched by each keyword, by [`rule_key`].
+    fn assigned(&self) -> &HashMap<String, Vec<String>> {
+        self.assigned.get_or_init(|| {
+            let mut assigned: HashMap<String, Vec<String>> = HashMap::new();
+            for m in self.matches.iter() {
+                assigned
+                    .entry(rule_key(&m.keyword))
+                    .or_default()
+                    .push(m.node.clone());
+            }
+            assigned
+        })
+    }
+
+    /// Ids of `nodes` after rolling them up.
+    fn roll_up(&self, nodes: &[&str]) -> HashSet<&str> {
+        nodes
+            .iter()
+            .flat_map(|node| match self.depth {
+                Some(depth) => self.taxonomy.ancestor_at(node, depth).into_iter().collect(),
+                None => self.taxonomy.path(node),
+            })
+            .map(|node| node.id.as_str())
+            .collect()
+    }
+}
+
+impl TrendingAnalyzer for TaxonomyTrendingAnalyzer {
+    type Raw = Arxiv;
+    type Ctx = ArxivTopicData;
+
+    fn count<P: Clone, F: Fn(&Self::Ctx) -> Vec<String>>(
+        &self,
+        tally: &mut Tally<P>,
+        id: P,
+        _raw: &Self::Raw,
+        ctx: &Self::Ctx,
+        f: &F,
+    ) {
+        let nodes: Vec<&str> = f(ctx)
+            .iter()
+            .filter_map(|keyword| self.assigned().get(&rule_key(keyword)))
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        for node in self.roll_up(&nodes) {
+            if let Some(node) = self.taxonomy.get(node) {
+                tally.add(&node.label, id.clone(), 1.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,539 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    sync::OnceLock,
+};
+
+use arxiv::Arxiv;
+use chrono::{Local, NaiveDate};
+use derive_builder::Builder;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::json;
+use sqlx::SqlitePool;
+
+use crate::{
+    dao::{PaperDAO, TopicDAO},
+    models::{PaperTopic, StoredTopic, TopicModelRun},
+};
+
+use super::trend::{parse_day, Granularity};
+
+/// Weights of a topic in a paper below which they are not stored.
+pub const MIN_STORED_WEIGHT: f64 = 0.05;
+
+/// Words too common in abstracts to tell topics apart.
+const STOPWORDS: &str = "\
+    about above across after again against all also among and any approach are based been \
+    before being between both but can could demonstrate does during each either existing \
+    experiments few for from further has have how however into its itself many may method \
+    methods more most much new not novel our over paper per propose proposed provide results \
+    show shows significantly such than that the their them then there these they this those \
+    through thus under use used using various via was were what when where whether which \
+    while who whose why will with within without work would yet";
+
+/// Lowercase words of `text` worth counting in a topic model.
+fn tokenize(text: &str) -> Vec<String> {
+    static STOP: OnceLock<HashSet<&str>> = OnceLock::new();
+    let stopwords = STOP.get_or_init(|| STOPWORDS.split_whitespace().collect());
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3 && !w.chars().all(|c| c.is_numeric()))
+        .map(|w| w.to_lowercase())
+        .filter(|w| !stopwords.contains(w.as_str()))
+        .collect()
+}
+
+/// A topic, its top terms and its share of the corpus.
+#[derive(Debug, Clone)]
+pub struct Topic {
+    /// The first few top terms.
+    pub label: String,
+    /// Terms with their weights in the topic, the heaviest first.
+    pub terms: Vec<(String, f64)>,
+    /// Mean weight of the topic over the papers.
+    pub prevalence: f64,
+}
+
+impl Display for Topic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let terms: Vec<&str> = self.terms.iter().map(|(t, _)| t.as_str()).collect();
+        write!(
+            f,
+            "{} ({:.1}%): {}",
+            self.label,
+            self.prevalence * 100.0,
+            terms.join(", ")
+        )
+    }
+}
+
+/// Topics of a corpus and the topic distribution of each paper.
+#[derive(Debug, Clone)]
+pub struct TopicModel {
+    /// The most prevalent first.
+    pub topics: Vec<Topic>,
+    /// Weight of each topic in each paper, in the order the papers were
+    /// given, summing to 1 or all 0 for papers with no known term.
+    pub distributions: Vec<Vec<f64>>,
+    /// Day each paper was published.
+    pub days: Vec<Option<NaiveDate>>,
+}
+
+impl TopicModel {
+    /// The heaviest topic of paper `i`, if it has any.
+    pub fn dominant(&self, i: usize) -> Option<usize> {
+        self.distributions[i]
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| **w > 0.0)
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(t, _)| t)
+    }
+
+    /// Mean topic distribution of the papers of each bucket, oldest first.
+    /// Papers without a publish date are left out.
+    pub fn prevalence(&self, granularity: Granularity) -> Vec<(NaiveDate, Vec<f64>)> {
+        let mut buckets: BTreeMap<NaiveDate, (Vec<f64>, usize)> = BTreeMap::new();
+        for (day, distribution) in self.days.iter().zip(self.distributions.iter()) {
+            let Some(day) = day else {
+                continue;
+            };
+            let (sum, papers) = buckets
+                .entry(granularity.bucket(*day))
+                .or_insert_with(|| (vec![0.0; self.topics.len()], 0));
+            for (s, w) in sum.iter_mut().zip(distribution) {
+                *s += w;
+            }
+            *papers += 1;
+        }
+        buckets
+            .into_iter()
+            .map(|(bucket, (sum, papers))| {
+                (bucket, sum.into_iter().map(|s| s / papers as f64).collect())
+            })
+            .collect()
+    }
+}
+
+/// Topic modeling of titles and abstracts by non-negative matrix
+/// factorization of their TF-IDF matrix, `X ≈ W H` with Lee and Seung's
+/// multiplicative updates. Rows of `H` are the topics over the terms, rows of
+/// `W` the topics of each paper.
+///
+/// Unlike the extracted keywords it needs no LLM, so the two can be checked
+/// against each other.
+#[derive(Builder, Clone, Debug)]
+pub struct NmfTopicModeler {
+    #[builder(default = "10")]
+    topics: usize,
+    /// Papers a term needs to be in the vocabulary.
+    #[builder(default = "2")]
+    min_df: usize,
+    /// Share of the papers above which a term is too common to tell topics apart.
+    #[builder(default = "0.5")]
+    max_df: f64,
+    /// Most frequent terms kept in the vocabulary.
+    #[builder(default = "5000")]
+    max_terms: usize,
+    #[builder(default = "200")]
+    iterations: usize,
+    /// Seed of the initial factors, the same seed gives the same topics.
+    #[builder(default = "42")]
+    seed: u64,
+    /// Top terms kept per topic, the first three of them label it.
+    #[builder(default = "10")]
+    terms: usize,
+}
+
+impl NmfTopicModeler {
+    /// Topics of the titles and abstracts of `papers`.
+    pub fn fit(&self, papers: &[Arxiv]) -> TopicModel {
+        let docs: Vec<Vec<String>> = papers
+            .iter()
+            .map(|p| tokenize(&format!("{}\n{}", p.title, p.summary)))
+            .collect();
+        let days = papers.iter().map(|p| parse_day(&p.published)).collect();
+        let (vocabulary, x) = self.tfidf(&docs);
+        let k = self.topics.min(vocabulary.len()).min(docs.len());
+        if k == 0 {
+            return TopicModel {
+                topics: Vec::new(),
+                distributions: vec![Vec::new(); docs.len()],
+                days,
+            };
+        }
+        let (w, h) = self.factorize(&x, vocabulary.len(), k);
+
+        let distributions: Vec<Vec<f64>> = w
+            .iter()
+            .map(|row| {
+                let sum: f64 = row.iter().sum();
+                if sum > 0.0 {
+                    row.iter().map(|v| v / sum).collect()
+                } else {
+                    vec![0.0; k]
+                }
+            })
+            .collect();
+        let mut topics: Vec<(Topic, usize)> = (0..k)
+            .map(|t| {
+                let mut terms: Vec<(String, f64)> = h[t]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, v)| **v > 0.0)
+                    .map(|(j, v)| (vocabulary[j].clone(), *v))
+                    .collect();
+                terms.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+                terms.truncate(self.terms);
+                let label: Vec<&str> = terms.iter().take(3).map(|(t, _)| t.as_str()).collect();
+                let prevalence =
+                    distributions.iter().map(|d| d[t]).sum::<f64>() / docs.len() as f64;
+                let topic = Topic {
+                    label: label.join(", "),
+                    terms,
+                    prevalence,
+                };
+                (topic, t)
+            })
+            .collect();
+        topics.sort_by(|a, b| b.0.prevalence.total_cmp(&a.0.prevalence));
+
+        let distributions = distributions
+            .iter()
+            .map(|d| topics.iter().map(|(_, t)| d[*t]).collect())
+            .collect();
+        TopicModel {
+            topics: topics.into_iter().map(|(topic, _)| topic).collect(),
+            distributions,
+            days,
+        }
+    }
+
+    /// Vocabulary and L2-normalized sublinear TF-IDF rows of `docs`, as
+    /// `(term, value)` pairs.
+    fn tfidf(&self, docs: &[Vec<String>]) -> (Vec<String>, Vec<Vec<(usize, f64)>>) {
+        let mut df: HashMap<&str, usize> = HashMap::new();
+        for doc in docs {
+            let mut terms: Vec<&str> = doc.iter().map(String::as_str).collect();
+            terms.sort_unstable();
+            terms.dedup();
+            for term in terms {
+                *df.entry(term).or_default() += 1;
+            }
+        }
+        let max_df = (self.max_df * docs.len() as f64).max(1.0);
+        let mut vocabulary: Vec<(&str, usize)> = df
+            .into_iter()
+            .filter(|(_, df)| *df >= self.min_df && *df as f64 <= max_df)
+            .collect();
+        vocabulary.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        vocabulary.truncate(self.max_terms);
+        vocabulary.sort();
+        let index: HashMap<&str, usize> = vocabulary
+            .iter()
+            .enumerate()
+            .map(|(j, (term, _))| (*term, j))
+            .collect();
+        let n = docs.len() as f64;
+        let idf: Vec<f64> = vocabulary
+            .iter()
+            .map(|(_, df)| ((1.0 + n) / (1.0 + *df as f64)).ln() + 1.0)
+            .collect();
+
+        let rows = docs
+            .iter()
+            .map(|doc| {
+                let mut tf: BTreeMap<usize, usize> = BTreeMap::new();
+                for j in doc.iter().filter_map(|term| index.get(term.as_str())) {
+                    *tf.entry(*j).or_default() += 1;
+                }
+                let mut row: Vec<(usize, f64)> = tf
+                    .into_iter()
+                    .map(|(j, tf)| (j, (1.0 + (tf as f64).ln()) * idf[j]))
+                    .collect();
+                let norm = row.iter().map(|(_, v)| v * v).sum::<f64>().sqrt();
+                for (_, v) in row.iter_mut() {
+                    *v /= norm;
+                }
+                row
+            })
+            .collect();
+        let vocabulary = vocabulary.into_iter().map(|(t, _)| t.to_string()).collect();
+        (vocabulary, rows)
+    }
+
+    /// `W` (papers × k) and `H` (k × terms) minimizing `|X - W H|²`.
+    fn factorize(
+        &self,
+        x: &[Vec<(usize, f64)>],
+        terms: usize,
+        k: usize,
+    ) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        const EPS: f64 = 1e-10;
+        let n = x.len();
+        let mean = x.iter().flatten().map(|(_, v)| v).sum::<f64>() / (n * terms) as f64;
+        let scale = (mean / k as f64).sqrt();
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut w: Vec<Vec<f64>> = (0..n)
+            .map(|_| (0..k).map(|_| rng.gen::<f64>() * scale).collect())
+            .collect();
+        let mut h: Vec<Vec<f64>> = (0..k)
+            .map(|_| (0..terms).map(|_| rng.gen::<f64>() * scale).collect())
+            .collect();
+        let gram = |a: &[Vec<f64>], b: &[Vec<f64>]| -> Vec<Vec<f64>> {
+            (0..k)
+                .map(|s| {
+                    (0..k)
+                        .map(|t| a.iter().zip(b).map(|(a, b)| a[s] * b[t]).sum())
+                        .collect()
+                })
+                .collect()
+        };
+
+        for _ in 0..self.iterations {
+            // H <- H * (W^T X) / (W^T W H)
+            let mut wtx = vec![vec![0.0; terms]; k];
+            for (row, w) in x.iter().zip(w.iter()) {
+                for (j, v) in row {
+                    for t in 0..k {
+                        wtx[t][*j] += w[t] * v;
+                    }
+                }
+            }
+            let wtw = gram(&w, &w);
+            for t in 0..k {
+                for j in 0..terms {
+                    let denominator: f64 = (0..k).map(|s| wtw[t][s] * h[s][j]).sum();
+                    h[t][j] *= wtx[t][j] / (denominator + EPS);
+                }
+            }
+
+            // W <- W * (X H^T) / (W H H^T)
+            let ht: Vec<Vec<f64>> = (0..terms)
+                .map(|j| h.iter().map(|h| h[j]).collect())
+                .collect();
+            let hht = gram(&ht, &ht);
+            for (row, w) in x.iter().zip(w.iter_mut()) {
+                let mut xht = vec![0.0; k];
+                for (j, v) in row {
+                    for (t, xht) in xht.iter_mut().enumerate() {
+                        *xht += v * h[t][*j];
+                    }
+                }
+                let whht: Vec<f64> = (0..k)
+                    .map(|t| (0..k).map(|s| w[s] * hht[s][t]).sum())
+                    .collect();
+                for t in 0..k {
+                    w[t] *= xht[t] / (whht[t] + EPS);
+                }
+            }
+        }
+        (w, h)
+    }
+
+    /// Topics of the papers stored since `since`, e.g. `2025-03-01`, with
+    /// the papers and their database ids in the order of the distributions.
+    pub async fn fit_stored(
+        &self,
+        pool: &SqlitePool,
+        since: &str,
+    ) -> Result<(Vec<(i64, Arxiv)>, TopicModel), sqlx::Error> {
+        let (ids, papers): (Vec<i64>, Vec<Arxiv>) = PaperDAO::list_papers(pool, since)
+            .await?
+            .into_iter()
+            .map(|paper| {
+                let raw = Arxiv {
+                    id: paper.url.clone(),
+                    title: paper.title,
+                    summary: paper.abstract_text.unwrap_or_default(),
+                    published: paper.publish_date.unwrap_or(paper.insert_date),
+                    pdf_url: paper.url,
+                    ..Default::default()
+                };
+                (paper.id, raw)
+            })
+            .unzip();
+        let model = self.fit(&papers);
+        Ok((ids.into_iter().zip(papers).collect(), model))
+    }
+}
+
+/// Store the topics of the papers with ids `paper_ids` and the weights of
+/// the topics in each paper above [`MIN_STORED_WEIGHT`], all or nothing,
+/// returning the id of the stored model.
+pub async fn save_topics(
+    pool: &SqlitePool,
+    paper_ids: &[i64],
+    model: &TopicModel,
+) -> Result<i64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let run = TopicModelRun {
+        id: 0,
+        method: "nmf".to_string(),
+        papers: paper_ids.len() as i64,
+        created_at: Local::now().to_string(),
+    };
+    let model_id = TopicDAO::create_model(&mut *tx, &run).await?;
+    let mut topic_ids = Vec::with_capacity(model.topics.len());
+    for (position, topic) in model.topics.iter().enumerate() {
+        let terms: Vec<&str> = topic.terms.iter().map(|(t, _)| t.as_str()).collect();
+        let topic = StoredTopic {
+            id: 0,
+            model_id,
+            position: position as i64,
+            label: topic.label.clone(),
+            terms: json!(terms).to_string(),
+            prevalence: topic.prevalence,
+        };
+        topic_ids.push(TopicDAO::create_topic(&mut *tx, &topic).await?);
+    }
+    for (paper_id, distribution) in paper_ids.iter().zip(model.distributions.iter()) {
+        for (topic_id, weight) in topic_ids.iter().zip(distribution) {
+            if *weight < MIN_STORED_WEIGHT {
+                continue;
+            }
+            let topic = PaperTopic {
+                paper_id: *paper_id,
+                topic_id: *topic_id,
+                weight: *weight,
+            };
+            TopicDAO::add_paper_topic(&mut *tx, &topic).await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(model_id)
+}
+
+/// Topics of the latest stored model with the extracted keywords of their
+/// papers, the `k` most associated first, to see where the two agree.
+pub async fn stored_topic_keywords(
+    pool: &SqlitePool,
+    k: usize,
+) -> Result<Vec<(String, Vec<(String, f64)>)>, sqlx::Error> {
+    let mut topics = Vec::new();
+    for topic in TopicDAO::latest_topics(pool).await? {
+        let keywords = TopicDAO::topic_keywords(pool, topic.id, k as i64)
+            .await?
+            .into_iter()
+            .map(|k| (k.keyword, k.weight))
+            .collect();
+        topics.push((topic.label, keywords));
+    }
+    Ok(topics)
+}
+
+#[cfg(test)]
+mod test {
+    use arxiv::Arxiv;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::{save_topics, tokenize, NmfTopicModelerBuilder};
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("We propose LLM-based fuzzing of the C11 compiler in 2025."),
+            vec!["llm", "fuzzing", "c11", "compiler"]
+        );
+    }
+
+    #[test]
+    fn test_fit() {
+        let texts = [
+            "fuzzing compilers finds crashes in compiler optimizations",
+            "coverage guided fuzzing of parsers finds crashes",
+            "grammar fuzzing of compilers and interpreters",
+            "protein folding predicts the structure of proteins",
+            "protein structure prediction with folding simulations",
+            "folding proteins into their native structure",
+        ];
+        let papers: Vec<Arxiv> = texts
+            .iter()
+            .map(|text| Arxiv {
+                title: text.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let model = NmfTopicModelerBuilder::default()
+            .topics(2)
+            .build()
+            .unwrap()
+            .fit(&papers);
+
+        assert_eq!(model.topics.len(), 2);
+        let fuzzing = model.dominant(0).unwrap();
+        assert_ne!(fuzzing, model.dominant(3).unwrap());
+        for i in 0..3 {
+            assert_eq!(model.dominant(i), Some(fuzzing));
+            assert_ne!(model.dominant(i + 3), Some(fuzzing));
+        }
+        assert!(model.topics[fuzzing].label.contains("fuzzing"));
+        let total: f64 = model.distributions[0].iter().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_save_stored() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // papers sharing a title are still told apart
+        let papers = [
+            (
+                "fuzzing",
+                "fuzzing compilers finds crashes in compiler optimizations",
+            ),
+            (
+                "fuzzing",
+                "coverage guided fuzzing of parsers finds crashes",
+            ),
+            ("fuzzing", "grammar fuzzing of compilers and interpreters"),
+            (
+                "folding",
+                "protein folding predicts the structure of proteins",
+            ),
+            (
+                "folding",
+                "protein structure prediction with folding simulations",
+            ),
+            ("folding", "folding proteins into their native structure"),
+        ];
+        for (i, (title, abstract_text)) in papers.into_iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO papers (title, abstract_text, publish_date, insert_date, url) VALUES (?, ?, '2025-04-01', '2025-04-01', ?)",
+            )
+            .bind(title)
+            .bind(abstract_text)
+            .bind(format!("https://arxiv.org/pdf/2504.{:05}v1", i))
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let (papers, model) = NmfTopicModelerBuilder::default()
+            .topics(2)
+            .build()
+            .unwrap()
+            .fit_stored(&pool, "2025-03-01")
+            .await
+            .unwrap();
+        let ids: Vec<i64> = papers.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, (1..=6).collect::<Vec<_>>());
+        save_topics(&pool, &ids, &model).await.unwrap();
+
+        let stored: Vec<i64> =
+            sqlx::query_scalar("SELECT DISTINCT paper_id FROM paper_topics ORDER BY paper_id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(stored, ids);
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM papers")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 6);
+    }
+}
@@ -0,0 +1,383 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+use arxiv::Arxiv;
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
+use derive_builder::Builder;
+use sqlx::SqlitePool;
+
+use crate::{dao::PaperKeywordDAO, extracters::topic::ArxivTopicData};
+
+/// Length of the buckets keyword counts are summed over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Granularity {
+    Day,
+    /// Monday to Sunday.
+    #[default]
+    Week,
+    Month,
+}
+
+impl Granularity {
+    /// First day of the bucket `date` falls in.
+    pub fn bucket(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => date,
+            Self::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+            Self::Month => date.with_day(1).unwrap(),
+        }
+    }
+
+    /// First day of the bucket `n` buckets after the one of `date`.
+    pub fn add(self, date: NaiveDate, n: u32) -> NaiveDate {
+        let bucket = self.bucket(date);
+        match self {
+            Self::Day => bucket + Days::new(n as u64),
+            Self::Week => bucket + Days::new(7 * n as u64),
+            Self::Month => bucket + Months::new(n),
+        }
+    }
+
+    /// First day of the bucket `n` buckets before the one of `date`.
+    pub fn sub(self, date: NaiveDate, n: u32) -> NaiveDate {
+        let bucket = self.bucket(date);
+        match self {
+            Self::Day => bucket - Days::new(n as u64),
+            Self::Week => bucket - Days::new(7 * n as u64),
+            Self::Month => bucket - Months::new(n),
+        }
+    }
+
+    /// Last day of the bucket before the one of `today`, the latest bucket
+    /// that is over.
+    pub fn last_complete(self, today: NaiveDate) -> NaiveDate {
+        self.bucket(today) - Days::new(1)
+    }
+}
+
+impl FromStr for Granularity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            _ => Err(format!(
+                "unknown granularity {:?}, use day, week or month",
+                s
+            )),
+        }
+    }
+}
+
+/// Day a paper was published on, from arXiv's `2025-02-14T18:00:00Z` or a
+/// stored date.
+pub fn parse_day(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Papers per keyword and day published since `since`, from the database.
+pub async fn daily_counts(
+    pool: &SqlitePool,
+    since: NaiveDate,
+) -> Result<Vec<(String, NaiveDate, usize)>, sqlx::Error> {
+    Ok(
+        PaperKeywordDAO::daily_counts(pool, &since.format("%Y-%m-%d").to_string())
+            .await?
+            .into_iter()
+            .filter_map(|c| Some((c.keyword, parse_day(&c.day)?, c.papers as usize)))
+            .collect(),
+    )
+}
+
+/// Papers per keyword and day published, from extracted papers. A paper
+/// counts once per keyword `f` returns.
+pub fn daily_counts_of<F: Fn(&ArxivTopicData) -> Vec<String>>(
+    data: &[(Arxiv, ArxivTopicData)],
+    f: F,
+) -> Vec<(String, NaiveDate, usize)> {
+    let mut counts: BTreeMap<(NaiveDate, String), usize> = BTreeMap::new();
+    for (raw, ctx) in data {
+        let Some(day) = parse_day(&raw.published) else {
+            continue;
+        };
+        let keywords: HashSet<String> = f(ctx).into_iter().collect();
+        for keyword in keywords {
+            *counts.entry((day, keyword)).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .map(|((day, keyword), papers)| (keyword, day, papers))
+        .collect()
+}
+
+/// Paper counts per keyword over consecutive buckets, empty buckets included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeywordSeries {
+    granularity: Granularity,
+    /// First day of each bucket.
+    buckets: Vec<NaiveDate>,
+    counts: HashMap<String, Vec<usize>>,
+}
+
+impl KeywordSeries {
+    /// Bucket `counts` from the bucket of `from` to the one of `until`, both
+    /// included. Counts outside of them are left out.
+    pub fn new(
+        granularity: Granularity,
+        from: NaiveDate,
+        until: NaiveDate,
+        counts: &[(String, NaiveDate, usize)],
+    ) -> Self {
+        let mut buckets = Vec::new();
+        let mut bucket = granularity.bucket(from);
+        while bucket <= until {
+            buckets.push(bucket);
+            bucket = granularity.add(bucket, 1);
+        }
+        let mut series: HashMap<String, Vec<usize>> = HashMap::new();
+        for (keyword, day, papers) in counts {
+            let Ok(i) = buckets.binary_search(&granularity.bucket(*day)) else {
+                continue;
+            };
+            series
+                .entry(keyword.clone())
+                .or_insert_with(|| vec![0; buckets.len()])[i] += papers;
+        }
+        Self {
+            granularity,
+            buckets,
+            counts: series,
+        }
+    }
+
+    pub fn granularity(&self) -> Granularity {
+        self.granularity
+    }
+
+    pub fn buckets(&self) -> &[NaiveDate] {
+        &self.buckets
+    }
+
+    pub fn get(&self, keyword: &str) -> Option<&[usize]> {
+        self.counts.get(keyword).map(Vec::as_slice)
+    }
+
+    /// Every keyword with its counts, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[usize])> {
+        self.counts.iter().map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+}
+
+/// How a keyword did in the window compared to the baseline before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trend {
+    pub keyword: String,
+    /// Papers in the window.
+    pub papers: usize,
+    /// Papers per bucket in the window.
+    pub mean: f64,
+    /// Papers per bucket in the baseline.
+    pub baseline_mean: f64,
+    /// Relative change of `mean` over `baseline_mean`, `None` for keywords
+    /// absent from the baseline.
+    pub growth: Option<f64>,
+    /// Standard deviations `mean` is above the baseline.
+    pub z_score: f64,
+}
+
+impl Display for Trend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let growth = match self.growth {
+            Some(growth) => format!("{:+.0}%", growth * 100.0),
+            None => "new".to_string(),
+        };
+        write!(
+            f,
+            "{}: {} papers, {:.1} per bucket vs {:.1}, {}, z {:.2}",
+            self.keyword, self.papers, self.mean, self.baseline_mean, growth, self.z_score
+        )
+    }
+}
+
+/// Ranks keywords by momentum rather than volume: how far their count in the
+/// last `window` buckets rises above the `baseline` buckets before it, so
+/// perennial keywords like "LLM" only rank when they grow.
+///
+/// The z-score compares the window's mean with the baseline's, the baseline's
+/// deviation being floored by Poisson noise so that a flat or empty baseline
+/// does not make any paper a surge.
+#[derive(Builder, Clone, Debug)]
+pub struct WindowedTrendAnalyzer {
+    #[builder(default)]
+    granularity: Granularity,
+    /// Buckets ranked on, the latest ones.
+    #[builder(default = "1")]
+    window: u32,
+    /// Buckets before the window to compare with.
+    #[builder(default = "4")]
+    baseline: u32,
+    /// Papers a keyword needs in the window to rank at all.
+    #[builder(default = "2")]
+    min_papers: usize,
+    /// Last day of the window, the latest day of the counts or, for the
+    /// database, the end of the last complete bucket if unset.
+    #[builder(default, setter(strip_option))]
+    until: Option<NaiveDate>,
+}
+
+impl WindowedTrendAnalyzer {
+    /// First day of the baseline when the window ends on `until`.
+    pub fn since(&self, until: NaiveDate) -> NaiveDate {
+        self.granularity.sub(until, self.window + self.baseline - 1)
+    }
+
+    /// Keywords of `counts` ranked by z-score, then papers in the window.
+    pub fn rank(&self, counts: &[(String, NaiveDate, usize)]) -> Vec<Trend> {
+        let Some(until) = self
+            .until
+            .or_else(|| counts.iter().map(|(_, day, _)| *day).max())
+        else {
+            return Vec::new();
+        };
+        let series = KeywordSeries::new(self.granularity, self.since(until), until, counts);
+        self.rank_series(&series)
+    }
+
+    /// Keywords of `series` ranked on its last buckets.
+    pub fn rank_series(&self, series: &KeywordSeries) -> Vec<Trend> {
+        let window = (self.window as usize).min(series.buckets().len()).max(1);
+        let mut trends: Vec<Trend> = series
+            .iter()
+            .filter_map(|(keyword, counts)| {
+                let split = counts.len().checked_sub(window)?;
+                let start = split.saturating_sub(self.baseline as usize);
+                let trend = Self::trend(keyword, &counts[start..split], &counts[split..]);
+                (trend.papers >= self.min_papers).then_some(trend)
+            })
+            .collect();
+        trends.sort_by(|a, b| {
+            b.z_score
+                .partial_cmp(&a.z_score)
+                .unwrap_or(Ordering::Equal)
+                .then(b.papers.cmp(&a.papers))
+                .then(a.keyword.cmp(&b.keyword))
+        });
+        trends
+    }
+
+    /// Keywords ranked on the stored papers.
+    pub async fn rank_stored(&self, pool: &SqlitePool) -> Result<Vec<Trend>, sqlx::Error> {
+        // the current bucket is not over, its few papers would look like a drop
+        let until = self
+            .until
+            .unwrap_or_else(|| self.granularity.last_complete(Local::now().date_naive()));
+        let counts = daily_counts(pool, self.since(until)).await?;
+        Ok(Self {
+            until: Some(until),
+            ..self.clone()
+        }
+        .rank(&counts))
+    }
+
+    fn trend(keyword: &str, baseline: &[usize], window: &[usize]) -> Trend {
+        let papers: usize = window.iter().sum();
+        let mean = papers as f64 / window.len() as f64;
+        let (baseline_mean, deviation) = mean_deviation(baseline);
+        let growth = (baseline_mean > 0.0).then(|| mean / baseline_mean - 1.0);
+        // counts are at least as noisy as a Poisson process of the same mean
+        let noise = deviation.max(baseline_mean.sqrt()).max(1.0);
+        let z_score = (mean - baseline_mean) / (noise / (window.len() as f64).sqrt());
+        Trend {
+            keyword: keyword.to_string(),
+            papers,
+            mean,
+            baseline_mean,
+            growth,
+            z_score,
+        }
+    }
+}
+
+fn mean_deviation(counts: &[usize]) -> (f64, f64) {
+    if counts.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = counts.len() as f64;
+    let mean = counts.iter().sum::<usize>() as f64 / n;
+    let variance = counts
+        .iter()
+        .map(|&c| (c as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    (mean, variance.sqrt())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use super::{Granularity, KeywordSeries, WindowedTrendAnalyzerBuilder};
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_granularity() {
+        // a Wednesday
+        let date = day("2025-04-16");
+        assert_eq!(Granularity::Week.bucket(date), day("2025-04-14"));
+        assert_eq!(Granularity::Week.sub(date, 2), day("2025-03-31"));
+        assert_eq!(Granularity::Month.bucket(date), day("2025-04-01"));
+        assert_eq!(Granularity::Month.sub(date, 4), day("2024-12-01"));
+        assert_eq!(Granularity::Day.add(date, 1), day("2025-04-17"));
+        assert_eq!(Granularity::Week.last_complete(date), day("2025-04-13"));
+        assert_eq!(Granularity::Month.last_complete(date), day("2025-03-31"));
+
+        let counts = vec![
+            ("LLM".to_string(), day("2025-04-01"), 2),
+            ("LLM".to_string(), day("2025-04-16"), 1),
+            ("LLM".to_string(), day("2025-05-01"), 5),
+        ];
+        let series = KeywordSeries::new(
+            Granularity::Week,
+            day("2025-03-31"),
+            day("2025-04-20"),
+            &counts,
+        );
+        assert_eq!(series.buckets().len(), 3);
+        assert_eq!(series.get("LLM"), Some(&[2, 0, 1][..]));
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut counts = Vec::new();
+        for week in 0..5 {
+            let monday = Granularity::Week.add(day("2025-03-03"), week);
+            // steady and popular
+            counts.push(("LLM".to_string(), monday, 20 + week as usize % 2));
+            // rising in the last week
+            let agents = if week == 4 { 8 } else { 1 };
+            counts.push(("agents".to_string(), monday, agents));
+        }
+        counts.push(("diffusion".to_string(), day("2025-03-31"), 3));
+
+        let trends = WindowedTrendAnalyzerBuilder::default()
+            .build()
+            .unwrap()
+            .rank(&counts);
+        let keywords: Vec<&str> = trends.iter().map(|t| t.keyword.as_str()).collect();
+        assert_eq!(keywords, vec!["agents", "diffusion", "LLM"]);
+        assert_eq!(trends[0].papers, 8);
+        assert_eq!(trends[0].growth, Some(7.0));
+        assert_eq!(trends[1].growth, None);
+        assert!(trends[2].z_score.abs() < 1.0);
+    }
+}
@@ -0,0 +1,594 @@
+use std::{cmp::Ordering, collections::HashMap, fmt::Display, fs, io, path::Path, sync::Arc};
+
+use arxiv::Arxiv;
+use chrono::NaiveDate;
+use derive_builder::Builder;
+use regex::Regex;
+
+use crate::{config::TrendingConfig, extracters::topic::ArxivTopicData};
+
+use super::{trend::parse_day, Tally, TrendingAnalyzer};
+
+/// A keyword as it appears in one paper.
+pub struct Mention<'a> {
+    pub raw: &'a Arxiv,
+    pub ctx: &'a ArxivTopicData,
+    pub keyword: &'a str,
+    /// Index of the keyword in the paper's keywords, the main ones come first.
+    pub position: usize,
+}
+
+/// How much one paper counts towards a keyword's score, 1.0 for a plain count.
+pub trait KeywordScorer: Send + Sync {
+    /// Name in score breakdowns.
+    fn name(&self) -> &str;
+
+    fn weight(&self, mention: &Mention) -> f64;
+}
+
+/// A scorer of a [`WeightedTrendingAnalyzer`].
+#[derive(Clone)]
+pub struct TrendScorer {
+    scorer: Arc<dyn KeywordScorer>,
+}
+
+impl<S: KeywordScorer + 'static> From<S> for TrendScorer {
+    fn from(scorer: S) -> Self {
+        Self {
+            scorer: Arc::new(scorer),
+        }
+    }
+}
+
+/// `2504.00001` out of `http://arxiv.org/abs/2504.00001v2`.
+fn arxiv_key(id: &str) -> &str {
+    let id = id.rsplit_once("/abs/").map_or(id, |(_, id)| id);
+    match id.rfind('v') {
+        Some(v) if v > 0 && id[v + 1..].chars().all(|c| c.is_ascii_digit()) => &id[..v],
+        _ => id,
+    }
+}
+
+/// Papers weighted by `1 + ln(1 + citations)`, so that a few cited papers
+/// outweigh many uncited ones without a single one dominating. Counts are
+/// keyed by arXiv id without version, e.g. `2504.00001`, from a source like
+/// Semantic Scholar; papers without one count as uncited.
+pub struct CitationWeight {
+    citations: HashMap<String, u32>,
+}
+
+impl CitationWeight {
+    pub fn new(citations: HashMap<String, u32>) -> Self {
+        Self { citations }
+    }
+
+    /// Load a JSON object of counts by arXiv id, e.g. `{"2504.00001": 12}`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let citations = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(Self::new(citations))
+    }
+}
+
+impl KeywordScorer for CitationWeight {
+    fn name(&self) -> &str {
+        "citations"
+    }
+
+    fn weight(&self, mention: &Mention) -> f64 {
+        let citations = self
+            .citations
+            .get(arxiv_key(&mention.raw.id))
+            .copied()
+            .unwrap_or(0);
+        1.0 + (citations as f64).ln_1p()
+    }
+}
+
+/// Papers counting half every `half_life` days before `today`. Papers
+/// without a publish date are not decayed.
+pub struct RecencyDecay {
+    half_life: f64,
+    today: NaiveDate,
+}
+
+impl RecencyDecay {
+    /// `half_life` must be positive, see [`TrendingConfig::validate`].
+    pub fn new(half_life: f64, today: NaiveDate) -> Self {
+        Self { half_life, today }
+    }
+}
+
+impl KeywordScorer for RecencyDecay {
+    fn name(&self) -> &str {
+        "recency"
+    }
+
+    fn weight(&self, mention: &Mention) -> f64 {
+        match parse_day(&mention.raw.published) {
+            Some(day) => {
+                let age = (self.today - day).num_days().max(0) as f64;
+                0.5f64.powf(age / self.half_life)
+            }
+            None => 1.0,
+        }
+    }
+}
+
+/// Papers weighted by the share of the ensemble that agreed on the keyword,
+/// see [`TopicConfidence`](crate::extracters::topic::TopicConfidence).
+pub struct ConfidenceWeight;
+
+impl KeywordScorer for ConfidenceWeight {
+    fn name(&self) -> &str {
+        "confidence"
+    }
+
+    fn weight(&self, mention: &Mention) -> f64 {
+        let Some(confidence) = &mention.ctx.confidence else {
+            return 1.0;
+        };
+        [
+            &confidence.solved_problem,
+            &confidence.research_field,
+            &confidence.techniques_used,
+        ]
+        .iter()
+        .find_map(|facet| facet.get(mention.keyword).copied())
+        .unwrap_or(1.0)
+    }
+}
+
+/// Papers weighted by `1 / (1 + decay * position)`, counting less where the
+/// keyword is listed after the paper's main ones.
+pub struct PositionWeight {
+    decay: f64,
+}
+
+impl PositionWeight {
+    /// `decay` must be finite and not negative, see [`TrendingConfig::validate`].
+    pub fn new(decay: f64) -> Self {
+        Self { decay }
+    }
+}
+
+impl KeywordScorer for PositionWeight {
+    fn name(&self) -> &str {
+        "position"
+    }
+
+    fn weight(&self, mention: &Mention) -> f64 {
+        1.0 / (1.0 + self.decay * mention.position as f64)
+    }
+}
+
+/// Papers weighted by the venue their arXiv comment names, e.g. `Accepted
+/// at ICSE 2025`, the heaviest if it names several. Others count 1.0.
+pub struct VenueWeight {
+    venues: Vec<(Regex, f64)>,
+}
+
+impl VenueWeight {
+    /// Venues are matched as whole words, ignoring case.
+    pub fn new<S: AsRef<str>>(venues: impl IntoIterator<Item = (S, f64)>) -> Self {
+        let venues = venues
+            .into_iter()
+            .map(|(venue, weight)| {
+                let pattern = format!(r"(?i)\b{}\b", regex::escape(venue.as_ref()));
+                (Regex::new(&pattern).unwrap(), weight)
+            })
+            .collect();
+        Self { venues }
+    }
+}
+
+impl KeywordScorer for VenueWeight {
+    fn name(&self) -> &str {
+        "venue"
+    }
+
+    fn weight(&self, mention: &Mention) -> f64 {
+        let Some(comment) = &mention.raw.comment else {
+            return 1.0;
+        };
+        self.venues
+            .iter()
+            .filter(|(venue, _)| venue.is_match(comment))
+            .map(|(_, weight)| *weight)
+            .max_by(f64::total_cmp)
+            .unwrap_or(1.0)
+    }
+}
+
+/// How a keyword's score came about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreBreakdown {
+    pub papers: usize,
+    /// Groups of papers linked by shared authors, `papers` when they are not
+    /// de-duplicated.
+    pub groups: usize,
+    /// Mean weight of the keyword's papers by each scorer, and with author
+    /// de-duplication the share of the score it kept, as `authors`.
+    pub factors: Vec<(String, f64)>,
+}
+
+/// A keyword ranked by [`WeightedTrendingAnalyzer`].
+#[derive(Debug, Clone)]
+pub struct WeightedKeyword<'a> {
+    pub keyword: String,
+    pub score: f64,
+    pub breakdown: ScoreBreakdown,
+    pub papers: Vec<&'a Arxiv>,
+}
+
+impl Display for WeightedKeyword<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {:.2} ({} papers",
+            self.keyword, self.score, self.breakdown.papers
+        )?;
+        if self.breakdown.groups != self.breakdown.papers {
+            write!(f, " in {} author groups", self.breakdown.groups)?;
+        }
+        let factors: Vec<String> = self
+            .breakdown
+            .factors
+            .iter()
+            .map(|(name, factor)| format!("{} x{:.2}", name, factor))
+            .collect();
+        if !factors.is_empty() {
+            write!(f, "; {}", factors.join(", "))?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Weighted papers of a keyword, grouped as they come: papers sharing an
+/// author directly or through other papers are in the same group. Papers
+/// without authors are alone.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AuthorGroups {
+    first: HashMap<String, usize>,
+    parent: Vec<usize>,
+    weights: Vec<f64>,
+}
+
+impl AuthorGroups {
+    fn root(&mut self, i: usize) -> usize {
+        let mut r = i;
+        while self.parent[r] != r {
+            r = self.parent[r];
+        }
+        self.parent[i] = r;
+        r
+    }
+
+    pub(crate) fn add(&mut self, authors: &[String], weight: f64) {
+        let i = self.parent.len();
+        self.parent.push(i);
+        self.weights.push(weight);
+        for author in authors {
+            match self.first.get(author) {
+                Some(&j) => {
+                    let (a, b) = (self.root(i), self.root(j));
+                    self.parent[a] = b;
+                }
+                None => {
+                    self.first.insert(author.clone(), i);
+                }
+            }
+        }
+    }
+
+    /// Group of each paper, in the order they were added.
+    fn groups(&mut self) -> Vec<usize> {
+        (0..self.parent.len()).map(|i| self.root(i)).collect()
+    }
+
+    /// Sum of the heaviest weight of each group, and the number of groups.
+    pub(crate) fn score(&mut self) -> (f64, usize) {
+        let mut heaviest: HashMap<usize, f64> = HashMap::new();
+        for (group, weight) in self.groups().into_iter().zip(self.weights.iter()) {
+            let max = heaviest.entry(group).or_insert(0.0);
+            *max = max.max(*weight);
+        }
+        (heaviest.values().sum(), heaviest.len())
+    }
+}
+
+/// Ranks keywords by the sum of their papers' weights instead of the number
+/// of papers. A paper's weight is the product of the weights of `scorers`,
+/// 1.0 without any, so that the ranking falls back to the plain count.
+///
+/// With `dedup_authors`, papers sharing authors count once, with the weight
+/// of the heaviest of them, so that one group's series of papers does not
+/// make a trend.
+#[derive(Builder, Clone)]
+pub struct WeightedTrendingAnalyzer {
+    #[builder(default, setter(each(name = "scorer", into)))]
+    scorers: Vec<TrendScorer>,
+    #[builder(default)]
+    dedup_authors: bool,
+}
+
+impl WeightedTrendingAnalyzer {
+    /// The analyzer set up by the `[trending]` section of the config,
+    /// decaying papers relative to `today`. Fails if the citation counts
+    /// cannot be read.
+    pub fn from_config(config: &TrendingConfig, today: NaiveDate) -> io::Result<Self> {
+        let mut builder = WeightedTrendingAnalyzerBuilder::default();
+        builder.dedup_authors(config.dedup_authors);
+        if let Some(path) = &config.citations {
+            builder.scorer(CitationWeight::from_file(path)?);
+        }
+        if !config.venues.is_empty() {
+            builder.scorer(VenueWeight::new(config.venues.clone()));
+        }
+        if let Some(half_life) = config.half_life_days {
+            builder.scorer(RecencyDecay::new(half_life, today));
+        }
+        if config.confidence {
+            builder.scorer(ConfidenceWeight);
+        }
+        if let Some(decay) = config.position_decay {
+            builder.scorer(PositionWeight::new(decay));
+        }
+        Ok(builder.build().unwrap())
+    }
+
+    /// Keywords `f` picks out of `data`, the highest score first.
+    pub fn rank<'a, F: Fn(&ArxivTopicData) -> Vec<String>>(
+        &self,
+        data: &'a [(Arxiv, ArxivTopicData)],
+        f: F,
+    ) -> Vec<WeightedKeyword<'a>> {
+        // papers of each keyword with the weight of each scorer
+        let mut mentions: HashMap<String, Vec<(&'a Arxiv, Vec<f64>)>> = HashMap::new();
+        for (raw, ctx) in data {
+            for (keyword, weights) in self.weigh(raw, ctx, &f) {
+                mentions.entry(keyword).or_default().push((raw, weights));
+            }
+        }
+
+        let mut ranked: Vec<WeightedKeyword> = mentions
+            .into_iter()
+            .map(|(keyword, papers)| self.score(keyword, papers))
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then(b.papers.len().cmp(&a.papers.len()))
+                .then(a.keyword.cmp(&b.keyword))
+        });
+        ranked
+    }
+
+    /// Each keyword `f` picks out of `ctx`, once, with the weight of each
+    /// scorer.
+    fn weigh<F: Fn(&ArxivTopicData) -> Vec<String>>(
+        &self,
+        raw: &Arxiv,
+        ctx: &ArxivTopicData,
+        f: F,
+    ) -> Vec<(String, Vec<f64>)> {
+        let keywords = f(ctx);
+        let mut weighed = Vec::with_capacity(keywords.len());
+        for (position, keyword) in keywords.iter().enumerate() {
+            if keywords[..position].contains(keyword) {
+                continue;
+            }
+            let mention = Mention {
+                raw,
+                ctx,
+                keyword,
+                position,
+            };
+            let weights = self
+                .scorers
+                .iter()
+                .map(|s| s.scorer.weight(&mention))
+                .collect();
+            weighed.push((keyword.clone(), weights));
+        }
+        weighed
+    }
+
+    fn score<'a>(
+        &self,
+        keyword: String,
+        papers: Vec<(&'a Arxiv, Vec<f64>)>,
+    ) -> WeightedKeyword<'a> {
+        let n = papers.len();
+        let weights: Vec<f64> = papers.iter().map(|(_, w)| w.iter().product()).collect();
+        let mut factors: Vec<(String, f64)> = self
+            .scorers
+            .iter()
+            .enumerate()
+            .map(|(s, scorer)| {
+                let mean = papers.iter().map(|(_, w)| w[s]).sum::<f64>() / n as f64;
+                (scorer.scorer.name().to_string(), mean)
+            })
+            .collect();
+
+        let total: f64 = weights.iter().sum();
+        let (score, groups) = if self.dedup_authors {
+            let mut groups = AuthorGroups::default();
+            for ((raw, _), weight) in papers.iter().zip(weights.iter()) {
+                groups.add(&raw.authors, *weight);
+            }
+            let (score, groups) = groups.score();
+            let kept = if total > 0.0 { score / total } else { 1.0 };
+            factors.push(("authors".to_string(), kept));
+            (score, groups)
+        } else {
+            (total, n)
+        };
+
+        WeightedKeyword {
+            keyword,
+            score,
+            breakdown: ScoreBreakdown {
+                papers: n,
+                groups,
+                factors,
+            },
+            papers: papers.into_iter().map(|(raw, _)| raw).collect(),
+        }
+    }
+}
+
+impl TrendingAnalyzer for WeightedTrendingAnalyzer {
+    type Raw = Arxiv;
+    type Ctx = ArxivTopicData;
+
+    /// Scores the paper's keywords as [`WeightedTrendingAnalyzer::rank`]
+    /// does. With `dedup_authors`, the tally groups each keyword's papers by
+    /// author and de-duplicates them in [`Tally::top_k`].
+    fn count<P: Clone, F: Fn(&Self::Ctx) -> Vec<String>>(
+        &self,
+        tally: &mut Tally<P>,
+        id: P,
+        raw: &Self::Raw,
+        ctx: &Self::Ctx,
+        f: &F,
+    ) {
+        for (keyword, weights) in self.weigh(raw, ctx, f) {
+            let weight = weights.iter().product();
+            if self.dedup_authors {
+                tally.add_authored(&keyword, id.clone(), weight, &raw.authors);
+            } else {
+                tally.add(&keyword, id.clone(), weight);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::analyzers::stored_papers;
+    use crate::dao::{AuthorDAO, ExtractionDAO, PaperDAO};
+    use crate::extracters::topic::ArxivTopicData;
+    use crate::models::{Paper, PaperExtraction};
+
+    use super::{arxiv_key, AuthorGroups, VenueWeight, WeightedTrendingAnalyzerBuilder};
+
+    #[test]
+    fn test_arxiv_key() {
+        assert_eq!(
+            arxiv_key("http://arxiv.org/abs/2504.00001v12"),
+            "2504.00001"
+        );
+        assert_eq!(arxiv_key("2504.00001"), "2504.00001");
+        assert_eq!(arxiv_key("http://arxiv.org/abs/cs/0112017v1"), "cs/0112017");
+    }
+
+    #[test]
+    fn test_author_groups() {
+        let papers: [&[&str]; 6] = [
+            &["Ada", "Bob"],
+            &["Cy"],
+            &["Bob", "Dee"],
+            &[],
+            &["Dee"],
+            &[],
+        ];
+        let mut authors = AuthorGroups::default();
+        for (i, paper) in papers.iter().enumerate() {
+            let paper: Vec<String> = paper.iter().map(|a| a.to_string()).collect();
+            authors.add(&paper, i as f64);
+        }
+        let groups = authors.groups();
+        assert_eq!(groups[0], groups[2]);
+        assert_eq!(groups[0], groups[4]);
+        assert_ne!(groups[0], groups[1]);
+        assert_ne!(groups[3], groups[5]);
+        // the heaviest of 0, 2 and 4, then 1, 3 and 5
+        assert_eq!(authors.score(), (13.0, 4));
+    }
+
+    #[tokio::test]
+    async fn test_stored_venue_and_authors() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // two papers of one group on `a`, one accepted paper on `b`
+        let papers = [
+            ("first", "a", None, ["Ada", "Bob"]),
+            ("second", "a", None, ["Bob", "Ada"]),
+            (
+                "accepted",
+                "b",
+                Some("Accepted at ICSE 2025"),
+                ["Cy", "Dee"],
+            ),
+        ];
+        for (title, keyword, comment, authors) in papers {
+            let paper = Paper {
+                id: 0,
+                title: title.to_string(),
+                abstract_text: None,
+                publish_date: Some("2025-04-01".to_string()),
+                insert_date: "2025-04-01".to_string(),
+                url: format!("https://arxiv.org/pdf/{}", title),
+                comment: comment.map(str::to_string),
+            };
+            let paper_id = PaperDAO::create_paper(&pool, &paper).await.unwrap();
+            let authors: Vec<String> = authors.iter().map(|a| a.to_string()).collect();
+            AuthorDAO::add_authors(&pool, &paper, &authors)
+                .await
+                .unwrap();
+            let output = ArxivTopicData {
+                title: title.to_string(),
+                techniques_used: vec![keyword.to_string()],
+                ..Default::default()
+            };
+            let extraction = PaperExtraction {
+                id: 0,
+                paper_id,
+                extracter: "topic".to_string(),
+                prompt_name: "topic".to_string(),
+                prompt_version: "1".to_string(),
+                prompt_hash: "hash".to_string(),
+                model: "mock".to_string(),
+                created_at: "2025-04-01".to_string(),
+                run_id: None,
+                prompt_tokens: None,
+                completion_tokens: None,
+                cost: None,
+                output: serde_json::to_string(&output).ok(),
+            };
+            ExtractionDAO::create_extraction(&pool, &extraction)
+                .await
+                .unwrap();
+        }
+
+        let data = stored_papers(&pool, "2025-03-01").await.unwrap();
+        assert_eq!(data[2].0.authors, vec!["Cy", "Dee"]);
+        let ranked = |analyzer: &super::WeightedTrendingAnalyzer| {
+            analyzer
+                .rank(&data, |x| x.techniques_used.clone())
+                .into_iter()
+                .map(|k| (k.keyword, k.score))
+                .collect::<Vec<_>>()
+        };
+
+        let plain = WeightedTrendingAnalyzerBuilder::default().build().unwrap();
+        assert_eq!(ranked(&plain), vec![("a".into(), 2.0), ("b".into(), 1.0)]);
+        let weighted = WeightedTrendingAnalyzerBuilder::default()
+            .scorer(VenueWeight::new([("ICSE", 3.0)]))
+            .dedup_authors(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            ranked(&weighted),
+            vec![("b".into(), 3.0), ("a".into(), 1.0)]
+        );
+    }
+}
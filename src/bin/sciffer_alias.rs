@@ -0,0 +1,40 @@
+use clap::{Parser, Subcommand};
+use sciffer_rs::db::get_db_pool;
+use sciffer_rs::normalizers::keyword::{list_aliases, pin_alias, reject_alias};
+
+/// Review how the server merges keywords.
+#[derive(Parser)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Always store ALIAS as CANONICAL
+    Pin { alias: String, canonical: String },
+    /// Never merge ALIAS into CANONICAL
+    Reject { alias: String, canonical: String },
+    /// Print every stored alias
+    List,
+}
+
+#[tokio::main]
+async fn main() {
+    let _ = dotenv::dotenv();
+    let args = Args::parse();
+    let pool = get_db_pool().await.expect("Cannot open the database");
+
+    match args.command {
+        Command::Pin { alias, canonical } => pin_alias(&pool, &alias, &canonical).await.unwrap(),
+        Command::Reject { alias, canonical } => {
+            reject_alias(&pool, &alias, &canonical).await.unwrap()
+        }
+        Command::List => {
+            for (alias, canonical, status, score) in list_aliases(&pool).await.unwrap() {
+                let score = score.map(|s| format!(" ({:.3})", s)).unwrap_or_default();
+                println!("{} -> {} [{}]{}", alias, canonical, status, score);
+            }
+        }
+    }
+}
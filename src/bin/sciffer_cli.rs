@@ -1,8 +1,19 @@
 use async_openai::config::OPENAI_API_BASE;
+use chrono::Local;
 use clap::Parser;
 use langchain_rust::language_models::llm::LLM;
 use langchain_rust::llm::{client::Ollama, OpenAI};
 use langchain_rust::tools::OpenAIConfig;
+use sciffer_rs::analyzers::weighted::WeightedTrendingAnalyzer;
+use sciffer_rs::config::{load_config, ScifferConfig};
+use sciffer_rs::extracters::artifact::{ArtifactExtracterBuilder, ArtifactKind};
+use sciffer_rs::extracters::pipeline::{
+    LanguageStage, PipelineExtracter, PipelineExtracterBuilder, TopicStage,
+};
+use sciffer_rs::extracters::relevance::{RelevanceFilterBuilder, RelevanceMode, RelevanceScorer};
+use sciffer_rs::extracters::summary::SummaryExtracterBuilder;
+use sciffer_rs::fetchers::arxiv::ArxivFetcher;
+use sciffer_rs::prompts::PromptRegistry;
 use sciffer_rs::{
     extracters::topic::TopicExtracterBuilder,
     fetchers::arxiv::ArxivFetcherBuilder,
@@ -24,23 +35,36 @@ struct Args {
         default_value = "deepseek-ai/DeepSeek-R1-Distill-Llama-8B"
     )]
     model: String,
-    #[arg(short, long, default_value = "prompts/topic.md")]
+    /// Prompt id in `prompts/` (`topic`, `topic@2`) or path to a prompt file
+    #[arg(short, long, default_value = "topic")]
     prompt: String,
+    /// Stop extracting once this many USD are spent, overrides `max_cost`
+    #[arg(long)]
+    max_cost: Option<f64>,
+    /// Also summarize each paper: TL;DR, contributions, results and limitations
+    #[arg(long, default_value_t = false)]
+    summary: bool,
+    /// Also list datasets, benchmarks, metrics, scores and code links of each paper
+    #[arg(long, default_value_t = false)]
+    artifacts: bool,
+    /// Topic description: papers the model judges off-topic are dropped before extraction
+    #[arg(long)]
+    relevance: Option<String>,
+    /// Model answering the relevance question, overrides `relevance_model`,
+    /// `--model` if neither is set
+    #[arg(long)]
+    relevance_model: Option<String>,
+    /// Relevance score a paper needs, 0.5 if unset
+    #[arg(long)]
+    relevance_threshold: Option<f64>,
+    /// Extract off-topic papers anyway, only flagging them
+    #[arg(long, default_value_t = false)]
+    flag_irrelevant: bool,
 }
 
-#[tokio::main]
-async fn main() {
-    let _ = dotenv::dotenv();
-    let args = Args::parse();
-
-    let fetcher = ArxivFetcherBuilder::default()
-        .query(args.query)
-        .number(args.num)
-        .build()
-        .unwrap();
-
-    let llm: Box<dyn LLM> = if args.ollama {
-        Box::new(Ollama::default().with_model(args.model))
+fn language_model(ollama: bool, model: &str) -> Box<dyn LLM> {
+    if ollama {
+        Box::new(Ollama::default().with_model(model))
     } else {
         Box::new(
             OpenAI::default()
@@ -51,22 +75,129 @@ async fn main() {
                             env::var("API_KEY").expect("Are you waiting for my API_KEY?"),
                         ),
                 )
-                .with_model(args.model),
+                .with_model(model),
         )
+    }
+}
+
+async fn run(fetcher: ArxivFetcher, extracter: PipelineExtracter, cfg: ScifferConfig) {
+    let analyzer = WeightedTrendingAnalyzer::from_config(&cfg.trending, Local::now().date_naive())
+        .expect("Citation counts cannot be read.");
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(fetcher)
+        .extracter(extracter)
+        .config(cfg)
+        .build()
+        .unwrap();
+
+    let report = tokio::select! {
+        res = sciffer.sniffer_report() => res.unwrap(),
+        _ = sciffer.cancel_on_ctrl_c() => unreachable!(),
     };
+    let summary = report.to_string();
+    let usage = report.usage.to_string();
+    let pairs = report.into_pairs();
+    println!("{:#?}", pairs);
+    for (paper, data) in pairs.iter() {
+        if data.relevance.is_some_and(|r| !r.relevant) {
+            println!("\n## {} (off-topic)", paper.title);
+        }
+        if let Some(summary) = &data.summary {
+            println!("\n## {}\n{}", paper.title, summary);
+        }
+        if let Some(artifacts) = &data.artifacts {
+            println!("\n## {} (artifacts)\n{}", paper.title, artifacts);
+        }
+    }
+    let benchmarks = analyzer.rank(&pairs, |d| d.artifact_names(ArtifactKind::Benchmark));
+    if !benchmarks.is_empty() {
+        println!("\nTrending benchmarks:");
+        for benchmark in benchmarks.iter().take(10) {
+            println!("  {}", benchmark);
+        }
+    }
+    println!("{}", summary);
+    println!("{}", usage);
+}
 
-    let extracter = TopicExtracterBuilder::default()
-        .prompt(args.prompt)
-        .llm(llm)
+#[tokio::main]
+async fn main() {
+    let _ = dotenv::dotenv();
+    let args = Args::parse();
+    let mut cfg = load_config();
+    cfg.provider
+        .get_or_insert(if args.ollama { "ollama" } else { "openai" }.to_string());
+    if args.max_cost.is_some() {
+        cfg.max_cost = args.max_cost;
+    }
+    if args.relevance_model.is_some() {
+        cfg.relevance_model = args.relevance_model.clone();
+    }
+
+    let fetcher = ArxivFetcherBuilder::default()
+        .query(args.query)
+        .number(args.num)
         .build()
         .unwrap();
 
-    let sciffer = ArxivScifferBuilder::default()
-        .fetcher(fetcher)
-        .extracter(extracter)
+    let registry = PromptRegistry::load_dir("prompts").unwrap_or_default();
+    let prompt = registry
+        .resolve(&args.prompt)
+        .expect("Cannot find the prompt");
+
+    let llm = language_model(args.ollama, &args.model);
+
+    let relevance = args.relevance.map(|topic| {
+        let model = cfg.relevance_model.as_ref().unwrap_or(&args.model);
+        let mut filter = RelevanceFilterBuilder::default()
+            .topic(topic)
+            .scorer(RelevanceScorer::Llm(language_model(args.ollama, model)))
+            .model(model.clone());
+        if let Some(threshold) = args.relevance_threshold {
+            filter = filter.threshold(threshold);
+        }
+        if args.flag_irrelevant {
+            filter = filter.mode(RelevanceMode::Flag);
+        }
+        filter.build().unwrap()
+    });
+    let summarizer = args.summary.then(|| {
+        SummaryExtracterBuilder::default()
+            .llm(llm.clone_box())
+            .model(args.model.clone())
+            .build()
+            .unwrap()
+    });
+    let artifacts = args.artifacts.then(|| {
+        ArtifactExtracterBuilder::default()
+            .llm(llm.clone_box())
+            .model(args.model.clone())
+            .build()
+            .unwrap()
+    });
+    let extracter = TopicExtracterBuilder::default()
+        .prompt(prompt)
+        .llm(llm)
+        .model(args.model)
         .build()
         .unwrap();
 
-    let res = sciffer.sniffer_parallel().await.unwrap();
-    println!("{:#?}", res);
+    let mut pipeline = PipelineExtracterBuilder::default().stage(LanguageStage);
+    if let Some(relevance) = relevance {
+        pipeline = pipeline.stage(relevance);
+    }
+    let mut pipeline = pipeline.stage(TopicStage::new(extracter));
+    if let Some(artifacts) = artifacts {
+        pipeline = pipeline.stage(artifacts);
+    }
+    if let Some(summarizer) = summarizer {
+        pipeline = pipeline.stage(summarizer);
+    }
+    let mut pipeline = pipeline.build().unwrap();
+    for stage in cfg.disabled_stages.iter() {
+        if !pipeline.set_enabled(stage, false) {
+            eprintln!("warning: disabled stage {:?} is not in the pipeline", stage);
+        }
+    }
+    run(fetcher, pipeline, cfg).await
 }
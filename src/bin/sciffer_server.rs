@@ -1,16 +1,30 @@
 use async_openai::config::OPENAI_API_BASE;
 use clap::Parser;
+use langchain_rust::embedding::openai::OpenAiEmbedder;
+use langchain_rust::embedding::{Embedder, OllamaEmbedder};
 use langchain_rust::language_models::llm::LLM;
 use langchain_rust::llm::{client::Ollama, OpenAI};
 use langchain_rust::tools::OpenAIConfig;
-use sciffer_rs::config::load_config;
+use log::warn;
+use sciffer_rs::config::{load_config, ScifferConfig};
+use sciffer_rs::extracters::artifact::ArtifactExtracterBuilder;
+use sciffer_rs::extracters::batch::BatchTopicExtracterBuilder;
+use sciffer_rs::extracters::pipeline::{
+    LanguageStage, PipelineExtracter, PipelineExtracterBuilder, PipelineStage, TopicStage,
+};
+use sciffer_rs::extracters::relevance::{RelevanceFilterBuilder, RelevanceMode, RelevanceScorer};
+use sciffer_rs::extracters::summary::SummaryExtracterBuilder;
+use sciffer_rs::fetchers::arxiv::ArxivFetcher;
 use sciffer_rs::log::init_logger;
+use sciffer_rs::normalizers::keyword::{KeywordNormalizer, KeywordNormalizerBuilder};
+use sciffer_rs::prompts::PromptRegistry;
 use sciffer_rs::sciffer::SnifferServer;
 use sciffer_rs::{
     extracters::topic::TopicExtracterBuilder, fetchers::arxiv::ArxivFetcherBuilder,
     sciffer::ArxivScifferBuilder,
 };
 use std::env;
+use std::sync::Arc;
 
 #[derive(Parser)]
 struct Args {
@@ -26,25 +40,43 @@ struct Args {
         default_value = "deepseek-ai/DeepSeek-R1-Distill-Llama-8B"
     )]
     model: String,
-    #[arg(short, long, default_value = "prompts/topic.md")]
+    /// Prompt id in `prompts/` (`topic`, `topic@2`) or path to a prompt file
+    #[arg(short, long, default_value = "topic")]
     prompt: String,
+    /// Stop extracting once this many USD are spent, overrides `max_cost`
+    #[arg(long)]
+    max_cost: Option<f64>,
+    /// Embedding model used to merge synonymous keywords, rules only if unset
+    #[arg(short, long)]
+    embedding_model: Option<String>,
+    /// Papers sent to the LLM in one call, one at a time if 1
+    #[arg(short, long, default_value_t = 1)]
+    batch_size: usize,
+    /// Also store a TL;DR, contributions, results and limitations of each paper
+    #[arg(long, default_value_t = false)]
+    summary: bool,
+    /// Also store the datasets, benchmarks, metrics, scores and code links of each paper
+    #[arg(long, default_value_t = false)]
+    artifacts: bool,
+    /// Topic description: papers scoring below `--relevance-threshold` against
+    /// it are dropped before extraction, scored with `--embedding-model` if set
+    #[arg(long)]
+    relevance: Option<String>,
+    /// Model answering the relevance question, overrides `relevance_model`,
+    /// `--model` if neither is set
+    #[arg(long)]
+    relevance_model: Option<String>,
+    /// Relevance score a paper needs, 0.5 if unset
+    #[arg(long)]
+    relevance_threshold: Option<f64>,
+    /// Extract off-topic papers anyway, only flagging them
+    #[arg(long, default_value_t = false)]
+    flag_irrelevant: bool,
 }
 
-#[tokio::main]
-async fn main() {
-    init_logger();
-    let _ = dotenv::dotenv();
-    let args = Args::parse();
-    let cfg = load_config();
-
-    let fetcher = ArxivFetcherBuilder::default()
-        .query(args.query)
-        .number(args.num)
-        .build()
-        .unwrap();
-
-    let llm: Box<dyn LLM> = if args.ollama {
-        Box::new(Ollama::default().with_model(args.model))
+fn language_model(ollama: bool, model: &str) -> Box<dyn LLM> {
+    if ollama {
+        Box::new(Ollama::default().with_model(model))
     } else {
         Box::new(
             OpenAI::default()
@@ -55,22 +87,145 @@ async fn main() {
                             env::var("API_KEY").expect("Are you waiting for my API_KEY?"),
                         ),
                 )
-                .with_model(args.model),
+                .with_model(model),
         )
-    };
+    }
+}
 
-    let extracter = TopicExtracterBuilder::default()
-        .prompt(args.prompt)
-        .llm(llm)
-        .build()
-        .unwrap();
+fn embedder(ollama: bool, model: &str) -> Box<dyn Embedder> {
+    if ollama {
+        Box::new(OllamaEmbedder::default().with_model(model))
+    } else {
+        Box::new(
+            OpenAiEmbedder::default()
+                .with_config(
+                    OpenAIConfig::default()
+                        .with_api_base(env::var("API_BASE").unwrap_or(OPENAI_API_BASE.to_string()))
+                        .with_api_key(
+                            env::var("API_KEY").expect("Are you waiting for my API_KEY?"),
+                        ),
+                )
+                .with_model(model),
+        )
+    }
+}
 
+async fn start(
+    fetcher: ArxivFetcher,
+    extracter: PipelineExtracter,
+    cfg: ScifferConfig,
+    normalizer: Arc<KeywordNormalizer>,
+) {
     let sciffer = ArxivScifferBuilder::default()
         .fetcher(fetcher)
         .extracter(extracter)
         .config(cfg)
+        .normalizer(normalizer)
         .build()
         .unwrap();
 
     sciffer.start_server().await.unwrap();
 }
+
+#[tokio::main]
+async fn main() {
+    init_logger();
+    let _ = dotenv::dotenv();
+    let args = Args::parse();
+    let mut cfg = load_config();
+    cfg.provider
+        .get_or_insert(if args.ollama { "ollama" } else { "openai" }.to_string());
+    if args.max_cost.is_some() {
+        cfg.max_cost = args.max_cost;
+    }
+    if args.relevance_model.is_some() {
+        cfg.relevance_model = args.relevance_model.clone();
+    }
+
+    let fetcher = ArxivFetcherBuilder::default()
+        .query(args.query)
+        .number(args.num)
+        .build()
+        .unwrap();
+
+    let registry = PromptRegistry::load_dir("prompts").unwrap_or_default();
+    let prompt = registry
+        .resolve(&args.prompt)
+        .expect("Cannot find the prompt");
+
+    let llm = language_model(args.ollama, &args.model);
+
+    let relevance = args.relevance.map(|topic| {
+        let model = cfg.relevance_model.as_ref().unwrap_or(&args.model);
+        let scorer = match &args.embedding_model {
+            Some(model) => RelevanceScorer::Embedding(embedder(args.ollama, model)),
+            None => RelevanceScorer::Llm(language_model(args.ollama, model)),
+        };
+        let mut filter = RelevanceFilterBuilder::default()
+            .topic(topic)
+            .scorer(scorer)
+            .model(model.clone());
+        if let Some(threshold) = args.relevance_threshold {
+            filter = filter.threshold(threshold);
+        }
+        if args.flag_irrelevant {
+            filter = filter.mode(RelevanceMode::Flag);
+        }
+        filter.build().unwrap()
+    });
+    let summarizer = args.summary.then(|| {
+        SummaryExtracterBuilder::default()
+            .llm(llm.clone_box())
+            .model(args.model.clone())
+            .build()
+            .unwrap()
+    });
+    let artifacts = args.artifacts.then(|| {
+        ArtifactExtracterBuilder::default()
+            .llm(llm.clone_box())
+            .model(args.model.clone())
+            .build()
+            .unwrap()
+    });
+    let extracter = TopicExtracterBuilder::default()
+        .prompt(prompt)
+        .llm(llm)
+        .model(args.model)
+        .build()
+        .unwrap();
+
+    let mut normalizer = KeywordNormalizerBuilder::default();
+    if let Some(model) = &args.embedding_model {
+        normalizer = normalizer.embedder(embedder(args.ollama, model));
+    }
+
+    let normalizer = Arc::new(normalizer.build().unwrap());
+    let topics: PipelineStage = if args.batch_size > 1 {
+        let extracter = BatchTopicExtracterBuilder::default()
+            .single(extracter)
+            .max_batch_size(args.batch_size)
+            .build()
+            .unwrap();
+        TopicStage::new(extracter).into()
+    } else {
+        TopicStage::new(extracter).into()
+    };
+    let mut pipeline = PipelineExtracterBuilder::default().stage(LanguageStage);
+    if let Some(relevance) = relevance {
+        pipeline = pipeline.stage(relevance);
+    }
+    let mut pipeline = pipeline.stage(topics);
+    if let Some(artifacts) = artifacts {
+        pipeline = pipeline.stage(artifacts);
+    }
+    if let Some(summarizer) = summarizer {
+        pipeline = pipeline.stage(summarizer);
+    }
+    let mut pipeline = pipeline.build().unwrap();
+    for stage in cfg.disabled_stages.iter() {
+        if !pipeline.set_enabled(stage, false) {
+            warn!("disabled stage {:?} is not in the pipeline", stage);
+        }
+    }
+    start(fetcher, pipeline, cfg, normalizer).await;
+}
@@ -0,0 +1,455 @@
+use chrono::{Days, Local, NaiveDate};
+use clap::{Parser, Subcommand};
+use sciffer_rs::analyzers::author::{authored_stored, AuthorAnalyzerBuilder, AuthorIndex};
+use sciffer_rs::analyzers::burst::BurstDetectorBuilder;
+use sciffer_rs::analyzers::compare::{Period, PeriodComparerBuilder};
+use sciffer_rs::analyzers::forecast::{ForecastMethod, KeywordForecasterBuilder};
+use sciffer_rs::analyzers::graph::{CooccurrenceAnalyzerBuilder, EdgeWeight, GraphFormat};
+use sciffer_rs::analyzers::matrix::MatrixAnalyzerBuilder;
+use sciffer_rs::analyzers::stored_papers;
+use sciffer_rs::analyzers::topic_model::{
+    save_topics, stored_topic_keywords, NmfTopicModelerBuilder,
+};
+use sciffer_rs::analyzers::trend::{Granularity, WindowedTrendAnalyzerBuilder};
+use sciffer_rs::db::get_db_pool;
+
+/// Find what is rising among the stored papers.
+#[derive(Parser)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Keywords growing fastest in the latest buckets compared to the ones before
+    Rising {
+        /// Bucket length: day, week or month
+        #[arg(short, long, default_value = "week")]
+        granularity: Granularity,
+        /// Buckets ranked on
+        #[arg(short, long, default_value_t = 1)]
+        window: u32,
+        /// Buckets before the window to compare with
+        #[arg(short, long, default_value_t = 4)]
+        baseline: u32,
+        /// Last day of the window, e.g. 2025-04-30, end of the last complete bucket if unset
+        #[arg(short, long)]
+        until: Option<NaiveDate>,
+        /// Keywords to print
+        #[arg(short, default_value_t = 20)]
+        k: usize,
+    },
+    /// Keywords that suddenly appeared in many papers, and when
+    Bursts {
+        /// First day to look at, 90 days ago if unset
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// How many times more frequent a keyword is during a burst
+        #[arg(short, default_value_t = 2.0)]
+        s: f64,
+        /// Cost of entering a burst, higher for fewer and longer bursts
+        #[arg(short, long, default_value_t = 1.0)]
+        gamma: f64,
+        /// Bursts to print
+        #[arg(short, default_value_t = 20)]
+        k: usize,
+    },
+    /// New, vanished, rising and falling keywords from one period to another
+    Compare {
+        /// First period: a year 2024, a month 2025-03 or days 2025-03-01..2025-03-14
+        before: Period,
+        /// Second period, in the same forms
+        after: Period,
+        /// Papers a keyword needs in one of the periods
+        #[arg(long, default_value_t = 2)]
+        min_papers: usize,
+        /// Keywords to print of each kind
+        #[arg(short, default_value_t = 10)]
+        k: usize,
+    },
+    /// Topics of the stored abstracts by topic modeling, independent of the LLM
+    Topics {
+        /// First day to look at, 90 days ago if unset
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Topics to find
+        #[arg(short, long, default_value_t = 10)]
+        topics: usize,
+        /// Bucket length of the prevalence over time: day, week or month
+        #[arg(short, long, default_value = "month")]
+        granularity: Granularity,
+        /// Store the topics and the weights of the topics in each paper, and
+        /// print the extracted keywords of each topic
+        #[arg(long)]
+        save: bool,
+    },
+    /// Print the keyword co-occurrence graph with its clusters, for Gephi and the like
+    Graph {
+        /// First day to look at, 90 days ago if unset
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Edge weight: count, jaccard or pmi
+        #[arg(short, long, default_value = "pmi")]
+        weight: EdgeWeight,
+        /// Output format: graphml, gexf or json
+        #[arg(short, long, default_value = "graphml")]
+        format: GraphFormat,
+        /// Papers a keyword needs to be a node
+        #[arg(long, default_value_t = 2)]
+        min_papers: usize,
+    },
+    /// Which techniques are applied to which problems, more or less than expected
+    Matrix {
+        /// First day to look at, 90 days ago if unset
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Most frequent problems and techniques in the matrix
+        #[arg(short, long, default_value_t = 20)]
+        size: usize,
+        /// Compare the last two buckets of this length: day, week or month
+        #[arg(short, long)]
+        granularity: Option<Granularity>,
+        /// Combinations to print
+        #[arg(short, default_value_t = 10)]
+        k: usize,
+    },
+    /// Papers expected on each keyword over the next buckets, the fastest growing first
+    Forecast {
+        /// First day to fit on, two years ago if unset
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Bucket length: day, week or month
+        #[arg(short, long, default_value = "month")]
+        granularity: Granularity,
+        /// Model: holt, holt-winters or log-linear
+        #[arg(short, long, default_value = "holt")]
+        method: ForecastMethod,
+        /// Buckets to forecast
+        #[arg(long, default_value_t = 3)]
+        horizon: usize,
+        /// Probability of a count falling within its prediction interval
+        #[arg(long, default_value_t = 0.8)]
+        level: f64,
+        /// Papers a keyword needs to be forecast
+        #[arg(long, default_value_t = 10)]
+        min_papers: usize,
+        /// Also backtest every model on this many past windows
+        #[arg(long)]
+        backtest: Option<usize>,
+        /// Keywords to print
+        #[arg(short, default_value_t = 20)]
+        k: usize,
+    },
+    /// Most prolific authors on a keyword, and the ones new to it
+    ///
+    /// Authors are stored since the `authors` migration, papers stored
+    /// before it have none and are left out.
+    Authors {
+        keyword: String,
+        /// First day to look at, 90 days ago if unset
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Authors entering the keyword from this day on are new, 30 days ago if unset
+        #[arg(long)]
+        entered: Option<NaiveDate>,
+        /// Papers an author needs on the keyword
+        #[arg(long, default_value_t = 2)]
+        min_papers: usize,
+        /// Authors to print
+        #[arg(short, default_value_t = 20)]
+        k: usize,
+    },
+    /// Print the co-authorship graph with its research groups, for Gephi and the like
+    ///
+    /// Authors are stored since the `authors` migration, papers stored
+    /// before it have none and are left out.
+    Collaborations {
+        /// First day to look at, 90 days ago if unset
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Edge weight: count, jaccard or pmi
+        #[arg(short, long, default_value = "count")]
+        weight: EdgeWeight,
+        /// Output format: graphml, gexf or json
+        #[arg(short, long, default_value = "graphml")]
+        format: GraphFormat,
+        /// Papers an author needs to be a node
+        #[arg(long, default_value_t = 2)]
+        min_papers: usize,
+    },
+    /// Authors whose keywords changed the most over time
+    ///
+    /// Authors are stored since the `authors` migration, papers stored
+    /// before it have none and are left out.
+    Drift {
+        /// First day to look at, 365 days ago if unset
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Bucket length: day, week or month
+        #[arg(short, long, default_value = "month")]
+        granularity: Granularity,
+        /// Papers an author needs
+        #[arg(long, default_value_t = 3)]
+        min_papers: usize,
+        /// Authors to print
+        #[arg(short, default_value_t = 20)]
+        k: usize,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    let _ = dotenv::dotenv();
+    let args = Args::parse();
+    let pool = get_db_pool().await.expect("Cannot open the database");
+
+    match args.command {
+        Command::Rising {
+            granularity,
+            window,
+            baseline,
+            until,
+            k,
+        } => {
+            let mut analyzer = WindowedTrendAnalyzerBuilder::default();
+            analyzer
+                .granularity(granularity)
+                .window(window)
+                .baseline(baseline);
+            if let Some(until) = until {
+                analyzer.until(until);
+            }
+            let trends = analyzer.build().unwrap().rank_stored(&pool).await.unwrap();
+            for trend in trends.iter().take(k) {
+                println!("{}", trend);
+            }
+        }
+        Command::Bursts { since, s, gamma, k } => {
+            let since = since.unwrap_or_else(|| Local::now().date_naive() - Days::new(90));
+            let bursts = BurstDetectorBuilder::default()
+                .s(s)
+                .gamma(gamma)
+                .build()
+                .unwrap()
+                .detect_stored(&pool, since)
+                .await
+                .unwrap();
+            for burst in bursts.iter().take(k) {
+                println!("{}", burst);
+            }
+        }
+        Command::Compare {
+            before,
+            after,
+            min_papers,
+            k,
+        } => {
+            let comparison = PeriodComparerBuilder::default()
+                .min_papers(min_papers)
+                .build()
+                .unwrap()
+                .compare_stored(&pool, before, after)
+                .await
+                .unwrap();
+            println!("{} vs {}", before, after);
+            print!("{}", comparison.truncated(k));
+        }
+        Command::Topics {
+            since,
+            topics,
+            granularity,
+            save,
+        } => {
+            let since = since.unwrap_or_else(|| Local::now().date_naive() - Days::new(90));
+            let (papers, model) = NmfTopicModelerBuilder::default()
+                .topics(topics)
+                .build()
+                .unwrap()
+                .fit_stored(&pool, &since.format("%Y-%m-%d").to_string())
+                .await
+                .unwrap();
+            for (i, topic) in model.topics.iter().enumerate() {
+                println!("[{}] {}", i + 1, topic);
+            }
+            println!("Prevalence:");
+            for (bucket, prevalence) in model.prevalence(granularity) {
+                let prevalence: Vec<String> = prevalence
+                    .iter()
+                    .map(|p| format!("{:5.1}", p * 100.0))
+                    .collect();
+                println!("  {} {}", bucket, prevalence.join(" "));
+            }
+            if save {
+                let paper_ids: Vec<i64> = papers.iter().map(|(id, _)| *id).collect();
+                save_topics(&pool, &paper_ids, &model).await.unwrap();
+                println!("Extracted keywords:");
+                for (label, keywords) in stored_topic_keywords(&pool, 5).await.unwrap() {
+                    let keywords: Vec<&str> = keywords.iter().map(|(k, _)| k.as_str()).collect();
+                    println!("  {}: {}", label, keywords.join(", "));
+                }
+            }
+        }
+        Command::Graph {
+            since,
+            weight,
+            format,
+            min_papers,
+        } => {
+            let since = since.unwrap_or_else(|| Local::now().date_naive() - Days::new(90));
+            let graph = CooccurrenceAnalyzerBuilder::default()
+                .weight(weight)
+                .min_papers(min_papers)
+                .build()
+                .unwrap()
+                .graph_stored(&pool, since)
+                .await
+                .unwrap();
+            print!("{}", graph.export(format));
+        }
+        Command::Matrix {
+            since,
+            size,
+            granularity,
+            k,
+        } => {
+            let since = since.unwrap_or_else(|| Local::now().date_naive() - Days::new(90));
+            let papers = stored_papers(&pool, &since.format("%Y-%m-%d").to_string())
+                .await
+                .unwrap();
+            let analyzer = MatrixAnalyzerBuilder::default()
+                .problems(size)
+                .techniques(size)
+                .build()
+                .unwrap();
+            let matrix = analyzer.matrix(&papers);
+            println!("{}", matrix);
+            println!("Over-explored:");
+            for cell in matrix.over_explored(k) {
+                println!("  {}", cell);
+            }
+            println!("Gaps:");
+            for cell in matrix.gaps(k) {
+                println!("  {}", cell);
+            }
+            if let Some(granularity) = granularity {
+                let windows = analyzer.windows(&papers, granularity);
+                if let [.., (from, before), (to, after)] = windows.as_slice() {
+                    println!("Changes from {} to {}:", from, to);
+                    for change in before.compare(after).iter().take(k) {
+                        println!(
+                            "  {} x {}: {} to {} papers ({:+.1}%)",
+                            change.problem,
+                            change.technique,
+                            change.before,
+                            change.after,
+                            change.change * 100.0
+                        );
+                    }
+                }
+            }
+        }
+        Command::Forecast {
+            since,
+            granularity,
+            method,
+            horizon,
+            level,
+            min_papers,
+            backtest,
+            k,
+        } => {
+            let since = since.unwrap_or_else(|| Local::now().date_naive() - Days::new(730));
+            let forecaster = KeywordForecasterBuilder::default()
+                .granularity(granularity)
+                .method(method)
+                .horizon(horizon)
+                .level(level)
+                .min_papers(min_papers)
+                .build()
+                .unwrap_or_else(|e| panic!("Invalid forecast options: {}", e));
+            let forecasts = forecaster.forecast_stored(&pool, since).await.unwrap();
+            for forecast in forecasts.iter().take(k) {
+                println!("{}", forecast);
+            }
+            if let Some(windows) = backtest {
+                println!("Backtest:");
+                for backtest in forecaster
+                    .backtest_stored(&pool, since, windows)
+                    .await
+                    .unwrap()
+                {
+                    println!("  {}", backtest);
+                }
+            }
+        }
+        Command::Authors {
+            keyword,
+            since,
+            entered,
+            min_papers,
+            k,
+        } => {
+            let today = Local::now().date_naive();
+            let since = since.unwrap_or(today - Days::new(90));
+            let papers = authored_stored(&pool, &since.format("%Y-%m-%d").to_string())
+                .await
+                .unwrap();
+            let index = AuthorIndex::new(papers);
+            let analyzer = AuthorAnalyzerBuilder::default()
+                .min_papers(min_papers)
+                .build()
+                .unwrap();
+            println!("Top authors on {}:", keyword);
+            for author in analyzer.top_authors(&index, &keyword).iter().take(k) {
+                println!("  {}", author);
+            }
+            let entered = entered.unwrap_or(today - Days::new(30));
+            println!("New since {}:", entered);
+            for entrant in analyzer
+                .new_entrants(&index, &keyword, entered)
+                .iter()
+                .take(k)
+            {
+                println!("  {}", entrant);
+            }
+        }
+        Command::Collaborations {
+            since,
+            weight,
+            format,
+            min_papers,
+        } => {
+            let since = since.unwrap_or_else(|| Local::now().date_naive() - Days::new(90));
+            let papers = authored_stored(&pool, &since.format("%Y-%m-%d").to_string())
+                .await
+                .unwrap();
+            let graph = AuthorAnalyzerBuilder::default()
+                .min_papers(min_papers)
+                .build()
+                .unwrap()
+                .collaborations(&AuthorIndex::new(papers), weight);
+            print!("{}", graph.export(format));
+        }
+        Command::Drift {
+            since,
+            granularity,
+            min_papers,
+            k,
+        } => {
+            let since = since.unwrap_or_else(|| Local::now().date_naive() - Days::new(365));
+            let papers = authored_stored(&pool, &since.format("%Y-%m-%d").to_string())
+                .await
+                .unwrap();
+            let drifts = AuthorAnalyzerBuilder::default()
+                .min_papers(min_papers)
+                .granularity(granularity)
+                .build()
+                .unwrap()
+                .drift(&AuthorIndex::new(papers));
+            for drift in drifts.iter().take(k) {
+                println!("{}", drift);
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use clap::Parser;
+use sciffer_rs::db::get_db_pool;
+use sciffer_rs::tools::usage::{recent_runs, usage_since};
+
+/// Print what the server's cycles cost.
+#[derive(Parser)]
+struct Args {
+    /// Number of recent runs to list
+    #[arg(short, long, default_value_t = 10)]
+    runs: i64,
+    /// Total usage per model since this date, e.g. 2025-03-01
+    #[arg(short, long, default_value = "")]
+    since: String,
+}
+
+#[tokio::main]
+async fn main() {
+    let _ = dotenv::dotenv();
+    let args = Args::parse();
+    let pool = get_db_pool().await.expect("Cannot open the database");
+
+    for run in recent_runs(&pool, args.runs).await.unwrap() {
+        println!(
+            "{}: {} extracted, {} failed, {} unfinished in {:.1?}, {} tokens, ${:.4}",
+            run.created_at,
+            run.successes,
+            run.failures,
+            run.unfinished,
+            run.elapsed,
+            run.usage.total_tokens(),
+            run.usage.total_cost()
+        );
+    }
+    println!();
+    println!("{}", usage_since(&pool, &args.since).await.unwrap());
+}
@@ -1,9 +1,132 @@
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
 use config::{Config, File};
 use serde::Deserialize;
 
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 8;
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Default, Deserialize, Clone)]
+pub struct RateLimitConfig {
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+}
+
+/// USD per million tokens of one model.
+#[derive(Debug, Default, Deserialize, Clone, PartialEq)]
+pub struct ModelPrice {
+    pub prompt: f64,
+    pub completion: f64,
+}
+
+impl ModelPrice {
+    pub fn cost(&self, prompt_tokens: u32, completion_tokens: u32) -> f64 {
+        (prompt_tokens as f64 * self.prompt + completion_tokens as f64 * self.completion)
+            / 1_000_000.0
+    }
+}
+
+/// Weights of the papers counted by
+/// [`WeightedTrendingAnalyzer`](crate::analyzers::weighted::WeightedTrendingAnalyzer),
+/// every paper counts 1.0 if unset.
+#[derive(Debug, Default, Deserialize, Clone)]
+pub struct TrendingConfig {
+    /// Days after which a paper counts half, no decay if unset.
+    pub half_life_days: Option<f64>,
+    /// Weigh papers by the share of the ensemble that agreed on the keyword.
+    #[serde(default)]
+    pub confidence: bool,
+    /// How much less keywords count the later they are listed in a paper.
+    pub position_decay: Option<f64>,
+    /// Weights of papers by venue named in their arXiv comment, e.g. `ICSE = 2.0`.
+    #[serde(default)]
+    pub venues: HashMap<String, f64>,
+    /// Count papers sharing authors once.
+    #[serde(default)]
+    pub dedup_authors: bool,
+    /// JSON file of citation counts by arXiv id, e.g. `{"2504.00001": 12}`,
+    /// see [`CitationWeight`](crate::analyzers::weighted::CitationWeight).
+    pub citations: Option<PathBuf>,
+}
+
+impl TrendingConfig {
+    /// A half-life of zero or less, or a negative or infinite position decay,
+    /// would turn scores into NaN or infinity.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(half_life) = self.half_life_days.filter(|h| h.is_nan() || *h <= 0.0) {
+            return Err(format!(
+                "half_life_days must be positive, got {}",
+                half_life
+            ));
+        }
+        if let Some(decay) = self.position_decay.filter(|d| !d.is_finite() || *d < 0.0) {
+            return Err(format!(
+                "position_decay must be finite and not negative, got {}",
+                decay
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default, Deserialize, Clone)]
 pub struct ScifferConfig {
     pub time_interval: u64,
+    /// Max number of extractions running at once, `DEFAULT_MAX_IN_FLIGHT` if unset.
+    pub max_in_flight: Option<usize>,
+    /// Name of the LLM provider in use, selects its entry in `providers`.
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub providers: HashMap<String, RateLimitConfig>,
+    /// Seconds a single extraction may take before it is abandoned.
+    pub extract_timeout: Option<u64>,
+    /// Seconds a whole fetch-and-extract cycle may take.
+    pub cycle_deadline: Option<u64>,
+    /// Extraction attempts per paper before the server gives up on it.
+    pub max_attempts: Option<u32>,
+    /// Prices by model name, models without one are not costed.
+    #[serde(default)]
+    pub prices: HashMap<String, ModelPrice>,
+    /// USD the sniffer may spend before it stops extracting, no cap if unset.
+    /// A batch starts if its estimated prompt cost fits, so the completions
+    /// and the batches already running can overshoot it.
+    pub max_cost: Option<f64>,
+    /// Model answering the relevance question, usually a small one. The
+    /// extraction model if unset.
+    pub relevance_model: Option<String>,
+    /// Names of the pipeline stages to skip, e.g. `["summary"]`.
+    #[serde(default)]
+    pub disabled_stages: Vec<String>,
+    #[serde(default)]
+    pub trending: TrendingConfig,
+}
+
+impl ScifferConfig {
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight.unwrap_or(DEFAULT_MAX_IN_FLIGHT).max(1)
+    }
+
+    pub fn extract_timeout(&self) -> Option<Duration> {
+        self.extract_timeout.map(Duration::from_secs)
+    }
+
+    pub fn cycle_deadline(&self) -> Option<Duration> {
+        self.cycle_deadline.map(Duration::from_secs)
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS)
+    }
+
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices.get(model)
+    }
+
+    pub fn rate_limit(&self) -> Option<&RateLimitConfig> {
+        self.provider
+            .as_ref()
+            .and_then(|provider| self.providers.get(provider))
+    }
 }
 
 pub fn load_config() -> ScifferConfig {
@@ -15,5 +138,8 @@ pub fn load_config() -> ScifferConfig {
     let config: ScifferConfig = config
         .try_deserialize()
         .expect("Config cannot be deserialized.");
+    if let Err(e) = config.trending.validate() {
+        panic!("Invalid [trending] config: {}", e);
+    }
     config
 }
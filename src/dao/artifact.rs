@@ -0,0 +1,218 @@
+use crate::models::{Artifact, ArtifactCount, ArtifactScore};
+use sqlx::{query, query_as, SqlitePool};
+
+pub struct ArtifactDAO;
+
+impl ArtifactDAO {
+    /// Id of the artifact of the same kind and key, inserted if there is none.
+    pub async fn create_artifact(
+        pool: &SqlitePool,
+        artifact: &Artifact,
+    ) -> Result<i64, sqlx::Error> {
+        let existing = query!(
+            "SELECT id FROM artifacts WHERE kind = ? AND key = ?",
+            artifact.kind,
+            artifact.key
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some(record) = existing {
+            return Ok(record.id);
+        }
+
+        let inserted = query!(
+            "INSERT INTO artifacts (kind, name, key) VALUES (?, ?, ?) RETURNING id",
+            artifact.kind,
+            artifact.name,
+            artifact.key,
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(inserted.id)
+    }
+
+    pub async fn associate_artifact_with_paper(
+        pool: &SqlitePool,
+        paper_id: i64,
+        artifact_id: i64,
+        extraction_id: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        query!(
+            "INSERT OR IGNORE INTO paper_artifacts (paper_id, artifact_id, extraction_id) VALUES (?, ?, ?)",
+            paper_id,
+            artifact_id,
+            extraction_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Scores already stored for the paper are skipped.
+    pub async fn add_score(pool: &SqlitePool, score: &ArtifactScore) -> Result<(), sqlx::Error> {
+        query!(
+            "INSERT OR IGNORE INTO artifact_scores (paper_id, benchmark_id, metric_id, value, extraction_id)
+            VALUES (?, ?, ?, ?, ?)",
+            score.paper_id,
+            score.benchmark_id,
+            score.metric_id,
+            score.value,
+            score.extraction_id,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn add_code_link(
+        pool: &SqlitePool,
+        paper_id: i64,
+        url: &str,
+        extraction_id: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        query!(
+            "INSERT OR IGNORE INTO code_links (paper_id, url, extraction_id) VALUES (?, ?, ?)",
+            paper_id,
+            url,
+            extraction_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The `limit` artifacts of `kind` used by most papers published since
+    /// `since`, e.g. `2025-03-01`.
+    pub async fn top_artifacts(
+        pool: &SqlitePool,
+        kind: &str,
+        since: &str,
+        limit: i64,
+    ) -> Result<Vec<ArtifactCount>, sqlx::Error> {
+        query_as!(
+            ArtifactCount,
+            r#"SELECT a.name, COUNT(DISTINCT pa.paper_id) AS "papers!: i64"
+            FROM artifacts a
+            JOIN paper_artifacts pa ON pa.artifact_id = a.id
+            JOIN papers p ON p.id = pa.paper_id
+            WHERE a.kind = ? AND COALESCE(p.publish_date, p.insert_date) >= ?
+            GROUP BY a.id
+            ORDER BY 2 DESC, a.name
+            LIMIT ?"#,
+            kind,
+            since,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::{
+        dao::PaperDAO,
+        models::{Artifact, ArtifactScore, Paper},
+    };
+
+    use super::ArtifactDAO;
+
+    #[tokio::test]
+    async fn test_top_artifacts() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let papers = [
+            ("a", "2025-04-01", vec!["HumanEval", "MBPP"]),
+            ("b", "2025-04-02", vec!["humaneval"]),
+            ("c", "2025-01-01", vec!["MBPP"]),
+        ];
+        for (title, date, benchmarks) in papers {
+            let paper = Paper {
+                id: 0,
+                title: title.to_string(),
+                abstract_text: None,
+                publish_date: Some(date.to_string()),
+                insert_date: date.to_string(),
+                url: format!("https://arxiv.org/pdf/{}", title),
+                comment: None,
+            };
+            let pid = PaperDAO::create_paper(&pool, &paper).await.unwrap();
+            for name in benchmarks {
+                let artifact = Artifact {
+                    id: 0,
+                    kind: "benchmark".to_string(),
+                    name: name.to_string(),
+                    key: name.to_lowercase(),
+                };
+                let aid = ArtifactDAO::create_artifact(&pool, &artifact)
+                    .await
+                    .unwrap();
+                ArtifactDAO::associate_artifact_with_paper(&pool, pid, aid, None)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let top = ArtifactDAO::top_artifacts(&pool, "benchmark", "2025-03-01", 10)
+            .await
+            .unwrap();
+        let top: Vec<(&str, i64)> = top.iter().map(|a| (a.name.as_str(), a.papers)).collect();
+        assert_eq!(top, vec![("HumanEval", 2), ("MBPP", 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_scores_are_stored_once() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let paper = Paper {
+            id: 0,
+            title: "a".to_string(),
+            abstract_text: None,
+            publish_date: None,
+            insert_date: "2025-04-01".to_string(),
+            url: "https://arxiv.org/pdf/a".to_string(),
+            comment: None,
+        };
+        let paper_id = PaperDAO::create_paper(&pool, &paper).await.unwrap();
+        let benchmark = Artifact {
+            id: 0,
+            kind: "benchmark".to_string(),
+            name: "MBPP".to_string(),
+            key: "mbpp".to_string(),
+        };
+        let benchmark_id = ArtifactDAO::create_artifact(&pool, &benchmark)
+            .await
+            .unwrap();
+        // no metric, NULLs must not make the scores distinct
+        let score = ArtifactScore {
+            id: 0,
+            paper_id,
+            benchmark_id,
+            metric_id: None,
+            value: "71.2%".to_string(),
+            extraction_id: None,
+        };
+        // the paper is extracted twice
+        ArtifactDAO::add_score(&pool, &score).await.unwrap();
+        ArtifactDAO::add_score(&pool, &score).await.unwrap();
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM artifact_scores")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::dao::PaperDAO;
+use crate::models::{Paper, PaperAuthorName};
+use sqlx::{query, query_as, SqlitePool};
+
+pub struct AuthorDAO;
+
+impl AuthorDAO {
+    pub async fn create_author(pool: &SqlitePool, name: &str) -> Result<i64, sqlx::Error> {
+        let id = query!(
+            "INSERT INTO authors (name) VALUES (?)
+            ON CONFLICT (name) DO UPDATE SET name = excluded.name
+            RETURNING id",
+            name
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(id.id)
+    }
+
+    /// Store the authors of `paper` in order, replacing the ones stored before.
+    pub async fn add_authors(
+        pool: &SqlitePool,
+        paper: &Paper,
+        authors: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let pid = PaperDAO::create_paper(pool, paper).await?;
+        query!("DELETE FROM paper_authors WHERE paper_id = ?", pid)
+            .execute(pool)
+            .await?;
+        for (position, name) in authors.iter().enumerate() {
+            let aid = Self::create_author(pool, name).await?;
+            let position = position as i64;
+            query!(
+                "INSERT OR IGNORE INTO paper_authors (paper_id, author_id, position) VALUES (?, ?, ?)",
+                pid,
+                aid,
+                position
+            )
+            .execute(pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Authors of each paper published since `since`, e.g. `2025-03-01`, by
+    /// paper and in order.
+    pub async fn authors_by_paper(
+        pool: &SqlitePool,
+        since: &str,
+    ) -> Result<Vec<PaperAuthorName>, sqlx::Error> {
+        query_as!(
+            PaperAuthorName,
+            r#"SELECT p.id AS "paper_id!: i64", a.name,
+                COALESCE(p.publish_date, p.insert_date) AS "published!: String"
+            FROM paper_authors pa
+            JOIN authors a ON a.id = pa.author_id
+            JOIN papers p ON p.id = pa.paper_id
+            WHERE COALESCE(p.publish_date, p.insert_date) >= ?
+            ORDER BY p.id, pa.position"#,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::models::Paper;
+
+    use super::AuthorDAO;
+
+    #[tokio::test]
+    async fn test_authors() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let papers = [
+            ("a", "2025-04-01", vec!["Ada Lovelace", "Alan Turing"]),
+            ("b", "2025-04-02", vec!["Alan Turing"]),
+            ("c", "2025-01-01", vec!["Grace Hopper"]),
+        ];
+        for (title, date, authors) in papers {
+            let paper = Paper {
+                id: 0,
+                title: title.to_string(),
+                abstract_text: None,
+                publish_date: Some(date.to_string()),
+                insert_date: date.to_string(),
+                url: format!("https://arxiv.org/pdf/{}", title),
+                comment: None,
+            };
+            let authors: Vec<String> = authors.into_iter().map(str::to_string).collect();
+            // storing a paper again keeps one row per author
+            for _ in 0..2 {
+                AuthorDAO::add_authors(&pool, &paper, &authors)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let authors = AuthorDAO::authors_by_paper(&pool, "2025-03-01")
+            .await
+            .unwrap();
+        let authors: Vec<(i64, &str)> = authors
+            .iter()
+            .map(|a| (a.paper_id, a.name.as_str()))
+            .collect();
+        assert_eq!(
+            authors,
+            vec![(1, "Ada Lovelace"), (1, "Alan Turing"), (2, "Alan Turing")]
+        );
+    }
+}
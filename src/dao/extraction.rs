@@ -0,0 +1,182 @@
+use crate::models::{PaperExtraction, PaperOutput};
+use futures::{stream::BoxStream, TryStreamExt};
+use sqlx::{query, query_as, SqlitePool};
+
+pub struct ExtractionDAO;
+
+impl ExtractionDAO {
+    pub async fn create_extraction(
+        pool: &SqlitePool,
+        extraction: &PaperExtraction,
+    ) -> Result<i64, sqlx::Error> {
+        let id = query!(
+            "INSERT INTO extractions (paper_id, extracter, prompt_name, prompt_version, prompt_hash, model, created_at, run_id, prompt_tokens, completion_tokens, cost, output)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING id",
+            extraction.paper_id,
+            extraction.extracter,
+            extraction.prompt_name,
+            extraction.prompt_version,
+            extraction.prompt_hash,
+            extraction.model,
+            extraction.created_at,
+            extraction.run_id,
+            extraction.prompt_tokens,
+            extraction.completion_tokens,
+            extraction.cost,
+            extraction.output,
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(id.id)
+    }
+
+    /// Papers published since `since`, e.g. `2025-03-01`, with the output
+    /// of their latest extraction that stored one.
+    pub async fn latest_outputs(
+        pool: &SqlitePool,
+        since: &str,
+    ) -> Result<Vec<PaperOutput>, sqlx::Error> {
+        Self::stream_outputs(pool, since).try_collect().await
+    }
+
+    /// [`ExtractionDAO::latest_outputs`] one paper at a time.
+    pub fn stream_outputs<'a>(
+        pool: &'a SqlitePool,
+        since: &'a str,
+    ) -> BoxStream<'a, Result<PaperOutput, sqlx::Error>> {
+        // the checked macro binds `since` to a local, which the stream
+        // would outlive, `test_stream_outputs` checks the columns instead
+        query_as::<_, PaperOutput>(
+            r#"SELECT p.id, p.title, p.abstract_text, p.publish_date, p.insert_date, p.url, p.comment,
+                (SELECT json_group_array(name) FROM (
+                    SELECT a.name FROM paper_authors pa
+                    JOIN authors a ON a.id = pa.author_id
+                    WHERE pa.paper_id = p.id
+                    ORDER BY pa.position
+                )) AS authors,
+                (SELECT json_group_array(json_object('keyword', keyword, 'confidence', confidence)) FROM (
+                    SELECT k.keyword, pk.confidence FROM paper_keywords pk
+                    JOIN keywords k ON k.id = pk.keyword_id
+                    WHERE pk.extraction_id = e.id
+                    ORDER BY pk.rowid
+                )) AS keywords,
+                e.output
+            FROM extractions e
+            JOIN papers p ON p.id = e.paper_id
+            WHERE COALESCE(p.publish_date, p.insert_date) >= ?
+                AND e.id = (SELECT MAX(id) FROM extractions WHERE paper_id = e.paper_id AND output IS NOT NULL)
+            ORDER BY p.id"#,
+        )
+        .bind(since)
+        .fetch(pool)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures::TryStreamExt;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::dao::PaperDAO;
+    use crate::models::{Paper, PaperExtraction};
+
+    use super::ExtractionDAO;
+
+    #[tokio::test]
+    async fn test_stream_outputs() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        // an old paper, one dated by its publication, one by its insertion
+        let papers = [
+            ("old", Some("2025-02-01"), "2025-04-01"),
+            ("published", Some("2025-03-20"), "2025-04-01"),
+            ("inserted", None, "2025-04-02"),
+        ];
+        for (title, publish_date, insert_date) in papers {
+            let paper = Paper {
+                id: 0,
+                title: title.to_string(),
+                abstract_text: Some(format!("{} abstract", title)),
+                publish_date: publish_date.map(str::to_string),
+                insert_date: insert_date.to_string(),
+                url: format!("https://arxiv.org/pdf/{}", title),
+                comment: None,
+            };
+            let paper_id = PaperDAO::create_paper(&pool, &paper).await.unwrap();
+            let extraction = PaperExtraction {
+                id: 0,
+                paper_id,
+                extracter: "topic".to_string(),
+                prompt_name: "topic".to_string(),
+                prompt_version: "1".to_string(),
+                prompt_hash: "hash".to_string(),
+                model: "mock".to_string(),
+                created_at: insert_date.to_string(),
+                run_id: None,
+                prompt_tokens: None,
+                completion_tokens: None,
+                cost: None,
+                output: Some(format!(r#"{{"title":"{}"}}"#, title)),
+            };
+            ExtractionDAO::create_extraction(&pool, &extraction)
+                .await
+                .unwrap();
+            // a later extraction without an output keeps the stored one
+            let failed = PaperExtraction {
+                output: None,
+                ..extraction
+            };
+            ExtractionDAO::create_extraction(&pool, &failed)
+                .await
+                .unwrap();
+        }
+
+        let outputs: Vec<_> = ExtractionDAO::stream_outputs(&pool, "2025-03-01")
+            .try_collect()
+            .await
+            .unwrap();
+        let rows: Vec<_> = outputs
+            .iter()
+            .map(|p| {
+                (
+                    p.title.as_str(),
+                    p.abstract_text.as_deref(),
+                    p.publish_date.as_deref(),
+                    p.insert_date.as_str(),
+                    p.url.as_str(),
+                    p.output.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    "published",
+                    Some("published abstract"),
+                    Some("2025-03-20"),
+                    "2025-04-01",
+                    "https://arxiv.org/pdf/published",
+                    r#"{"title":"published"}"#,
+                ),
+                (
+                    "inserted",
+                    Some("inserted abstract"),
+                    None,
+                    "2025-04-02",
+                    "https://arxiv.org/pdf/inserted",
+                    r#"{"title":"inserted"}"#,
+                ),
+            ]
+        );
+        assert!(outputs[0].id < outputs[1].id);
+        // no authors nor keywords stored
+        assert_eq!(outputs[0].authors, "[]");
+        assert_eq!(outputs[0].keywords, "[]");
+    }
+}
@@ -0,0 +1,131 @@
+use crate::models::FailedExtraction;
+use sqlx::{query, query_as, SqlitePool};
+
+pub struct FailedExtractionDAO;
+
+impl FailedExtractionDAO {
+    /// Insert a failure, or bump `attempts` if the paper already failed before.
+    pub async fn record_failure(
+        pool: &SqlitePool,
+        failure: &FailedExtraction,
+    ) -> Result<(), sqlx::Error> {
+        query!(
+            "INSERT INTO failed_extractions (arxiv_id, title, summary, publish_date, url, error_kind, error, attempts, last_attempt, transient)
+            VALUES (?, ?, ?, ?, ?, ?, ?, 1, ?, ?)
+            ON CONFLICT (arxiv_id) DO UPDATE SET
+                error_kind = excluded.error_kind,
+                error = excluded.error,
+                attempts = attempts + 1,
+                last_attempt = excluded.last_attempt,
+                transient = excluded.transient",
+            failure.arxiv_id,
+            failure.title,
+            failure.summary,
+            failure.publish_date,
+            failure.url,
+            failure.error_kind,
+            failure.error,
+            failure.last_attempt,
+            failure.transient,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Transient failures with fewer than `max_attempts` attempts.
+    pub async fn list_retryable(
+        pool: &SqlitePool,
+        max_attempts: i64,
+    ) -> Result<Vec<FailedExtraction>, sqlx::Error> {
+        query_as!(
+            FailedExtraction,
+            r#"SELECT id, arxiv_id, title, summary, publish_date, url, error_kind, error, attempts, last_attempt, transient AS "transient: bool"
+            FROM failed_extractions WHERE transient AND attempts < ? ORDER BY last_attempt"#,
+            max_attempts
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn delete_failure(pool: &SqlitePool, arxiv_id: &str) -> Result<(), sqlx::Error> {
+        query!(
+            "DELETE FROM failed_extractions WHERE arxiv_id = ?",
+            arxiv_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::models::FailedExtraction;
+
+    use super::FailedExtractionDAO;
+
+    #[tokio::test]
+    async fn test_record_failure() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let failure = FailedExtraction {
+            id: 0,
+            arxiv_id: "http://arxiv.org/abs/2502.00001v1".to_string(),
+            title: "title".to_string(),
+            summary: "summary".to_string(),
+            publish_date: "2025-02-01T00:00:00Z".to_string(),
+            url: "https://arxiv.org/pdf/2502.00001v1".to_string(),
+            error_kind: "timeout".to_string(),
+            error: "timed out".to_string(),
+            attempts: 1,
+            last_attempt: "2025-03-01".to_string(),
+            transient: true,
+        };
+        FailedExtractionDAO::record_failure(&pool, &failure)
+            .await
+            .unwrap();
+        FailedExtractionDAO::record_failure(&pool, &failure)
+            .await
+            .unwrap();
+
+        let failures = FailedExtractionDAO::list_retryable(&pool, 3).await.unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].attempts, 2);
+        assert!(FailedExtractionDAO::list_retryable(&pool, 2)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let permanent = FailedExtraction {
+            arxiv_id: "http://arxiv.org/abs/2502.00002v1".to_string(),
+            transient: false,
+            ..failure
+        };
+        FailedExtractionDAO::record_failure(&pool, &permanent)
+            .await
+            .unwrap();
+        assert_eq!(
+            FailedExtractionDAO::list_retryable(&pool, 3)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        FailedExtractionDAO::delete_failure(&pool, "http://arxiv.org/abs/2502.00001v1")
+            .await
+            .unwrap();
+        assert!(FailedExtractionDAO::list_retryable(&pool, 3)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::models::Keyword;
-use sqlx::{query, SqlitePool};
+use sqlx::{query, query_as, SqlitePool};
 
 pub struct KeywordDAO;
 
@@ -22,4 +22,10 @@ impl KeywordDAO {
 
         Ok(inserted_id.id)
     }
+
+    pub async fn list_keywords(pool: &SqlitePool) -> Result<Vec<Keyword>, sqlx::Error> {
+        query_as!(Keyword, "SELECT id, keyword FROM keywords ORDER BY id")
+            .fetch_all(pool)
+            .await
+    }
 }
@@ -0,0 +1,109 @@
+use crate::models::KeywordAlias;
+use sqlx::{query, query_as, SqlitePool};
+
+pub struct KeywordAliasDAO;
+
+impl KeywordAliasDAO {
+    /// Record a merge made by the normalizer. Pinned and rejected aliases are left untouched.
+    pub async fn record_alias(
+        pool: &SqlitePool,
+        alias: &str,
+        keyword_id: i64,
+        score: Option<f64>,
+    ) -> Result<(), sqlx::Error> {
+        query!(
+            "INSERT INTO keyword_aliases (alias, keyword_id, status, score) VALUES (?, ?, 'auto', ?)
+            ON CONFLICT (alias, keyword_id) DO UPDATE SET score = excluded.score
+            WHERE keyword_aliases.status = 'auto'",
+            alias,
+            keyword_id,
+            score,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Set a user decision on `alias`. Pinning an alias drops every other merge of it.
+    pub async fn set_status(
+        pool: &SqlitePool,
+        alias: &str,
+        keyword_id: i64,
+        status: &str,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        if status == "pinned" {
+            query!(
+                "DELETE FROM keyword_aliases WHERE alias = ? AND keyword_id != ? AND status != 'rejected'",
+                alias,
+                keyword_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        query!(
+            "INSERT INTO keyword_aliases (alias, keyword_id, status) VALUES (?, ?, ?)
+            ON CONFLICT (alias, keyword_id) DO UPDATE SET status = excluded.status",
+            alias,
+            keyword_id,
+            status,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
+    pub async fn list_aliases(pool: &SqlitePool) -> Result<Vec<KeywordAlias>, sqlx::Error> {
+        query_as!(
+            KeywordAlias,
+            "SELECT a.id, a.alias, k.keyword, a.status, a.score
+            FROM keyword_aliases a JOIN keywords k ON a.keyword_id = k.id ORDER BY a.id"
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::{dao::KeywordDAO, models::Keyword};
+
+    use super::KeywordAliasDAO;
+
+    #[tokio::test]
+    async fn test_user_status_survives_auto_merge() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let llm = KeywordDAO::create_keyword(
+            &pool,
+            &Keyword {
+                id: 0,
+                keyword: "large language model".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        KeywordAliasDAO::record_alias(&pool, "LLMs", llm, Some(0.9))
+            .await
+            .unwrap();
+        KeywordAliasDAO::set_status(&pool, "LLMs", llm, "rejected")
+            .await
+            .unwrap();
+        KeywordAliasDAO::record_alias(&pool, "LLMs", llm, Some(0.95))
+            .await
+            .unwrap();
+
+        let aliases = KeywordAliasDAO::list_aliases(&pool).await.unwrap();
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(aliases[0].keyword, "large language model");
+        assert_eq!(aliases[0].status, "rejected");
+        assert_eq!(aliases[0].score, Some(0.9));
+    }
+}
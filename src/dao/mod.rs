@@ -1,20 +1,133 @@
+mod artifact;
+mod author;
+mod extraction;
+mod failed_extraction;
 mod keyword;
+mod keyword_alias;
 mod paper;
 mod paper_keyword;
+mod paper_summary;
+mod relevance;
+mod run;
+mod topic;
 
-use crate::models::{Keyword, Paper};
+use crate::models::{Keyword, Paper, PaperExtraction};
+pub use artifact::*;
+pub use author::*;
+pub use extraction::*;
+pub use failed_extraction::*;
 pub use keyword::*;
+pub use keyword_alias::*;
 pub use paper::*;
 pub use paper_keyword::*;
+pub use paper_summary::*;
+pub use relevance::*;
+pub use run::*;
 use sqlx::SqlitePool;
+pub use topic::*;
 
+/// Store `paper` with its keywords. With `extraction`, its provenance and
+/// usage are recorded for the new paper too, its `paper_id` is ignored, and
+/// the id of the stored extraction is returned.
 pub async fn add_paper_with_keywords(
     pool: &SqlitePool,
     paper: &Paper,
-    keywords: &Vec<Keyword>,
-) -> Result<(), sqlx::Error> {
-    for keyword in keywords.iter() {
-        PaperKeywordDAO::associate_keyword_with_paper(pool, paper, keyword).await?;
+    keywords: &[(Keyword, Option<f64>)],
+    extraction: Option<&PaperExtraction>,
+) -> Result<Option<i64>, sqlx::Error> {
+    let extraction_id = match extraction {
+        Some(extraction) => {
+            let paper_id = PaperDAO::create_paper(pool, paper).await?;
+            let extraction = PaperExtraction {
+                paper_id,
+                ..extraction.clone()
+            };
+            Some(ExtractionDAO::create_extraction(pool, &extraction).await?)
+        }
+        None => None,
+    };
+    for (keyword, confidence) in keywords.iter() {
+        PaperKeywordDAO::associate_keyword_with_paper(
+            pool,
+            paper,
+            keyword,
+            extraction_id,
+            *confidence,
+        )
+        .await?;
+    }
+    Ok(extraction_id)
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::{query_as, sqlite::SqlitePoolOptions};
+
+    use crate::{
+        extracters::{topic::default_topic_prompt, Provenance},
+        models::{Keyword, Paper, PaperExtraction},
+    };
+
+    use super::{add_paper_with_keywords, ExtractionDAO};
+
+    #[tokio::test]
+    async fn test_add_paper_with_provenance() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let paper = Paper {
+            id: 0,
+            title: "title".to_string(),
+            abstract_text: None,
+            publish_date: None,
+            insert_date: "2025-03-10".to_string(),
+            url: "https://arxiv.org/pdf/2503.00001v1".to_string(),
+            comment: None,
+        };
+        let keywords = vec![(
+            Keyword {
+                id: 0,
+                keyword: "LLM".to_string(),
+            },
+            Some(0.5),
+        )];
+        let provenance = Provenance::new("topic", &default_topic_prompt(), "mock");
+        let extraction = PaperExtraction {
+            prompt_tokens: Some(1200),
+            completion_tokens: Some(80),
+            cost: Some(0.001),
+            output: Some(r#"{"title":"title","language":"en"}"#.to_string()),
+            ..provenance.to_extraction(&paper.insert_date)
+        };
+        add_paper_with_keywords(&pool, &paper, &keywords, Some(&extraction))
+            .await
+            .unwrap();
+
+        let (model, confidence, tokens, output): (String, Option<f64>, Option<i64>, Option<String>) = query_as(
+            "SELECT e.model, pk.confidence, e.prompt_tokens, e.output FROM paper_keywords pk JOIN extractions e ON pk.extraction_id = e.id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(model, "mock");
+        assert_eq!(confidence, Some(0.5));
+        assert_eq!(tokens, Some(1200));
+        assert_eq!(output, extraction.output);
+
+        // a later extraction of the same paper replaces its output
+        let later = PaperExtraction {
+            output: Some(r#"{"title":"title","language":"de"}"#.to_string()),
+            ..extraction.clone()
+        };
+        add_paper_with_keywords(&pool, &paper, &[], Some(&later))
+            .await
+            .unwrap();
+        let outputs = ExtractionDAO::latest_outputs(&pool, "").await.unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(Some(&outputs[0].output), later.output.as_ref());
     }
-    Ok(())
 }
@@ -1,5 +1,5 @@
-use crate::models::Paper;
-use sqlx::{query, SqlitePool};
+use crate::models::{DayCount, Paper};
+use sqlx::{query, query_as, SqlitePool};
 
 pub struct PaperDAO;
 
@@ -14,15 +14,97 @@ impl PaperDAO {
         }
 
         let id = query!(
-            "INSERT INTO papers (title, abstract_text, publish_date, insert_date, url) VALUES (?, ?, ?, ?, ?) RETURNING id",
+            "INSERT INTO papers (title, abstract_text, publish_date, insert_date, url, comment) VALUES (?, ?, ?, ?, ?, ?) RETURNING id",
             paper.title,
             paper.abstract_text,
             paper.publish_date,
             paper.insert_date,
             paper.url,
+            paper.comment,
         )
             .fetch_one(pool)
             .await?;
         Ok(id.id)
     }
+
+    /// Papers published since `since`, e.g. `2025-03-01`, the oldest first,
+    /// dated by their insertion when the publish date is unknown.
+    pub async fn list_papers(pool: &SqlitePool, since: &str) -> Result<Vec<Paper>, sqlx::Error> {
+        query_as!(
+            Paper,
+            "SELECT id, title, abstract_text, publish_date, insert_date, url, comment
+            FROM papers
+            WHERE COALESCE(publish_date, insert_date) >= ?
+            ORDER BY COALESCE(publish_date, insert_date), id",
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Papers per day published since `since`, e.g. `2025-03-01`, dated by
+    /// their insertion when the publish date is unknown.
+    pub async fn daily_counts(
+        pool: &SqlitePool,
+        since: &str,
+    ) -> Result<Vec<DayCount>, sqlx::Error> {
+        query_as!(
+            DayCount,
+            r#"SELECT substr(COALESCE(publish_date, insert_date), 1, 10) AS "day!: String",
+                COUNT(*) AS "papers!: i64"
+            FROM papers
+            WHERE COALESCE(publish_date, insert_date) >= ?
+            GROUP BY 1
+            ORDER BY 1"#,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::models::Paper;
+
+    use super::PaperDAO;
+
+    #[tokio::test]
+    async fn test_daily_counts() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let papers = [
+            ("a", Some("2025-04-01T10:00:00Z")),
+            ("b", Some("2025-04-01T18:00:00Z")),
+            ("c", None),
+            ("d", Some("2025-01-01T09:00:00Z")),
+        ];
+        for (title, date) in papers {
+            let paper = Paper {
+                id: 0,
+                title: title.to_string(),
+                abstract_text: None,
+                publish_date: date.map(str::to_string),
+                insert_date: "2025-04-03 12:00:00 +02:00".to_string(),
+                url: format!("https://arxiv.org/pdf/{}", title),
+                comment: None,
+            };
+            PaperDAO::create_paper(&pool, &paper).await.unwrap();
+        }
+
+        let counts = PaperDAO::daily_counts(&pool, "2025-03-01").await.unwrap();
+        let counts: Vec<(&str, i64)> = counts.iter().map(|c| (c.day.as_str(), c.papers)).collect();
+        assert_eq!(counts, vec![("2025-04-01", 2), ("2025-04-03", 1)]);
+
+        let papers = PaperDAO::list_papers(&pool, "2025-03-01").await.unwrap();
+        let titles: Vec<&str> = papers.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, vec!["a", "b", "c"]);
+    }
 }
@@ -1,6 +1,6 @@
 use crate::dao::{KeywordDAO, PaperDAO};
-use crate::models::{Keyword, Paper};
-use sqlx::{query, SqlitePool};
+use crate::models::{Keyword, KeywordDayCount, KeywordPaper, Paper, PaperKeywordName};
+use sqlx::{query, query_as, SqlitePool};
 
 pub struct PaperKeywordDAO;
 
@@ -9,16 +9,169 @@ impl PaperKeywordDAO {
         pool: &SqlitePool,
         paper: &Paper,
         keyword: &Keyword,
+        extraction_id: Option<i64>,
+        confidence: Option<f64>,
     ) -> Result<(), sqlx::Error> {
         let pid = PaperDAO::create_paper(pool, paper).await?;
         let kid = KeywordDAO::create_keyword(pool, keyword).await?;
         query!(
-            "INSERT INTO paper_keywords (paper_id, keyword_id) VALUES (?, ?)",
+            "INSERT INTO paper_keywords (paper_id, keyword_id, extraction_id, confidence) VALUES (?, ?, ?, ?)",
             pid,
-            kid
+            kid,
+            extraction_id,
+            confidence
         )
         .execute(pool)
         .await?;
         Ok(())
     }
+
+    /// Keywords of each paper published since `since`, by paper.
+    pub async fn keywords_by_paper(
+        pool: &SqlitePool,
+        since: &str,
+    ) -> Result<Vec<PaperKeywordName>, sqlx::Error> {
+        query_as!(
+            PaperKeywordName,
+            r#"SELECT DISTINCT p.id AS "paper_id!: i64", k.keyword
+            FROM paper_keywords pk
+            JOIN keywords k ON k.id = pk.keyword_id
+            JOIN papers p ON p.id = pk.paper_id
+            WHERE COALESCE(p.publish_date, p.insert_date) >= ?
+            ORDER BY p.id, k.keyword"#,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Papers of each keyword published from `from` to `until`, both included,
+    /// by keyword and the latest first.
+    pub async fn keyword_papers(
+        pool: &SqlitePool,
+        from: &str,
+        until: &str,
+    ) -> Result<Vec<KeywordPaper>, sqlx::Error> {
+        query_as!(
+            KeywordPaper,
+            r#"SELECT DISTINCT k.keyword, p.title, p.url,
+                COALESCE(p.publish_date, p.insert_date) AS "published!: String"
+            FROM paper_keywords pk
+            JOIN keywords k ON k.id = pk.keyword_id
+            JOIN papers p ON p.id = pk.paper_id
+            WHERE substr(COALESCE(p.publish_date, p.insert_date), 1, 10) BETWEEN ? AND ?
+            ORDER BY k.keyword, 4 DESC, p.url"#,
+            from,
+            until
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Papers per keyword and day published since `since`, e.g. `2025-03-01`,
+    /// dated by their insertion when the publish date is unknown.
+    pub async fn daily_counts(
+        pool: &SqlitePool,
+        since: &str,
+    ) -> Result<Vec<KeywordDayCount>, sqlx::Error> {
+        query_as!(
+            KeywordDayCount,
+            r#"SELECT k.keyword, substr(COALESCE(p.publish_date, p.insert_date), 1, 10) AS "day!: String",
+                COUNT(DISTINCT p.id) AS "papers!: i64"
+            FROM paper_keywords pk
+            JOIN keywords k ON k.id = pk.keyword_id
+            JOIN papers p ON p.id = pk.paper_id
+            WHERE COALESCE(p.publish_date, p.insert_date) >= ?
+            GROUP BY k.id, 2
+            ORDER BY 2, k.keyword"#,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::models::{Keyword, Paper};
+
+    use super::PaperKeywordDAO;
+
+    #[tokio::test]
+    async fn test_keyword_counts() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let papers = [
+            ("a", "2025-04-01T10:00:00Z", vec!["LLM", "fuzzing"]),
+            ("b", "2025-04-01T18:00:00Z", vec!["LLM"]),
+            ("c", "2025-04-02T09:00:00Z", vec!["LLM"]),
+            ("d", "2025-01-01T09:00:00Z", vec!["LLM"]),
+        ];
+        for (title, date, keywords) in papers {
+            let paper = Paper {
+                id: 0,
+                title: title.to_string(),
+                abstract_text: None,
+                publish_date: Some(date.to_string()),
+                insert_date: date.to_string(),
+                url: format!("https://arxiv.org/pdf/{}", title),
+                comment: None,
+            };
+            for keyword in keywords {
+                let keyword = Keyword {
+                    id: 0,
+                    keyword: keyword.to_string(),
+                };
+                // a second extraction of the same paper counts once
+                for _ in 0..2 {
+                    PaperKeywordDAO::associate_keyword_with_paper(
+                        &pool, &paper, &keyword, None, None,
+                    )
+                    .await
+                    .unwrap();
+                }
+            }
+        }
+
+        let keywords = PaperKeywordDAO::keywords_by_paper(&pool, "2025-03-01")
+            .await
+            .unwrap();
+        let keywords: Vec<&str> = keywords.iter().map(|k| k.keyword.as_str()).collect();
+        assert_eq!(keywords, vec!["LLM", "fuzzing", "LLM", "LLM"]);
+
+        let papers = PaperKeywordDAO::keyword_papers(&pool, "2025-01-01", "2025-04-01")
+            .await
+            .unwrap();
+        let papers: Vec<(&str, &str)> = papers
+            .iter()
+            .map(|p| (p.keyword.as_str(), p.title.as_str()))
+            .collect();
+        assert_eq!(
+            papers,
+            vec![("LLM", "b"), ("LLM", "a"), ("LLM", "d"), ("fuzzing", "a")]
+        );
+
+        let counts = PaperKeywordDAO::daily_counts(&pool, "2025-03-01")
+            .await
+            .unwrap();
+        let counts: Vec<(&str, &str, i64)> = counts
+            .iter()
+            .map(|c| (c.keyword.as_str(), c.day.as_str(), c.papers))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("LLM", "2025-04-01", 2),
+                ("fuzzing", "2025-04-01", 1),
+                ("LLM", "2025-04-02", 1)
+            ]
+        );
+    }
 }
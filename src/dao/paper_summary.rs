@@ -0,0 +1,91 @@
+use crate::dao::PaperDAO;
+use crate::models::{Paper, PaperSummary};
+use sqlx::{query, SqlitePool};
+
+pub struct PaperSummaryDAO;
+
+impl PaperSummaryDAO {
+    /// Store the summary of `paper`, replacing an older one.
+    pub async fn save_summary(
+        pool: &SqlitePool,
+        paper: &Paper,
+        summary: &PaperSummary,
+    ) -> Result<(), sqlx::Error> {
+        let pid = PaperDAO::create_paper(pool, paper).await?;
+        query!(
+            "INSERT INTO paper_summaries (paper_id, extraction_id, tldr, contributions, results, limitations, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (paper_id) DO UPDATE SET
+                extraction_id = excluded.extraction_id,
+                tldr = excluded.tldr,
+                contributions = excluded.contributions,
+                results = excluded.results,
+                limitations = excluded.limitations,
+                created_at = excluded.created_at",
+            pid,
+            summary.extraction_id,
+            summary.tldr,
+            summary.contributions,
+            summary.results,
+            summary.limitations,
+            summary.created_at,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::{query_as, sqlite::SqlitePoolOptions};
+
+    use crate::models::{Paper, PaperSummary};
+
+    use super::PaperSummaryDAO;
+
+    #[tokio::test]
+    async fn test_save_summary() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let paper = Paper {
+            id: 0,
+            title: "title".to_string(),
+            abstract_text: None,
+            publish_date: None,
+            insert_date: "2025-04-01".to_string(),
+            url: "https://arxiv.org/pdf/2504.00001v1".to_string(),
+            comment: None,
+        };
+        for tldr in ["first", "second"] {
+            let summary = PaperSummary {
+                id: 0,
+                paper_id: 0,
+                extraction_id: None,
+                tldr: tldr.to_string(),
+                contributions: r#"["a tool"]"#.to_string(),
+                results: "[]".to_string(),
+                limitations: "[]".to_string(),
+                created_at: "2025-04-01".to_string(),
+            };
+            PaperSummaryDAO::save_summary(&pool, &paper, &summary)
+                .await
+                .unwrap();
+        }
+
+        let summaries: Vec<(String, String)> =
+            query_as("SELECT tldr, contributions FROM paper_summaries")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            summaries,
+            vec![("second".to_string(), r#"["a tool"]"#.to_string())]
+        );
+    }
+}
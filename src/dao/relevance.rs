@@ -0,0 +1,101 @@
+use crate::models::PaperRelevance;
+use sqlx::{query, query_as, SqlitePool};
+
+pub struct RelevanceDAO;
+
+impl RelevanceDAO {
+    /// Store the relevance of a paper, replacing the score of an earlier run.
+    pub async fn save_relevance(
+        pool: &SqlitePool,
+        relevance: &PaperRelevance,
+    ) -> Result<(), sqlx::Error> {
+        query!(
+            "INSERT INTO paper_relevance (arxiv_id, title, score, relevant, kept, run_id, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (arxiv_id) DO UPDATE SET
+                title = excluded.title,
+                score = excluded.score,
+                relevant = excluded.relevant,
+                kept = excluded.kept,
+                run_id = excluded.run_id,
+                created_at = excluded.created_at",
+            relevance.arxiv_id,
+            relevance.title,
+            relevance.score,
+            relevance.relevant,
+            relevance.kept,
+            relevance.run_id,
+            relevance.created_at,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The latest `limit` papers below the threshold, dropped or flagged.
+    pub async fn list_off_topic(
+        pool: &SqlitePool,
+        limit: i64,
+    ) -> Result<Vec<PaperRelevance>, sqlx::Error> {
+        query_as!(
+            PaperRelevance,
+            r#"SELECT arxiv_id, title, score, relevant AS "relevant: bool", kept AS "kept: bool", run_id, created_at
+            FROM paper_relevance WHERE NOT relevant ORDER BY created_at DESC, score LIMIT ?"#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::models::PaperRelevance;
+
+    use super::RelevanceDAO;
+
+    #[tokio::test]
+    async fn test_relevance() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let scores = [("2504.00001", 0.9, true), ("2504.00002", 0.2, false)];
+        for (arxiv_id, score, relevant) in scores {
+            let relevance = PaperRelevance {
+                arxiv_id: arxiv_id.to_string(),
+                title: "title".to_string(),
+                score,
+                relevant,
+                kept: relevant,
+                run_id: None,
+                created_at: "2025-04-15".to_string(),
+            };
+            RelevanceDAO::save_relevance(&pool, &relevance)
+                .await
+                .unwrap();
+        }
+        // flagged in a later run instead of dropped
+        let flagged = PaperRelevance {
+            arxiv_id: "2504.00002".to_string(),
+            title: "title".to_string(),
+            score: 0.3,
+            relevant: false,
+            kept: true,
+            run_id: None,
+            created_at: "2025-04-16".to_string(),
+        };
+        RelevanceDAO::save_relevance(&pool, &flagged).await.unwrap();
+
+        let off_topic = RelevanceDAO::list_off_topic(&pool, 10).await.unwrap();
+        assert_eq!(off_topic.len(), 1);
+        assert_eq!(off_topic[0].arxiv_id, "2504.00002");
+        assert_eq!(off_topic[0].score, 0.3);
+        assert!(off_topic[0].kept);
+    }
+}
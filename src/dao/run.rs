@@ -0,0 +1,137 @@
+use crate::models::{Run, RunUsage};
+use sqlx::{query, query_as, SqlitePool};
+
+pub struct RunDAO;
+
+impl RunDAO {
+    pub async fn create_run(pool: &SqlitePool, run: &Run) -> Result<i64, sqlx::Error> {
+        let id = query!(
+            "INSERT INTO runs (created_at, elapsed_ms, successes, failures, unfinished)
+            VALUES (?, ?, ?, ?, ?) RETURNING id",
+            run.created_at,
+            run.elapsed_ms,
+            run.successes,
+            run.failures,
+            run.unfinished,
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(id.id)
+    }
+
+    pub async fn add_usage(pool: &SqlitePool, usage: &RunUsage) -> Result<(), sqlx::Error> {
+        query!(
+            "INSERT INTO run_usage (run_id, model, extractions, prompt_tokens, completion_tokens, cost)
+            VALUES (?, ?, ?, ?, ?, ?)",
+            usage.run_id,
+            usage.model,
+            usage.extractions,
+            usage.prompt_tokens,
+            usage.completion_tokens,
+            usage.cost,
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The latest `limit` runs, newest first.
+    pub async fn list_runs(pool: &SqlitePool, limit: i64) -> Result<Vec<Run>, sqlx::Error> {
+        query_as!(
+            Run,
+            "SELECT id, created_at, elapsed_ms, successes, failures, unfinished
+            FROM runs ORDER BY id DESC LIMIT ?",
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn list_usage(pool: &SqlitePool, run_id: i64) -> Result<Vec<RunUsage>, sqlx::Error> {
+        query_as!(
+            RunUsage,
+            "SELECT run_id, model, extractions, prompt_tokens, completion_tokens, cost
+            FROM run_usage WHERE run_id = ? ORDER BY model",
+            run_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Usage of every model over all runs since `since`, `run_id` is the
+    /// latest run that used the model.
+    pub async fn usage_by_model(
+        pool: &SqlitePool,
+        since: &str,
+    ) -> Result<Vec<RunUsage>, sqlx::Error> {
+        query_as!(
+            RunUsage,
+            r#"SELECT MAX(u.run_id) AS "run_id!: i64", u.model AS "model!",
+                SUM(u.extractions) AS "extractions!: i64",
+                SUM(u.prompt_tokens) AS "prompt_tokens!: i64",
+                SUM(u.completion_tokens) AS "completion_tokens!: i64",
+                SUM(u.cost) AS "cost: f64"
+            FROM run_usage u JOIN runs r ON u.run_id = r.id
+            WHERE r.created_at >= ? GROUP BY u.model ORDER BY u.model"#,
+            since
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::models::{Run, RunUsage};
+
+    use super::RunDAO;
+
+    #[tokio::test]
+    async fn test_usage_by_model() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        for (day, cost) in [("2025-03-24", Some(0.5)), ("2025-03-25", None)] {
+            let run = Run {
+                id: 0,
+                created_at: day.to_string(),
+                elapsed_ms: 1000,
+                successes: 2,
+                failures: 0,
+                unfinished: 0,
+            };
+            let run_id = RunDAO::create_run(&pool, &run).await.unwrap();
+            let usage = RunUsage {
+                run_id,
+                model: "gpt-4o-mini".to_string(),
+                extractions: 2,
+                prompt_tokens: 2000,
+                completion_tokens: 200,
+                cost,
+            };
+            RunDAO::add_usage(&pool, &usage).await.unwrap();
+        }
+
+        let runs = RunDAO::list_runs(&pool, 10).await.unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].created_at, "2025-03-25");
+        assert_eq!(
+            RunDAO::list_usage(&pool, runs[1].id).await.unwrap()[0].cost,
+            Some(0.5)
+        );
+
+        let usage = RunDAO::usage_by_model(&pool, "2025-03-01").await.unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].extractions, 4);
+        assert_eq!(usage[0].prompt_tokens, 4000);
+        assert_eq!(usage[0].cost, Some(0.5));
+        let usage = RunDAO::usage_by_model(&pool, "2025-03-25").await.unwrap();
+        assert_eq!(usage[0].cost, None);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::models::{PaperTopic, StoredTopic, TopicKeyword, TopicModelRun};
+use sqlx::{query, query_as, Executor, Sqlite, SqlitePool};
+
+pub struct TopicDAO;
+
+impl TopicDAO {
+    pub async fn create_model<'e, E: Executor<'e, Database = Sqlite>>(
+        executor: E,
+        model: &TopicModelRun,
+    ) -> Result<i64, sqlx::Error> {
+        let id = query!(
+            "INSERT INTO topic_models (method, papers, created_at) VALUES (?, ?, ?) RETURNING id",
+            model.method,
+            model.papers,
+            model.created_at,
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(id.id)
+    }
+
+    pub async fn create_topic<'e, E: Executor<'e, Database = Sqlite>>(
+        executor: E,
+        topic: &StoredTopic,
+    ) -> Result<i64, sqlx::Error> {
+        let id = query!(
+            "INSERT INTO topics (model_id, position, label, terms, prevalence) VALUES (?, ?, ?, ?, ?) RETURNING id",
+            topic.model_id,
+            topic.position,
+            topic.label,
+            topic.terms,
+            topic.prevalence,
+        )
+        .fetch_one(executor)
+        .await?;
+        Ok(id.id)
+    }
+
+    pub async fn add_paper_topic<'e, E: Executor<'e, Database = Sqlite>>(
+        executor: E,
+        topic: &PaperTopic,
+    ) -> Result<(), sqlx::Error> {
+        query!(
+            "INSERT INTO paper_topics (paper_id, topic_id, weight) VALUES (?, ?, ?)
+            ON CONFLICT (paper_id, topic_id) DO UPDATE SET weight = excluded.weight",
+            topic.paper_id,
+            topic.topic_id,
+            topic.weight,
+        )
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Topics of the latest model, the most prevalent first.
+    pub async fn latest_topics(pool: &SqlitePool) -> Result<Vec<StoredTopic>, sqlx::Error> {
+        query_as!(
+            StoredTopic,
+            "SELECT id, model_id, position, label, terms, prevalence FROM topics
+            WHERE model_id = (SELECT MAX(id) FROM topic_models)
+            ORDER BY position"
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The `limit` extracted keywords most associated with a topic, each
+    /// paper counting with the weight of the topic in it.
+    pub async fn topic_keywords(
+        pool: &SqlitePool,
+        topic_id: i64,
+        limit: i64,
+    ) -> Result<Vec<TopicKeyword>, sqlx::Error> {
+        query_as!(
+            TopicKeyword,
+            r#"SELECT k.keyword, SUM(pt.weight) AS "weight!: f64"
+            FROM paper_topics pt
+            JOIN (SELECT DISTINCT paper_id, keyword_id FROM paper_keywords) pk ON pk.paper_id = pt.paper_id
+            JOIN keywords k ON k.id = pk.keyword_id
+            WHERE pt.topic_id = ?
+            GROUP BY k.id
+            ORDER BY 2 DESC, k.keyword
+            LIMIT ?"#,
+            topic_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::{
+        dao::{PaperDAO, PaperKeywordDAO},
+        models::{Keyword, Paper, PaperTopic, StoredTopic, TopicModelRun},
+    };
+
+    use super::TopicDAO;
+
+    #[tokio::test]
+    async fn test_topics() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        let papers = [("a", vec!["LLM", "fuzzing"]), ("b", vec!["fuzzing"])];
+        let mut ids = Vec::new();
+        for (title, keywords) in papers {
+            let paper = Paper {
+                id: 0,
+                title: title.to_string(),
+                abstract_text: None,
+                publish_date: None,
+                insert_date: "2025-04-20".to_string(),
+                url: format!("https://arxiv.org/pdf/{}", title),
+                comment: None,
+            };
+            ids.push(PaperDAO::create_paper(&pool, &paper).await.unwrap());
+            for keyword in keywords {
+                let keyword = Keyword {
+                    id: 0,
+                    keyword: keyword.to_string(),
+                };
+                PaperKeywordDAO::associate_keyword_with_paper(&pool, &paper, &keyword, None, None)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        // an older model is superseded by the latest one
+        for _ in 0..2 {
+            let model = TopicModelRun {
+                id: 0,
+                method: "nmf".to_string(),
+                papers: 2,
+                created_at: "2025-04-20".to_string(),
+            };
+            let model_id = TopicDAO::create_model(&pool, &model).await.unwrap();
+            let topic = StoredTopic {
+                id: 0,
+                model_id,
+                position: 0,
+                label: "fuzzing".to_string(),
+                terms: r#"["fuzzing"]"#.to_string(),
+                prevalence: 1.0,
+            };
+            let topic_id = TopicDAO::create_topic(&pool, &topic).await.unwrap();
+            for (paper_id, weight) in ids.iter().zip([0.25, 1.0]) {
+                let paper_topic = PaperTopic {
+                    paper_id: *paper_id,
+                    topic_id,
+                    weight,
+                };
+                TopicDAO::add_paper_topic(&pool, &paper_topic)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let topics = TopicDAO::latest_topics(&pool).await.unwrap();
+        assert_eq!(topics.len(), 1);
+        assert_eq!(topics[0].model_id, 2);
+        let keywords = TopicDAO::topic_keywords(&pool, topics[0].id, 10)
+            .await
+            .unwrap();
+        let keywords: Vec<(&str, f64)> = keywords
+            .iter()
+            .map(|k| (k.keyword.as_str(), k.weight))
+            .collect();
+        assert_eq!(keywords, vec![("fuzzing", 1.25), ("LLM", 0.25)]);
+    }
+}
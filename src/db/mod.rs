@@ -3,5 +3,7 @@ use std::env;
 
 pub async fn get_db_pool() -> Result<SqlitePool, sqlx::Error> {
     let db_url = env::var("DATABASE_URL").unwrap_or("sqlite://my_database.db".into());
-    SqlitePool::connect(&db_url).await
+    let pool = SqlitePool::connect(&db_url).await?;
+    sqlx::migrate!().run(&pool).await?;
+    Ok(pool)
 }
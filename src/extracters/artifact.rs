@@ -0,0 +1,354 @@
+use std::{fmt::Display, sync::Arc};
+
+use arxiv::Arxiv;
+use derive_builder::Builder;
+use langchain_rust::{
+    fmt_template,
+    language_models::llm::LLM,
+    message_formatter,
+    prompt::{FormatPrompter, HumanMessagePromptTemplate},
+    prompt_args, template_fstring,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    agent::AgentApp,
+    normalizers::keyword::rule_key,
+    prompts::PromptTemplate,
+    tools::{
+        parser::parse_json_md,
+        usage::{count_tokens, usage_or_estimate, ExtractionUsage},
+    },
+};
+
+use super::{Extracter, ExtracterError, Provenance};
+
+/// The `artifact` prompt shipped with the crate.
+pub fn default_artifact_prompt() -> PromptTemplate {
+    PromptTemplate::new("artifact", "1", include_str!("../../prompts/artifact.md"))
+}
+
+/// Kinds of named artifacts, stored as `as_str` in `artifacts.kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArtifactKind {
+    Dataset,
+    Benchmark,
+    Metric,
+}
+
+impl ArtifactKind {
+    pub const ALL: [ArtifactKind; 3] = [Self::Dataset, Self::Benchmark, Self::Metric];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dataset => "dataset",
+            Self::Benchmark => "benchmark",
+            Self::Metric => "metric",
+        }
+    }
+}
+
+/// A number the paper reports, `value` as written, e.g. `3.57%`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ReportedScore {
+    pub benchmark: String,
+    #[serde(default)]
+    pub metric: String,
+    pub value: String,
+}
+
+/// Datasets, benchmarks and metrics a paper evaluates with, and where its code lives.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ArxivArtifactData {
+    #[serde(default)]
+    pub datasets: Vec<String>,
+    #[serde(default)]
+    pub benchmarks: Vec<String>,
+    #[serde(default)]
+    pub metrics: Vec<String>,
+    #[serde(default)]
+    pub scores: Vec<ReportedScore>,
+    #[serde(default)]
+    pub code_urls: Vec<String>,
+}
+
+/// `name` trimmed, whitespace collapsed and trailing punctuation dropped.
+fn clean_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(['.', ',', ';', ':'])
+        .to_string()
+}
+
+/// Cleaned `names`, one per [`rule_key`], first spelling kept.
+fn dedup_names(names: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut keys = Vec::new();
+    let mut deduped = Vec::new();
+    for name in names {
+        let name = clean_name(&name);
+        let key = rule_key(&name);
+        if key.is_empty() || keys.contains(&key) {
+            continue;
+        }
+        keys.push(key);
+        deduped.push(name);
+    }
+    deduped
+}
+
+/// URLs in free text such as an abstract or a `comment`.
+pub fn find_urls(text: &str) -> Vec<String> {
+    let re = Regex::new(r#"https?://[^\s<>"'(){}\[\]]+"#).unwrap();
+    re.find_iter(text).map(|m| m.as_str().to_string()).collect()
+}
+
+/// Trailing punctuation, `/` and `.git` dropped, scheme and host lowercased:
+/// `https://GitHub.com/a/b.git.` and `https://github.com/a/b/` are the same link.
+pub fn normalize_url(url: &str) -> String {
+    let mut url = url.trim().trim_end_matches(['.', ',', ';', ':', '/']);
+    if let Some(stripped) = url.strip_suffix(".git") {
+        url = stripped;
+    }
+    match url.find("://") {
+        Some(scheme_end) => {
+            let host_end = url[scheme_end + 3..]
+                .find('/')
+                .map_or(url.len(), |i| scheme_end + 3 + i);
+            format!("{}{}", url[..host_end].to_lowercase(), &url[host_end..])
+        }
+        None => url.to_string(),
+    }
+}
+
+impl ArxivArtifactData {
+    pub fn names(&self, kind: ArtifactKind) -> &[String] {
+        match kind {
+            ArtifactKind::Dataset => &self.datasets,
+            ArtifactKind::Benchmark => &self.benchmarks,
+            ArtifactKind::Metric => &self.metrics,
+        }
+    }
+
+    /// Names cleaned and deduplicated, benchmarks and metrics of the scores
+    /// listed too, and the URLs found in `texts` added to the model's.
+    pub fn normalize(self, texts: &[&str]) -> Self {
+        let scores: Vec<ReportedScore> = self
+            .scores
+            .into_iter()
+            .map(|s| ReportedScore {
+                benchmark: clean_name(&s.benchmark),
+                metric: clean_name(&s.metric),
+                value: s.value.trim().to_string(),
+            })
+            .filter(|s| !s.benchmark.is_empty() && !s.value.is_empty())
+            .collect();
+        let mut seen = Vec::new();
+        let scores = scores
+            .into_iter()
+            .filter(|s| {
+                let key = (rule_key(&s.benchmark), rule_key(&s.metric), s.value.clone());
+                let new = !seen.contains(&key);
+                seen.push(key);
+                new
+            })
+            .collect::<Vec<_>>();
+
+        let benchmarks = self
+            .benchmarks
+            .into_iter()
+            .chain(scores.iter().map(|s| s.benchmark.clone()));
+        let metrics = self.metrics.into_iter().chain(
+            scores
+                .iter()
+                .filter(|s| !s.metric.is_empty())
+                .map(|s| s.metric.clone()),
+        );
+        let mut code_urls: Vec<String> = Vec::new();
+        let found = texts.iter().flat_map(|text| find_urls(text));
+        for url in self.code_urls.into_iter().chain(found) {
+            let url = normalize_url(&url);
+            if url.contains("://") && !code_urls.contains(&url) {
+                code_urls.push(url);
+            }
+        }
+
+        Self {
+            datasets: dedup_names(self.datasets),
+            benchmarks: dedup_names(benchmarks),
+            metrics: dedup_names(metrics),
+            scores,
+            code_urls,
+        }
+    }
+}
+
+impl Display for ArxivArtifactData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sections = [
+            ("Datasets", &self.datasets),
+            ("Benchmarks", &self.benchmarks),
+            ("Metrics", &self.metrics),
+            ("Code", &self.code_urls),
+        ];
+        for (name, items) in sections {
+            if !items.is_empty() {
+                writeln!(f, "{}: {}", name, items.join(", "))?;
+            }
+        }
+        for s in self.scores.iter() {
+            match s.metric.as_str() {
+                "" => writeln!(f, "  {}: {}", s.benchmark, s.value)?,
+                metric => writeln!(f, "  {} {}: {}", s.benchmark, metric, s.value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the artifacts of a paper from its abstract and `comment`, where
+/// arXiv authors usually put their code links.
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct ArtifactExtracter {
+    #[builder(default = "Arc::new(default_artifact_prompt())", setter(into))]
+    prompt: Arc<PromptTemplate>,
+    llm: Box<dyn LLM>,
+    /// Model name behind `llm`, recorded with every extraction.
+    #[builder(default = "\"unknown\".to_string()", setter(into))]
+    model: String,
+}
+
+impl Clone for ArtifactExtracter {
+    fn clone(&self) -> Self {
+        Self {
+            prompt: self.prompt.clone(),
+            llm: self.llm.clone_box(),
+            model: self.model.clone(),
+        }
+    }
+}
+
+impl ArtifactExtracter {
+    fn parse(ctx: &Arxiv, data: &str) -> Result<ArxivArtifactData, ExtracterError> {
+        let parse_error = |err| ExtracterError::ParseError(err, ctx.id.clone(), data.to_string());
+        let json_data = parse_json_md(data).map_err(parse_error)?;
+
+        let artifacts: ArxivArtifactData =
+            serde_json::from_value(json_data).map_err(|err| parse_error(Box::new(err)))?;
+        let comment = ctx.comment.as_deref().unwrap_or_default();
+        Ok(artifacts.normalize(&[&ctx.summary, comment]))
+    }
+}
+
+impl Extracter for ArtifactExtracter {
+    type Input = Arxiv;
+    type Output = ArxivArtifactData;
+
+    async fn extract(&self, ctx: &Self::Input) -> Result<Self::Output, ExtracterError> {
+        self.extract_with_usage(ctx).await.0
+    }
+
+    async fn extract_with_usage(
+        &self,
+        ctx: &Self::Input,
+    ) -> (
+        Result<Self::Output, ExtracterError>,
+        Option<ExtractionUsage>,
+    ) {
+        let comment = ctx.comment.clone().unwrap_or_default();
+        let prompt_tokens = self.prompt.tokens
+            + count_tokens(&ctx.title)
+            + count_tokens(&ctx.summary)
+            + count_tokens(&comment);
+        let args = prompt_args![
+            "title" => ctx.title,
+            "summary" => ctx.summary,
+            "comment" => comment];
+        let res = match self.call(args).await {
+            Ok(res) => res,
+            Err(err) => return (Err(ExtracterError::ChainError(err, ctx.id.clone())), None),
+        };
+        let usage = usage_or_estimate(res.tokens, prompt_tokens, &res.generation);
+        let usage = ExtractionUsage::new(&self.model, usage);
+        (Self::parse(ctx, &res.generation), Some(usage))
+    }
+
+    fn estimate_tokens(&self, ctx: &Self::Input) -> u32 {
+        let comment = ctx.comment.as_ref().map_or(0, String::len);
+        // ~4 characters per token for English text
+        ((self.prompt.content.len() + ctx.title.len() + ctx.summary.len() + comment) / 4) as u32
+    }
+
+    fn provenance(&self) -> Option<Provenance> {
+        Some(Provenance::new("artifact", &self.prompt, &self.model))
+    }
+}
+
+impl AgentApp for ArtifactExtracter {
+    fn get_prompt(&self) -> Box<dyn FormatPrompter> {
+        let artifact_prompt = self.prompt.content.clone();
+        let prompt = message_formatter![fmt_template!(HumanMessagePromptTemplate::new(
+            template_fstring!(artifact_prompt, "title", "summary", "comment")
+        ))];
+        Box::new(prompt)
+    }
+
+    fn get_llm(&self) -> Box<dyn LLM> {
+        self.llm.clone_box()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_urls, normalize_url, ArxivArtifactData, ReportedScore};
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("https://GitHub.com/KaimingHe/resnet.git."),
+            "https://github.com/KaimingHe/resnet"
+        );
+        assert_eq!(
+            normalize_url("https://github.com/KaimingHe/resnet/"),
+            "https://github.com/KaimingHe/resnet"
+        );
+        assert_eq!(
+            find_urls("Code: https://github.com/a/b. Data (https://hf.co/d)"),
+            vec!["https://github.com/a/b.", "https://hf.co/d"]
+        );
+    }
+
+    #[test]
+    fn test_normalize_artifacts() {
+        let artifacts = ArxivArtifactData {
+            datasets: vec!["ImageNet".into(), " imagenet. ".into(), "".into()],
+            benchmarks: vec!["HumanEval".into()],
+            metrics: vec!["pass@1".into()],
+            scores: vec![
+                ReportedScore {
+                    benchmark: "MBPP".into(),
+                    metric: "Pass@1".into(),
+                    value: "71.2%".into(),
+                },
+                ReportedScore {
+                    benchmark: "MBPP".into(),
+                    metric: "".into(),
+                    value: " ".into(),
+                },
+            ],
+            code_urls: vec!["https://github.com/a/b/".into()],
+        }
+        .normalize(&["Code at https://github.com/a/b.git and https://x.org/c."]);
+
+        assert_eq!(artifacts.datasets, vec!["ImageNet"]);
+        assert_eq!(artifacts.benchmarks, vec!["HumanEval", "MBPP"]);
+        assert_eq!(artifacts.metrics, vec!["pass@1"]);
+        assert_eq!(artifacts.scores.len(), 1);
+        assert_eq!(
+            artifacts.code_urls,
+            vec!["https://github.com/a/b", "https://x.org/c"]
+        );
+    }
+}
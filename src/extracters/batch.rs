@@ -0,0 +1,265 @@
+use std::sync::Arc;
+
+use arxiv::Arxiv;
+use derive_builder::Builder;
+use langchain_rust::{
+    fmt_template,
+    language_models::{llm::LLM, TokenUsage},
+    message_formatter,
+    prompt::{FormatPrompter, HumanMessagePromptTemplate},
+    prompt_args, template_fstring,
+};
+use log::warn;
+use serde_json::Value;
+
+use crate::{
+    agent::AgentApp,
+    prompts::PromptTemplate,
+    tools::{
+        parser::parse_json_array_md,
+        usage::{count_tokens, usage_or_estimate, ExtractionUsage},
+    },
+};
+
+use super::{
+    topic::{ArxivTopicData, TopicExtracter},
+    ExtractResult, Extracter, ExtracterError, Provenance,
+};
+
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 8;
+/// Estimated prompt tokens of one batched call.
+pub const DEFAULT_TOKEN_BUDGET: u32 = 8000;
+
+/// The `topic_batch` prompt shipped with the crate.
+pub fn default_topic_batch_prompt() -> PromptTemplate {
+    PromptTemplate::new(
+        "topic_batch",
+        "1",
+        include_str!("../../prompts/topic_batch.md"),
+    )
+}
+
+/// `2502.00001v1` for `http://arxiv.org/abs/2502.00001v1`.
+fn short_id(id: &str) -> &str {
+    id.rsplit('/').next().unwrap_or(id)
+}
+
+fn paper_block(ctx: &Arxiv) -> String {
+    format!(
+        "Id: {}\nPaper: \"{}\"\nAbstract: {}\n",
+        short_id(&ctx.id),
+        ctx.title,
+        ctx.summary.trim()
+    )
+}
+
+/// `usage` shared among `n` papers, the remainder going to the first one.
+fn split_usage(usage: &TokenUsage, n: usize) -> Vec<TokenUsage> {
+    let n = n.max(1) as u32;
+    let share = |total: u32, first: bool| total / n + if first { total % n } else { 0 };
+    (0..n)
+        .map(|i| {
+            TokenUsage::new(
+                share(usage.prompt_tokens, i == 0),
+                share(usage.completion_tokens, i == 0),
+            )
+        })
+        .collect()
+}
+
+/// Extracts topics of several papers with one LLM call.
+///
+/// Papers are packed, in order, into batches of at most `max_batch_size`
+/// whose estimated prompt stays within `token_budget`. The answer is a JSON
+/// array keyed by paper id; papers missing from it, or whose entry cannot be
+/// parsed, fail with [`ExtracterError::Unanswered`]. The sniffer then
+/// schedules each of them as a batch of its own, extracted by `single`. When
+/// the call itself fails, every paper of the batch fails with the class of
+/// its error: transient ones are retried, batched again, in a later cycle,
+/// permanent ones are not retried. The prompt needs a `{papers}` placeholder.
+#[derive(Builder, Clone)]
+#[builder(pattern = "owned")]
+pub struct BatchTopicExtracter {
+    /// Extracts batches of one paper, its LLM runs the larger batches too.
+    single: TopicExtracter,
+    #[builder(default = "Arc::new(default_topic_batch_prompt())", setter(into))]
+    prompt: Arc<PromptTemplate>,
+    #[builder(default = "DEFAULT_MAX_BATCH_SIZE")]
+    max_batch_size: usize,
+    #[builder(default = "DEFAULT_TOKEN_BUDGET")]
+    token_budget: u32,
+}
+
+impl BatchTopicExtracter {
+    fn paper_tokens(ctx: &Arxiv) -> u32 {
+        // ~4 characters per token for English text
+        (paper_block(ctx).len() / 4) as u32
+    }
+
+    fn prompt_tokens(&self) -> u32 {
+        (self.prompt.content.len() / 4) as u32
+    }
+
+    /// Topics of the papers of `ctxs` found in `output`, by position in `ctxs`.
+    fn parse(ctxs: &[Arxiv], output: &str) -> Vec<Option<ArxivTopicData>> {
+        let mut found = vec![None; ctxs.len()];
+        let values = match parse_json_array_md(output) {
+            Ok(values) => values,
+            Err(err) => {
+                warn!("cannot parse batch output: {}", err);
+                return found;
+            }
+        };
+        for value in values {
+            let Some(id) = value.get("id").and_then(Value::as_str) else {
+                continue;
+            };
+            let id = short_id(id.trim());
+            let Some(idx) = ctxs.iter().position(|ctx| short_id(&ctx.id) == id) else {
+                continue;
+            };
+            if let Ok(data) = serde_json::from_value(value) {
+                found[idx].get_or_insert(data);
+            }
+        }
+        found
+    }
+}
+
+impl Extracter for BatchTopicExtracter {
+    type Input = Arxiv;
+    type Output = ArxivTopicData;
+
+    async fn extract(&self, ctx: &Self::Input) -> Result<Self::Output, ExtracterError> {
+        self.single.extract(ctx).await
+    }
+
+    async fn extract_with_usage(
+        &self,
+        ctx: &Self::Input,
+    ) -> (
+        Result<Self::Output, ExtracterError>,
+        Option<ExtractionUsage>,
+    ) {
+        self.single.extract_with_usage(ctx).await
+    }
+
+    fn estimate_tokens(&self, ctx: &Self::Input) -> u32 {
+        self.single.estimate_tokens(ctx)
+    }
+
+    fn provenance(&self) -> Option<Provenance> {
+        let model = self
+            .single
+            .provenance()
+            .map(|p| p.model)
+            .unwrap_or_default();
+        Some(Provenance::new("topic_batch", &self.prompt, &model))
+    }
+
+    fn plan_batches(&self, ctxs: &[Self::Input]) -> Vec<usize> {
+        let mut sizes = Vec::new();
+        let (mut size, mut tokens) = (0, self.prompt_tokens());
+        for ctx in ctxs {
+            let paper = Self::paper_tokens(ctx);
+            if size > 0 && (size >= self.max_batch_size || tokens + paper > self.token_budget) {
+                sizes.push(size);
+                (size, tokens) = (0, self.prompt_tokens());
+            }
+            size += 1;
+            tokens += paper;
+        }
+        if size > 0 {
+            sizes.push(size);
+        }
+        sizes
+    }
+
+    fn estimate_batch_tokens(&self, ctxs: &[Self::Input]) -> u32 {
+        match ctxs {
+            [ctx] => self.estimate_tokens(ctx),
+            _ => self.prompt_tokens() + ctxs.iter().map(Self::paper_tokens).sum::<u32>(),
+        }
+    }
+
+    /// One request for the whole batch.
+    fn estimate_requests(&self, ctxs: &[Self::Input]) -> u32 {
+        ctxs.len().min(1) as u32
+    }
+
+    async fn extract_batch(&self, ctxs: &[Self::Input]) -> Vec<ExtractResult<Self::Output>> {
+        if ctxs.len() <= 1 {
+            let mut results = Vec::with_capacity(ctxs.len());
+            for ctx in ctxs {
+                results.push(self.extract_with_usage(ctx).await);
+            }
+            return results;
+        }
+
+        let papers = ctxs.iter().map(paper_block).collect::<Vec<_>>().join("\n");
+        let prompt_tokens = self.prompt.tokens + count_tokens(&papers);
+        let args = prompt_args!["papers" => papers];
+        let (found, usage, failure) = match self.call(args).await {
+            Ok(res) => (
+                Self::parse(ctxs, &res.generation),
+                Some(usage_or_estimate(
+                    res.tokens,
+                    prompt_tokens,
+                    &res.generation,
+                )),
+                None,
+            ),
+            Err(err) => {
+                warn!("batch of {} papers failed: {}", ctxs.len(), err);
+                let message = err.to_string();
+                let class = ExtracterError::ChainError(err, String::new()).class();
+                (vec![None; ctxs.len()], None, Some((class, message)))
+            }
+        };
+
+        let model = self
+            .single
+            .provenance()
+            .map(|p| p.model)
+            .unwrap_or_default();
+        let answered = found.iter().filter(|f| f.is_some()).count();
+        let mut shares = match &usage {
+            Some(usage) if answered > 0 => split_usage(usage, answered),
+            _ => Vec::new(),
+        }
+        .into_iter()
+        .map(|share| ExtractionUsage::new(&model, share));
+        let mut results: Vec<ExtractResult<Self::Output>> = ctxs
+            .iter()
+            .zip(found)
+            .map(|(ctx, data)| match (data, &failure) {
+                (Some(data), _) => (Ok(data), shares.next()),
+                // every paper fails as the call did
+                (None, Some((class, message))) => (
+                    Err(ExtracterError::of_class(*class, message.clone(), &ctx.id)),
+                    None,
+                ),
+                (None, None) => (Err(ExtracterError::Unanswered(ctx.id.clone())), None),
+            })
+            .collect();
+        // nothing usable came back, the batch call is still paid for
+        if let (0, Some(usage)) = (answered, usage) {
+            results[0].1 = Some(ExtractionUsage::new(&model, usage));
+        }
+        results
+    }
+}
+
+impl AgentApp for BatchTopicExtracter {
+    fn get_prompt(&self) -> Box<dyn FormatPrompter> {
+        let batch_prompt = self.prompt.content.clone();
+        let prompt = message_formatter![fmt_template!(HumanMessagePromptTemplate::new(
+            template_fstring!(batch_prompt, "papers")
+        ))];
+        Box::new(prompt)
+    }
+
+    fn get_llm(&self) -> Box<dyn LLM> {
+        self.single.get_llm()
+    }
+}
@@ -0,0 +1,210 @@
+use std::{cmp::Reverse, collections::HashMap, sync::Arc};
+
+use arxiv::Arxiv;
+use derive_builder::Builder;
+use futures::future::join_all;
+use log::warn;
+
+use crate::normalizers::keyword::{rule_key, KeywordNormalizer};
+use crate::tools::usage::ExtractionUsage;
+
+use super::{
+    sum_usage,
+    topic::{ArxivTopicData, TopicConfidence},
+    Extracter, ExtracterError, Provenance,
+};
+
+/// Runs several extracters on the same paper and merges their outputs by vote.
+///
+/// Keywords are compared by [`rule_key`], after the normalizer when one is
+/// set, and each keeps the spelling most members used. Its confidence is the
+/// share of all members that produced it, a failed member counting as a vote
+/// against every keyword.
+/// Keywords below `min_agreement` are dropped, most agreed-on keywords come
+/// first.
+#[derive(Builder, Clone)]
+#[builder(pattern = "owned")]
+pub struct EnsembleExtracter<E> {
+    members: Vec<E>,
+    #[builder(default, setter(strip_option))]
+    normalizer: Option<Arc<KeywordNormalizer>>,
+    #[builder(default)]
+    min_agreement: f64,
+}
+
+/// Votes for one keyword: its key, how many members produced it and the spellings they used.
+struct Tally {
+    key: String,
+    votes: usize,
+    forms: Vec<String>,
+}
+
+impl Tally {
+    /// Most used spelling, the earliest one on ties.
+    fn form(&self) -> &str {
+        let mut best = (0, self.forms[0].as_str());
+        for form in self.forms.iter() {
+            let count = self.forms.iter().filter(|f| *f == form).count();
+            if count > best.0 {
+                best = (count, form);
+            }
+        }
+        best.1
+    }
+}
+
+impl<E> EnsembleExtracter<E> {
+    /// Keywords of each member mapped to their canonical spelling.
+    async fn canonical(&self, keywords: &[String]) -> Vec<String> {
+        if let Some(normalizer) = &self.normalizer {
+            match normalizer.canonicalize(keywords).await {
+                Ok(keywords) => return keywords,
+                Err(err) => warn!(
+                    "ensemble normalization failed, voting on raw keywords: {}",
+                    err
+                ),
+            }
+        }
+        keywords.iter().map(|k| k.trim().to_string()).collect()
+    }
+
+    async fn vote(&self, ballots: &[&[String]]) -> (Vec<String>, HashMap<String, f64>) {
+        let mut tallies: Vec<Tally> = Vec::new();
+        for ballot in ballots {
+            let mut voted = Vec::new();
+            for form in self.canonical(ballot).await {
+                let key = rule_key(&form);
+                if key.is_empty() || voted.contains(&key) {
+                    continue;
+                }
+                match tallies.iter_mut().find(|t| t.key == key) {
+                    Some(tally) => {
+                        tally.votes += 1;
+                        tally.forms.push(form);
+                    }
+                    None => tallies.push(Tally {
+                        key: key.clone(),
+                        votes: 1,
+                        forms: vec![form],
+                    }),
+                }
+                voted.push(key);
+            }
+        }
+        // stable, keeps first-seen order among equal votes
+        tallies.sort_by_key(|t| Reverse(t.votes));
+
+        let mut keywords = Vec::new();
+        let mut confidence = HashMap::new();
+        for tally in tallies {
+            let agreement = tally.votes as f64 / self.members.len() as f64;
+            if agreement < self.min_agreement {
+                continue;
+            }
+            let form = tally.form().to_string();
+            confidence.insert(form.clone(), agreement);
+            keywords.push(form);
+        }
+        (keywords, confidence)
+    }
+
+    async fn merge(&self, outputs: &[ArxivTopicData]) -> ArxivTopicData {
+        let facet = |f: fn(&ArxivTopicData) -> &[String]| outputs.iter().map(f).collect::<Vec<_>>();
+        let (solved_problem, solved_confidence) = self.vote(&facet(|o| &o.solved_problem)).await;
+        let (research_field, field_confidence) = self.vote(&facet(|o| &o.research_field)).await;
+        let (techniques_used, technique_confidence) =
+            self.vote(&facet(|o| &o.techniques_used)).await;
+
+        let titles: Vec<&str> = outputs.iter().map(|o| o.title.as_str()).collect();
+        let title = titles
+            .iter()
+            .max_by_key(|t| titles.iter().filter(|o| o == t).count())
+            .map(|t| t.to_string())
+            .unwrap_or_default();
+
+        ArxivTopicData {
+            title,
+            solved_problem,
+            research_field,
+            techniques_used,
+            confidence: Some(TopicConfidence {
+                solved_problem: solved_confidence,
+                research_field: field_confidence,
+                techniques_used: technique_confidence,
+            }),
+            summary: outputs.iter().find_map(|o| o.summary.clone()),
+            artifacts: outputs.iter().find_map(|o| o.artifacts.clone()),
+            language: outputs.iter().find_map(|o| o.language.clone()),
+            relevance: outputs.iter().find_map(|o| o.relevance),
+            failed_stages: Vec::new(),
+        }
+    }
+}
+
+impl<E> Extracter for EnsembleExtracter<E>
+where
+    E: Extracter<Input = Arxiv, Output = ArxivTopicData> + Sync,
+{
+    type Input = Arxiv;
+    type Output = ArxivTopicData;
+
+    async fn extract(&self, ctx: &Self::Input) -> Result<Self::Output, ExtracterError> {
+        self.extract_with_usage(ctx).await.0
+    }
+
+    async fn extract_with_usage(
+        &self,
+        ctx: &Self::Input,
+    ) -> (
+        Result<Self::Output, ExtracterError>,
+        Option<ExtractionUsage>,
+    ) {
+        let results = join_all(self.members.iter().map(|m| m.extract_with_usage(ctx))).await;
+
+        let mut tokens: Option<ExtractionUsage> = None;
+        let mut outputs = Vec::new();
+        let mut errors = Vec::new();
+        for (res, usage) in results {
+            tokens = sum_usage(tokens, usage);
+            match res {
+                Ok(output) => outputs.push(output),
+                Err(err) => errors.push(err),
+            }
+        }
+
+        if outputs.is_empty() {
+            let err = errors.into_iter().next().unwrap_or_else(|| {
+                ExtracterError::Other("ensemble has no members".into(), ctx.id.clone())
+            });
+            return (Err(err), tokens);
+        }
+        for err in errors.iter() {
+            warn!("ensemble member failed, voting without it: {}", err);
+        }
+        (Ok(self.merge(&outputs).await), tokens)
+    }
+
+    fn estimate_tokens(&self, ctx: &Self::Input) -> u32 {
+        self.members.iter().map(|m| m.estimate_tokens(ctx)).sum()
+    }
+
+    fn estimate_requests(&self, ctxs: &[Self::Input]) -> u32 {
+        self.members.iter().map(|m| m.estimate_requests(ctxs)).sum()
+    }
+
+    /// Provenance of every member, fields joined with `+`.
+    fn provenance(&self) -> Option<Provenance> {
+        let members: Vec<Provenance> = self.members.iter().filter_map(|m| m.provenance()).collect();
+        if members.is_empty() {
+            return None;
+        }
+        let join = |f: fn(&Provenance) -> &str| members.iter().map(f).collect::<Vec<_>>().join("+");
+        Some(Provenance {
+            extracter: format!("ensemble({})", join(|p| &p.extracter)),
+            prompt: join(|p| &p.prompt),
+            prompt_version: join(|p| &p.prompt_version),
+            prompt_hash: join(|p| &p.prompt_hash),
+            model: join(|p| &p.model),
+        })
+    }
+}
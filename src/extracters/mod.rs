@@ -1,24 +1,222 @@
-use std::{
-    error::Error,
-    fmt::{Debug, Display},
-};
+use std::{error::Error, fmt::Display, time::Duration};
 
-use langchain_rust::chain::ChainError;
+use langchain_rust::{chain::ChainError, language_models::LLMError};
 
+use crate::{models::PaperExtraction, prompts::PromptTemplate, tools::usage::ExtractionUsage};
+
+pub mod artifact;
+pub mod batch;
+pub mod ensemble;
+pub mod pipeline;
+pub mod relevance;
+pub mod summary;
 pub mod topic;
 
+/// Max characters of model output kept in error messages.
+const OUTPUT_PREVIEW_LEN: usize = 200;
+
+/// Errors of an extraction, the trailing `String`s are the paper id and,
+/// for `ParseError`, the raw model output. `Irrelevant` carries the
+/// relevance score of a paper dropped as off-topic.
 #[derive(Debug)]
 pub enum ExtracterError {
     ChainError(ChainError, String),
-    ParseError(Box<dyn Error>, String),
-    Other(Box<dyn Error>, String),
+    ParseError(Box<dyn Error + Send + Sync>, String, String),
+    Other(Box<dyn Error + Send + Sync>, String),
+    Timeout(Duration, String),
+    Irrelevant(f64, String),
+    /// Left out of the answer to a batched call, to be extracted on its own.
+    Unanswered(String),
+}
+
+/// Result of one extraction with the token usage of each model it called.
+pub type ExtractResult<O> = (Result<O, ExtracterError>, Option<ExtractionUsage>);
+
+/// Usage of two calls made for the same paper.
+pub(crate) fn sum_usage(
+    a: Option<ExtractionUsage>,
+    b: Option<ExtractionUsage>,
+) -> Option<ExtractionUsage> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.sum(&b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Which extracter, prompt and model produced an output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    pub extracter: String,
+    pub prompt: String,
+    pub prompt_version: String,
+    pub prompt_hash: String,
+    pub model: String,
+}
+
+impl Provenance {
+    pub fn new(extracter: &str, prompt: &PromptTemplate, model: &str) -> Self {
+        Self {
+            extracter: extracter.to_string(),
+            prompt: prompt.name.clone(),
+            prompt_version: prompt.version.clone(),
+            prompt_hash: prompt.hash.clone(),
+            model: model.to_string(),
+        }
+    }
+
+    /// Provenance of `self` followed by `next` on the same paper, extracters
+    /// joined with `+` and models too when they differ.
+    pub fn chain(self, next: &Provenance) -> Self {
+        let model = if self.model == next.model {
+            self.model.clone()
+        } else {
+            format!("{}+{}", self.model, next.model)
+        };
+        Self {
+            extracter: format!("{}+{}", self.extracter, next.extracter),
+            model,
+            ..self
+        }
+    }
+
+    /// Models of the chained or ensembled extracters, each once.
+    pub fn models(&self) -> impl Iterator<Item = &str> {
+        let mut seen = Vec::new();
+        self.model.split('+').filter(move |model| {
+            let new = !seen.contains(model);
+            seen.push(*model);
+            new
+        })
+    }
+
+    /// Extraction record of this provenance, paper and usage left unset.
+    pub fn to_extraction(&self, created_at: &str) -> PaperExtraction {
+        PaperExtraction {
+            id: 0,
+            paper_id: 0,
+            extracter: self.extracter.clone(),
+            prompt_name: self.prompt.clone(),
+            prompt_version: self.prompt_version.clone(),
+            prompt_hash: self.prompt_hash.clone(),
+            model: self.model.clone(),
+            created_at: created_at.to_string(),
+            run_id: None,
+            prompt_tokens: None,
+            completion_tokens: None,
+            cost: None,
+            output: None,
+        }
+    }
+}
+
+/// Whether retrying the same extraction may succeed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Network trouble, throttling, timeouts: worth retrying later.
+    Transient,
+    /// Bad prompt, unusable model output: retrying won't help.
+    Permanent,
+}
+
+impl ExtracterError {
+    /// Failure of paper `id` with `message`, of `class` so that it is
+    /// retried, or not, like the failure it stands for.
+    pub(crate) fn of_class(class: ErrorClass, message: String, id: &str) -> Self {
+        match class {
+            ErrorClass::Transient => {
+                let err = LLMError::IoError(std::io::Error::other(message));
+                Self::ChainError(ChainError::LLMError(err), id.to_string())
+            }
+            ErrorClass::Permanent => Self::Other(message.into(), id.to_string()),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ChainError(..) => "chain",
+            Self::ParseError(..) => "parse",
+            Self::Other(..) => "other",
+            Self::Timeout(..) => "timeout",
+            Self::Irrelevant(..) => "irrelevant",
+            Self::Unanswered(..) => "unanswered",
+        }
+    }
+
+    pub fn paper_id(&self) -> &str {
+        match self {
+            Self::ChainError(_, id)
+            | Self::ParseError(_, id, _)
+            | Self::Other(_, id)
+            | Self::Timeout(_, id)
+            | Self::Irrelevant(_, id)
+            | Self::Unanswered(id) => id,
+        }
+    }
+
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            Self::ChainError(ChainError::LLMError(err), _) => match err {
+                LLMError::OpenAIError(_)
+                | LLMError::AnthropicError(_)
+                | LLMError::OllamaError(_)
+                | LLMError::RequestError(_)
+                | LLMError::IoError(_)
+                | LLMError::Timeout(_) => ErrorClass::Transient,
+                _ => ErrorClass::Permanent,
+            },
+            Self::Timeout(..) | Self::Unanswered(..) => ErrorClass::Transient,
+            Self::ChainError(..)
+            | Self::ParseError(..)
+            | Self::Other(..)
+            | Self::Irrelevant(..) => ErrorClass::Permanent,
+        }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        self.class() == ErrorClass::Transient
+    }
+}
+
+fn preview(output: &str) -> String {
+    let output = output.trim();
+    match output.char_indices().nth(OUTPUT_PREVIEW_LEN) {
+        Some((end, _)) => format!("{}...", &output[..end]),
+        None => output.to_string(),
+    }
 }
+
 impl Display for ExtracterError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "")
+        match self {
+            Self::ChainError(err, id) => write!(f, "LLM call failed for paper {}: {}", id, err),
+            Self::ParseError(err, id, output) => write!(
+                f,
+                "cannot parse model output for paper {}: {}, output: {:?}",
+                id,
+                err,
+                preview(output)
+            ),
+            Self::Other(err, id) => write!(f, "extraction failed for paper {}: {}", id, err),
+            Self::Timeout(limit, id) => {
+                write!(f, "extraction of paper {} timed out after {:?}", id, limit)
+            }
+            Self::Irrelevant(score, id) => {
+                write!(f, "paper {} is off-topic, relevance {:.2}", id, score)
+            }
+            Self::Unanswered(id) => write!(f, "batched call left out paper {}", id),
+        }
+    }
+}
+
+impl Error for ExtracterError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::ChainError(err, _) => Some(err),
+            Self::ParseError(err, ..) | Self::Other(err, _) => Some(err.as_ref()),
+            Self::Timeout(..) | Self::Irrelevant(..) | Self::Unanswered(..) => None,
+        }
     }
 }
-impl Error for ExtracterError {}
 
 pub trait Extracter {
     type Input;
@@ -28,4 +226,119 @@ pub trait Extracter {
         &self,
         ctx: &Self::Input,
     ) -> impl std::future::Future<Output = Result<Self::Output, ExtracterError>> + Send;
+
+    /// Like `extract`, also returning the token usage of each model called.
+    fn extract_with_usage(
+        &self,
+        ctx: &Self::Input,
+    ) -> impl std::future::Future<Output = ExtractResult<Self::Output>> + Send
+    where
+        Self: Sync,
+        Self::Input: Sync,
+    {
+        async move { (self.extract(ctx).await, None) }
+    }
+
+    /// Rough number of tokens one `extract` call spends, used for rate limiting.
+    fn estimate_tokens(&self, _ctx: &Self::Input) -> u32 {
+        0
+    }
+
+    /// Prompt and model of this extracter, stored alongside its outputs.
+    fn provenance(&self) -> Option<Provenance> {
+        None
+    }
+
+    /// Sizes of the consecutive groups of `ctxs` to pass to `extract_batch`,
+    /// one paper per group by default.
+    fn plan_batches(&self, ctxs: &[Self::Input]) -> Vec<usize> {
+        vec![1; ctxs.len()]
+    }
+
+    /// Rough number of tokens one `extract_batch` call over `ctxs` spends.
+    fn estimate_batch_tokens(&self, ctxs: &[Self::Input]) -> u32 {
+        ctxs.iter().map(|ctx| self.estimate_tokens(ctx)).sum()
+    }
+
+    /// Number of model requests one `extract_batch` call over `ctxs` sends,
+    /// one per paper by default.
+    fn estimate_requests(&self, ctxs: &[Self::Input]) -> u32 {
+        ctxs.len() as u32
+    }
+
+    /// Extract every paper of `ctxs`, results in the same order.
+    fn extract_batch(
+        &self,
+        ctxs: &[Self::Input],
+    ) -> impl std::future::Future<Output = Vec<ExtractResult<Self::Output>>> + Send
+    where
+        Self: Sync,
+        Self::Input: Sync,
+        Self::Output: Send,
+    {
+        async move {
+            let mut results = Vec::with_capacity(ctxs.len());
+            for ctx in ctxs {
+                results.push(self.extract_with_usage(ctx).await);
+            }
+            results
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{error::Error, time::Duration};
+
+    use langchain_rust::{chain::ChainError, language_models::LLMError};
+
+    use super::{ErrorClass, ExtracterError};
+
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+    #[test]
+    fn test_extracter_error_display() {
+        assert_send_sync::<ExtracterError>();
+
+        let output = "x".repeat(1000);
+        let err = ExtracterError::ParseError(
+            "No valid JSON block found".into(),
+            "2502.00001".to_string(),
+            output,
+        );
+        let msg = err.to_string();
+        assert!(msg.contains("2502.00001"), "{}", msg);
+        assert!(msg.contains("No valid JSON block found"), "{}", msg);
+        assert!(msg.len() < 400, "{}", msg);
+        assert_eq!(
+            err.source().unwrap().to_string(),
+            "No valid JSON block found"
+        );
+        assert_eq!(err.class(), ErrorClass::Permanent);
+    }
+
+    #[test]
+    fn test_extracter_error_class() {
+        let err = ExtracterError::ChainError(
+            ChainError::LLMError(LLMError::OtherError("boom".to_string())),
+            "2502.00001".to_string(),
+        );
+        assert_eq!(err.class(), ErrorClass::Permanent);
+        assert!(err.source().is_some());
+
+        let err = ExtracterError::ChainError(
+            ChainError::LLMError(LLMError::IoError(
+                std::io::ErrorKind::ConnectionReset.into(),
+            )),
+            "2502.00001".to_string(),
+        );
+        assert!(err.is_transient());
+
+        let err = ExtracterError::Timeout(Duration::from_secs(1), "2502.00001".to_string());
+        assert!(err.is_transient());
+        assert_eq!(
+            err.to_string(),
+            "extraction of paper 2502.00001 timed out after 1s"
+        );
+    }
 }
@@ -0,0 +1,388 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use arxiv::Arxiv;
+use async_trait::async_trait;
+use derive_builder::Builder;
+use futures::future::join_all;
+use log::warn;
+
+use crate::tools::{language::detect_language, usage::ExtractionUsage};
+
+use super::{
+    artifact::ArtifactExtracter, sum_usage, summary::SummaryExtracter, topic::ArxivTopicData,
+    ExtractResult, Extracter, ExtracterError, Provenance,
+};
+
+/// One step of a [`PipelineExtracter`]. Stages share the composite output of
+/// a paper: each fills its own part of it and can read what the earlier
+/// stages filled.
+#[async_trait]
+pub trait Stage: Send + Sync {
+    /// Name used to enable or disable the stage, and in `failed_stages`.
+    fn name(&self) -> &str;
+
+    async fn run(&self, ctx: &Arxiv, data: &mut ArxivTopicData) -> ExtractResult<()>;
+
+    /// Run on every paper of `ctxs`, results in the same order. Papers run
+    /// concurrently by default.
+    async fn run_batch(
+        &self,
+        ctxs: &[Arxiv],
+        data: &mut [ArxivTopicData],
+    ) -> Vec<ExtractResult<()>> {
+        join_all(
+            ctxs.iter()
+                .zip(data.iter_mut())
+                .map(|(ctx, data)| self.run(ctx, data)),
+        )
+        .await
+    }
+
+    /// Whether a failure fails the paper, other failures are only recorded
+    /// in `failed_stages`.
+    fn required(&self) -> bool {
+        false
+    }
+
+    fn estimate_tokens(&self, _ctx: &Arxiv) -> u32 {
+        0
+    }
+
+    fn estimate_batch_tokens(&self, ctxs: &[Arxiv]) -> u32 {
+        ctxs.iter().map(|ctx| self.estimate_tokens(ctx)).sum()
+    }
+
+    /// Number of model requests `run_batch` sends, one per paper by default.
+    fn estimate_requests(&self, ctxs: &[Arxiv]) -> u32 {
+        ctxs.len() as u32
+    }
+
+    fn provenance(&self) -> Option<Provenance> {
+        None
+    }
+
+    /// Sizes of the consecutive groups of `ctxs` to pass to `run_batch`,
+    /// `None` when the stage has no batching of its own.
+    fn plan_batches(&self, _ctxs: &[Arxiv]) -> Option<Vec<usize>> {
+        None
+    }
+}
+
+/// A stage of a pipeline and whether it runs.
+#[derive(Clone)]
+pub struct PipelineStage {
+    stage: Arc<dyn Stage>,
+    enabled: bool,
+}
+
+impl<S: Stage + 'static> From<S> for PipelineStage {
+    fn from(stage: S) -> Self {
+        Self {
+            stage: Arc::new(stage),
+            enabled: true,
+        }
+    }
+}
+
+/// Runs its stages in order on every paper, e.g. language detection, a
+/// relevance filter, then topics, artifacts and a summary.
+///
+/// Papers are batched as the stages plan it, split wherever any stage splits,
+/// and run one at a time when no stage batches.
+/// A failing required stage fails the paper and skips the stages after it, a
+/// failing optional stage is logged and named in `failed_stages`. Usage is
+/// summed over the stages, the output is stored whole with the extraction.
+#[derive(Builder, Clone)]
+#[builder(pattern = "owned")]
+pub struct PipelineExtracter {
+    #[builder(default, setter(each(name = "stage", into)))]
+    stages: Vec<PipelineStage>,
+}
+
+impl PipelineExtracter {
+    /// Enable or disable the stages called `name`, returns whether there were any.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let mut found = false;
+        for stage in self.stages.iter_mut().filter(|s| s.stage.name() == name) {
+            stage.enabled = enabled;
+            found = true;
+        }
+        found
+    }
+
+    /// Names of the stages, in order, with whether they run.
+    pub fn stages(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.stages.iter().map(|s| (s.stage.name(), s.enabled))
+    }
+
+    fn enabled(&self) -> impl Iterator<Item = &dyn Stage> {
+        self.stages
+            .iter()
+            .filter(|s| s.enabled)
+            .map(|s| s.stage.as_ref())
+    }
+
+    async fn run(&self, ctxs: &[Arxiv]) -> Vec<ExtractResult<ArxivTopicData>> {
+        let n = ctxs.len();
+        let mut data: Vec<ArxivTopicData> = ctxs
+            .iter()
+            .map(|ctx| ArxivTopicData {
+                title: ctx.title.clone(),
+                ..Default::default()
+            })
+            .collect();
+        let mut usage: Vec<Option<ExtractionUsage>> = vec![None; n];
+        let mut errors: Vec<Option<ExtracterError>> = (0..n).map(|_| None).collect();
+
+        for stage in self.enabled() {
+            let alive: Vec<usize> = (0..n).filter(|&i| errors[i].is_none()).collect();
+            if alive.is_empty() {
+                break;
+            }
+            let remaining: Vec<Arxiv>;
+            let stage_ctxs = if alive.len() == n {
+                ctxs
+            } else {
+                remaining = alive.iter().map(|&i| ctxs[i].clone()).collect();
+                &remaining
+            };
+            let mut stage_data: Vec<ArxivTopicData> = alive
+                .iter()
+                .map(|&i| std::mem::take(&mut data[i]))
+                .collect();
+            let results = stage.run_batch(stage_ctxs, &mut stage_data).await;
+
+            for ((i, output), (res, stage_usage)) in alive.into_iter().zip(stage_data).zip(results)
+            {
+                data[i] = output;
+                usage[i] = sum_usage(usage[i].take(), stage_usage);
+                let Err(err) = res else {
+                    continue;
+                };
+                if stage.required() {
+                    errors[i] = Some(err);
+                } else {
+                    warn!("stage {} failed, keeping the others: {}", stage.name(), err);
+                    data[i].failed_stages.push(stage.name().to_string());
+                }
+            }
+        }
+
+        data.into_iter()
+            .zip(usage)
+            .zip(errors)
+            .map(|((data, usage), err)| match err {
+                Some(err) => (Err(err), usage),
+                None => (Ok(data), usage),
+            })
+            .collect()
+    }
+}
+
+impl Extracter for PipelineExtracter {
+    type Input = Arxiv;
+    type Output = ArxivTopicData;
+
+    async fn extract(&self, ctx: &Self::Input) -> Result<Self::Output, ExtracterError> {
+        self.extract_with_usage(ctx).await.0
+    }
+
+    async fn extract_with_usage(&self, ctx: &Self::Input) -> ExtractResult<Self::Output> {
+        self.run(std::slice::from_ref(ctx))
+            .await
+            .pop()
+            .unwrap_or_else(|| {
+                let err =
+                    ExtracterError::Other("pipeline produced no output".into(), ctx.id.clone());
+                (Err(err), None)
+            })
+    }
+
+    fn estimate_tokens(&self, ctx: &Self::Input) -> u32 {
+        self.enabled().map(|s| s.estimate_tokens(ctx)).sum()
+    }
+
+    /// Provenance of the enabled stages that have one, chained in order.
+    fn provenance(&self) -> Option<Provenance> {
+        self.enabled()
+            .filter_map(|s| s.provenance())
+            .reduce(|chained, next| chained.chain(&next))
+    }
+
+    fn plan_batches(&self, ctxs: &[Self::Input]) -> Vec<usize> {
+        let plans: Vec<Vec<usize>> = self
+            .enabled()
+            .filter_map(|stage| stage.plan_batches(ctxs))
+            .collect();
+        if plans.is_empty() {
+            return vec![1; ctxs.len()];
+        }
+        let mut ends = BTreeSet::new();
+        for plan in plans {
+            ends.extend(plan.into_iter().scan(0, |end, size| {
+                *end += size;
+                Some(*end)
+            }));
+        }
+        ends.insert(ctxs.len());
+        let mut start = 0;
+        ends.into_iter()
+            .filter(|&end| end > 0)
+            .map(|end| {
+                let size = end - start;
+                start = end;
+                size
+            })
+            .collect()
+    }
+
+    fn estimate_batch_tokens(&self, ctxs: &[Self::Input]) -> u32 {
+        self.enabled().map(|s| s.estimate_batch_tokens(ctxs)).sum()
+    }
+
+    /// Requests of every enabled stage, papers dropped midway included.
+    fn estimate_requests(&self, ctxs: &[Self::Input]) -> u32 {
+        self.enabled().map(|s| s.estimate_requests(ctxs)).sum()
+    }
+
+    async fn extract_batch(&self, ctxs: &[Self::Input]) -> Vec<ExtractResult<Self::Output>> {
+        self.run(ctxs).await
+    }
+}
+
+/// Sets `language` from the title and abstract, without calling a model.
+#[derive(Debug, Clone, Default)]
+pub struct LanguageStage;
+
+#[async_trait]
+impl Stage for LanguageStage {
+    fn name(&self) -> &str {
+        "language"
+    }
+
+    async fn run(&self, ctx: &Arxiv, data: &mut ArxivTopicData) -> ExtractResult<()> {
+        let text = format!("{}\n{}", ctx.title, ctx.summary);
+        data.language = Some(detect_language(&text).to_string());
+        (Ok(()), None)
+    }
+
+    fn estimate_requests(&self, _ctxs: &[Arxiv]) -> u32 {
+        0
+    }
+}
+
+/// Runs a topic extracter as a required stage, batched as the extracter
+/// plans it. Outputs of earlier stages are kept.
+#[derive(Clone)]
+pub struct TopicStage<E> {
+    topics: E,
+}
+
+impl<E> TopicStage<E> {
+    pub fn new(topics: E) -> Self {
+        Self { topics }
+    }
+
+    fn merge(data: &mut ArxivTopicData, topics: ArxivTopicData) {
+        let earlier = std::mem::take(data);
+        *data = ArxivTopicData {
+            summary: topics.summary.or(earlier.summary),
+            artifacts: topics.artifacts.or(earlier.artifacts),
+            language: earlier.language,
+            relevance: earlier.relevance,
+            failed_stages: earlier.failed_stages,
+            ..topics
+        };
+    }
+}
+
+#[async_trait]
+impl<E> Stage for TopicStage<E>
+where
+    E: Extracter<Input = Arxiv, Output = ArxivTopicData> + Send + Sync,
+{
+    fn name(&self) -> &str {
+        "topic"
+    }
+
+    async fn run(&self, ctx: &Arxiv, data: &mut ArxivTopicData) -> ExtractResult<()> {
+        let (res, usage) = self.topics.extract_with_usage(ctx).await;
+        (res.map(|topics| Self::merge(data, topics)), usage)
+    }
+
+    async fn run_batch(
+        &self,
+        ctxs: &[Arxiv],
+        data: &mut [ArxivTopicData],
+    ) -> Vec<ExtractResult<()>> {
+        let results = self.topics.extract_batch(ctxs).await;
+        data.iter_mut()
+            .zip(results)
+            .map(|(data, (res, usage))| (res.map(|topics| Self::merge(data, topics)), usage))
+            .collect()
+    }
+
+    fn required(&self) -> bool {
+        true
+    }
+
+    fn estimate_tokens(&self, ctx: &Arxiv) -> u32 {
+        self.topics.estimate_tokens(ctx)
+    }
+
+    fn estimate_batch_tokens(&self, ctxs: &[Arxiv]) -> u32 {
+        self.topics.estimate_batch_tokens(ctxs)
+    }
+
+    fn estimate_requests(&self, ctxs: &[Arxiv]) -> u32 {
+        self.topics.estimate_requests(ctxs)
+    }
+
+    fn provenance(&self) -> Option<Provenance> {
+        self.topics.provenance()
+    }
+
+    fn plan_batches(&self, ctxs: &[Arxiv]) -> Option<Vec<usize>> {
+        Some(self.topics.plan_batches(ctxs))
+    }
+}
+
+#[async_trait]
+impl Stage for SummaryExtracter {
+    fn name(&self) -> &str {
+        "summary"
+    }
+
+    async fn run(&self, ctx: &Arxiv, data: &mut ArxivTopicData) -> ExtractResult<()> {
+        let (res, usage) = Extracter::extract_with_usage(self, ctx).await;
+        (res.map(|summary| data.summary = Some(summary)), usage)
+    }
+
+    fn estimate_tokens(&self, ctx: &Arxiv) -> u32 {
+        Extracter::estimate_tokens(self, ctx)
+    }
+
+    fn provenance(&self) -> Option<Provenance> {
+        Extracter::provenance(self)
+    }
+}
+
+#[async_trait]
+impl Stage for ArtifactExtracter {
+    fn name(&self) -> &str {
+        "artifact"
+    }
+
+    async fn run(&self, ctx: &Arxiv, data: &mut ArxivTopicData) -> ExtractResult<()> {
+        let (res, usage) = Extracter::extract_with_usage(self, ctx).await;
+        (res.map(|artifacts| data.artifacts = Some(artifacts)), usage)
+    }
+
+    fn estimate_tokens(&self, ctx: &Arxiv) -> u32 {
+        Extracter::estimate_tokens(self, ctx)
+    }
+
+    fn provenance(&self) -> Option<Provenance> {
+        Extracter::provenance(self)
+    }
+}
@@ -0,0 +1,372 @@
+use std::sync::{Arc, LazyLock};
+
+use arxiv::Arxiv;
+use async_trait::async_trait;
+use derive_builder::Builder;
+use futures::future::join_all;
+use langchain_rust::{
+    embedding::{Embedder, EmbedderError},
+    fmt_template,
+    language_models::llm::LLM,
+    message_formatter,
+    prompt::{FormatPrompter, HumanMessagePromptTemplate},
+    prompt_args, template_fstring,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tokio::sync::OnceCell;
+
+use crate::{
+    agent::AgentApp,
+    dao::RelevanceDAO,
+    models::PaperRelevance,
+    prompts::PromptTemplate,
+    tools::{
+        similarity::cosine,
+        usage::{count_tokens, usage_or_estimate, ExtractionUsage},
+    },
+};
+
+use super::{
+    pipeline::Stage, topic::ArxivTopicData, ErrorClass, ExtractResult, ExtracterError, Provenance,
+};
+
+/// Score below which a paper is off-topic.
+pub const DEFAULT_RELEVANCE_THRESHOLD: f64 = 0.5;
+
+/// The `relevance` prompt shipped with the crate.
+pub fn default_relevance_prompt() -> PromptTemplate {
+    PromptTemplate::new("relevance", "1", include_str!("../../prompts/relevance.md"))
+}
+
+/// How relevant a paper is to the subscription's topic.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Relevance {
+    /// In `0.0..=1.0` for a yes/no model, a cosine similarity for embeddings.
+    pub score: f64,
+    /// Whether `score` reached the threshold, off-topic papers that were
+    /// kept have it unset.
+    pub relevant: bool,
+}
+
+/// What happens to off-topic papers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RelevanceMode {
+    /// Stop before the later stages, the paper is reported as filtered.
+    #[default]
+    Drop,
+    /// Extract the paper anyway, with `relevant` unset.
+    Flag,
+}
+
+pub enum RelevanceScorer {
+    /// Cosine similarity of the embeddings of the paper and the topic.
+    Embedding(Box<dyn Embedder>),
+    /// A yes/no question to a small model, 1.0 for yes and 0.0 for no.
+    Llm(Box<dyn LLM>),
+}
+
+/// Scores each paper's relevance to `topic` before the expensive stages of a
+/// [`PipelineExtracter`](super::pipeline::PipelineExtracter), keyword search
+/// being full of false positives. Papers scoring below `threshold` are
+/// dropped or flagged as `mode` says.
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct RelevanceFilter {
+    /// What the subscription is about, e.g. "automated repair of software bugs".
+    #[builder(setter(into))]
+    topic: String,
+    scorer: RelevanceScorer,
+    #[builder(default = "DEFAULT_RELEVANCE_THRESHOLD")]
+    threshold: f64,
+    #[builder(default)]
+    mode: RelevanceMode,
+    /// Yes/no prompt of the LLM scorer, needs `{topic}`, `{title}` and `{summary}`.
+    #[builder(default = "Arc::new(default_relevance_prompt())", setter(into))]
+    prompt: Arc<PromptTemplate>,
+    /// Model name behind the LLM scorer.
+    #[builder(default = "\"unknown\".to_string()", setter(into))]
+    model: String,
+    /// Embedding of `topic`, computed on first use.
+    #[builder(setter(skip))]
+    topic_embedding: OnceCell<Vec<f64>>,
+}
+
+/// Asks the yes/no question of a [`RelevanceFilter`].
+struct Judge<'a> {
+    prompt: &'a PromptTemplate,
+    llm: &'a dyn LLM,
+}
+
+impl AgentApp for Judge<'_> {
+    fn get_prompt(&self) -> Box<dyn FormatPrompter> {
+        let relevance_prompt = self.prompt.content.clone();
+        let prompt = message_formatter![fmt_template!(HumanMessagePromptTemplate::new(
+            template_fstring!(relevance_prompt, "topic", "title", "summary")
+        ))];
+        Box::new(prompt)
+    }
+
+    fn get_llm(&self) -> Box<dyn LLM> {
+        self.llm.clone_box()
+    }
+}
+
+static ANSWER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b(yes|no)\b").unwrap());
+
+/// 1.0 for yes, 0.0 for no, by the last of them in `answer` since reasoning
+/// models think aloud first.
+fn parse_answer(answer: &str) -> Option<f64> {
+    let last = ANSWER.find_iter(answer).last()?;
+    Some(if last.as_str().eq_ignore_ascii_case("yes") {
+        1.0
+    } else {
+        0.0
+    })
+}
+
+fn paper_text(ctx: &Arxiv) -> String {
+    format!("{}\n{}", ctx.title, ctx.summary)
+}
+
+/// Transient when `err` is network trouble or throttling rather than a bad
+/// request.
+fn embedding_class(err: &EmbedderError) -> ErrorClass {
+    let transport = match err {
+        EmbedderError::RequestError(_)
+        | EmbedderError::OpenAIError(_)
+        | EmbedderError::OllamaError(_) => true,
+        EmbedderError::HttpError { status_code, .. } => {
+            status_code.is_server_error() || status_code.as_u16() == 429
+        }
+        _ => false,
+    };
+    if transport {
+        ErrorClass::Transient
+    } else {
+        ErrorClass::Permanent
+    }
+}
+
+impl RelevanceFilter {
+    async fn topic_embedding(&self, embedder: &dyn Embedder) -> Result<&[f64], EmbedderError> {
+        let embedding = self
+            .topic_embedding
+            .get_or_try_init(|| embedder.embed_query(&self.topic))
+            .await?;
+        Ok(embedding)
+    }
+
+    async fn ask(&self, llm: &dyn LLM, ctx: &Arxiv) -> ExtractResult<f64> {
+        let judge = Judge {
+            prompt: &self.prompt,
+            llm,
+        };
+        let args = prompt_args![
+            "topic" => self.topic,
+            "title" => ctx.title,
+            "summary" => ctx.summary];
+        let res = match judge.call(args).await {
+            Ok(res) => res,
+            Err(err) => return (Err(ExtracterError::ChainError(err, ctx.id.clone())), None),
+        };
+        let prompt_tokens = self.prompt.tokens
+            + count_tokens(&self.topic)
+            + count_tokens(&ctx.title)
+            + count_tokens(&ctx.summary);
+        let usage = usage_or_estimate(res.tokens, prompt_tokens, &res.generation);
+        let score = parse_answer(&res.generation).ok_or_else(|| {
+            ExtracterError::ParseError(
+                "no yes or no in the answer".into(),
+                ctx.id.clone(),
+                res.generation.clone(),
+            )
+        });
+        (score, Some(ExtractionUsage::new(&self.model, usage)))
+    }
+
+    /// Record `score` on `data`, an error for off-topic papers to drop.
+    fn judge(
+        &self,
+        ctx: &Arxiv,
+        data: &mut ArxivTopicData,
+        score: f64,
+    ) -> Result<(), ExtracterError> {
+        let relevant = score >= self.threshold;
+        data.relevance = Some(Relevance { score, relevant });
+        match (relevant, self.mode) {
+            (false, RelevanceMode::Drop) => Err(ExtracterError::Irrelevant(score, ctx.id.clone())),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl Stage for RelevanceFilter {
+    fn name(&self) -> &str {
+        "relevance"
+    }
+
+    async fn run(&self, ctx: &Arxiv, data: &mut ArxivTopicData) -> ExtractResult<()> {
+        match &self.scorer {
+            RelevanceScorer::Embedding(embedder) => {
+                let topic = match embedder.embed_query(&paper_text(ctx)).await {
+                    Ok(paper) => self
+                        .topic_embedding(embedder.as_ref())
+                        .await
+                        .map(|topic| (paper, topic)),
+                    Err(err) => Err(err),
+                };
+                match topic {
+                    Ok((paper, topic)) => (self.judge(ctx, data, cosine(&paper, topic)), None),
+                    Err(err) => {
+                        let err = ExtracterError::of_class(
+                            embedding_class(&err),
+                            err.to_string(),
+                            &ctx.id,
+                        );
+                        (Err(err), None)
+                    }
+                }
+            }
+            RelevanceScorer::Llm(llm) => {
+                let (score, usage) = self.ask(llm.as_ref(), ctx).await;
+                (score.and_then(|score| self.judge(ctx, data, score)), usage)
+            }
+        }
+    }
+
+    /// Embeds the whole batch in one call.
+    async fn run_batch(
+        &self,
+        ctxs: &[Arxiv],
+        data: &mut [ArxivTopicData],
+    ) -> Vec<ExtractResult<()>> {
+        let RelevanceScorer::Embedding(embedder) = &self.scorer else {
+            return join_all(
+                ctxs.iter()
+                    .zip(data.iter_mut())
+                    .map(|(ctx, data)| self.run(ctx, data)),
+            )
+            .await;
+        };
+        let texts: Vec<String> = ctxs.iter().map(paper_text).collect();
+        let vectors = match embedder.embed_documents(&texts).await {
+            Ok(vectors) => self
+                .topic_embedding(embedder.as_ref())
+                .await
+                .map(|topic| (vectors, topic)),
+            Err(err) => Err(err),
+        };
+        match vectors {
+            Ok((vectors, topic)) => ctxs
+                .iter()
+                .zip(data.iter_mut())
+                .zip(vectors)
+                .map(|((ctx, data), paper)| (self.judge(ctx, data, cosine(&paper, topic)), None))
+                .collect(),
+            Err(err) => {
+                let class = embedding_class(&err);
+                let message = err.to_string();
+                ctxs.iter()
+                    .map(|ctx| {
+                        (
+                            Err(ExtracterError::of_class(class, message.clone(), &ctx.id)),
+                            None,
+                        )
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Off-topic papers must not reach the later stages.
+    fn required(&self) -> bool {
+        true
+    }
+
+    fn estimate_tokens(&self, ctx: &Arxiv) -> u32 {
+        match self.scorer {
+            // embeddings are not rate limited
+            RelevanceScorer::Embedding(_) => 0,
+            // ~4 characters per token for English text
+            RelevanceScorer::Llm(_) => {
+                ((self.prompt.content.len()
+                    + self.topic.len()
+                    + ctx.title.len()
+                    + ctx.summary.len())
+                    / 4) as u32
+            }
+        }
+    }
+
+    fn estimate_requests(&self, ctxs: &[Arxiv]) -> u32 {
+        match self.scorer {
+            RelevanceScorer::Embedding(_) => 0,
+            RelevanceScorer::Llm(_) => ctxs.len() as u32,
+        }
+    }
+
+    fn provenance(&self) -> Option<Provenance> {
+        match self.scorer {
+            RelevanceScorer::Embedding(_) => None,
+            RelevanceScorer::Llm(_) => {
+                Some(Provenance::new("relevance", &self.prompt, &self.model))
+            }
+        }
+    }
+}
+
+/// A stored relevance score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoredPaper {
+    pub arxiv_id: String,
+    pub title: String,
+    pub score: f64,
+    pub relevant: bool,
+    /// Whether the paper was extracted, flagged papers are kept though off-topic.
+    pub kept: bool,
+    pub created_at: String,
+}
+
+impl From<PaperRelevance> for ScoredPaper {
+    fn from(relevance: PaperRelevance) -> Self {
+        Self {
+            arxiv_id: relevance.arxiv_id,
+            title: relevance.title,
+            score: relevance.score,
+            relevant: relevance.relevant,
+            kept: relevance.kept,
+            created_at: relevance.created_at,
+        }
+    }
+}
+
+/// The latest `limit` papers scored off-topic, to audit the threshold.
+pub async fn off_topic_papers(
+    pool: &SqlitePool,
+    limit: i64,
+) -> Result<Vec<ScoredPaper>, sqlx::Error> {
+    Ok(RelevanceDAO::list_off_topic(pool, limit)
+        .await?
+        .into_iter()
+        .map(ScoredPaper::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_answer;
+
+    #[test]
+    fn test_parse_answer() {
+        assert_eq!(parse_answer("Yes"), Some(1.0));
+        assert_eq!(parse_answer("no."), Some(0.0));
+        assert_eq!(
+            parse_answer("<think>yes, it mentions repair, but of concrete</think>\nNo"),
+            Some(0.0)
+        );
+        assert_eq!(parse_answer("nothing"), None);
+    }
+}
@@ -0,0 +1,146 @@
+use std::{fmt::Display, sync::Arc};
+
+use arxiv::Arxiv;
+use derive_builder::Builder;
+use langchain_rust::{
+    fmt_template,
+    language_models::llm::LLM,
+    message_formatter,
+    prompt::{FormatPrompter, HumanMessagePromptTemplate},
+    prompt_args, template_fstring,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    agent::AgentApp,
+    prompts::PromptTemplate,
+    tools::{
+        parser::parse_json_md,
+        usage::{count_tokens, usage_or_estimate, ExtractionUsage},
+    },
+};
+
+use super::{Extracter, ExtracterError, Provenance};
+
+/// The `summary` prompt shipped with the crate.
+pub fn default_summary_prompt() -> PromptTemplate {
+    PromptTemplate::new("summary", "1", include_str!("../../prompts/summary.md"))
+}
+
+/// What a paper is about in a few lines, for reading rather than trending.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ArxivSummaryData {
+    /// One sentence.
+    pub tldr: String,
+    #[serde(default)]
+    pub contributions: Vec<String>,
+    /// Headline quantitative results.
+    #[serde(default)]
+    pub results: Vec<String>,
+    #[serde(default)]
+    pub limitations: Vec<String>,
+}
+
+impl Display for ArxivSummaryData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "TL;DR: {}", self.tldr)?;
+        let sections = [
+            ("Contributions", &self.contributions),
+            ("Results", &self.results),
+            ("Limitations", &self.limitations),
+        ];
+        for (name, items) in sections {
+            if items.is_empty() {
+                continue;
+            }
+            writeln!(f, "{}:", name)?;
+            for item in items {
+                writeln!(f, "  - {}", item)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct SummaryExtracter {
+    #[builder(default = "Arc::new(default_summary_prompt())", setter(into))]
+    prompt: Arc<PromptTemplate>,
+    llm: Box<dyn LLM>,
+    /// Model name behind `llm`, recorded with every extraction.
+    #[builder(default = "\"unknown\".to_string()", setter(into))]
+    model: String,
+}
+
+impl Clone for SummaryExtracter {
+    fn clone(&self) -> Self {
+        Self {
+            prompt: self.prompt.clone(),
+            llm: self.llm.clone_box(),
+            model: self.model.clone(),
+        }
+    }
+}
+
+impl SummaryExtracter {
+    fn parse(id: &str, data: &str) -> Result<ArxivSummaryData, ExtracterError> {
+        let parse_error = |err| ExtracterError::ParseError(err, id.to_string(), data.to_string());
+        let json_data = parse_json_md(data).map_err(parse_error)?;
+
+        serde_json::from_value(json_data).map_err(|err| parse_error(Box::new(err)))
+    }
+}
+
+impl Extracter for SummaryExtracter {
+    type Input = Arxiv;
+    type Output = ArxivSummaryData;
+
+    async fn extract(&self, ctx: &Self::Input) -> Result<Self::Output, ExtracterError> {
+        self.extract_with_usage(ctx).await.0
+    }
+
+    async fn extract_with_usage(
+        &self,
+        ctx: &Self::Input,
+    ) -> (
+        Result<Self::Output, ExtracterError>,
+        Option<ExtractionUsage>,
+    ) {
+        let args = prompt_args![
+            "title" => ctx.title,
+            "summary" => ctx.summary];
+        let res = match self.call(args).await {
+            Ok(res) => res,
+            Err(err) => return (Err(ExtracterError::ChainError(err, ctx.id.clone())), None),
+        };
+        let prompt_tokens =
+            self.prompt.tokens + count_tokens(&ctx.title) + count_tokens(&ctx.summary);
+        let usage = usage_or_estimate(res.tokens, prompt_tokens, &res.generation);
+        let usage = ExtractionUsage::new(&self.model, usage);
+        (Self::parse(&ctx.id, &res.generation), Some(usage))
+    }
+
+    fn estimate_tokens(&self, ctx: &Self::Input) -> u32 {
+        // ~4 characters per token for English text
+        ((self.prompt.content.len() + ctx.title.len() + ctx.summary.len()) / 4) as u32
+    }
+
+    fn provenance(&self) -> Option<Provenance> {
+        Some(Provenance::new("summary", &self.prompt, &self.model))
+    }
+}
+
+impl AgentApp for SummaryExtracter {
+    fn get_prompt(&self) -> Box<dyn FormatPrompter> {
+        let summary_prompt = self.prompt.content.clone();
+        let prompt = message_formatter![fmt_template!(HumanMessagePromptTemplate::new(
+            template_fstring!(summary_prompt, "title", "summary")
+        ))];
+        Box::new(prompt)
+    }
+
+    fn get_llm(&self) -> Box<dyn LLM> {
+        self.llm.clone_box()
+    }
+}
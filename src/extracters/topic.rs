@@ -1,4 +1,4 @@
-use std::{fmt::Debug, fs};
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
 
 use arxiv::Arxiv;
 use derive_builder::Builder;
@@ -12,16 +12,36 @@ use langchain_rust::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{agent::AgentApp, tools::parser::parse_json_md};
+use crate::{
+    agent::AgentApp,
+    prompts::PromptTemplate,
+    tools::{
+        parser::parse_json_md,
+        usage::{count_tokens, usage_or_estimate, ExtractionUsage},
+    },
+};
+
+use super::{
+    artifact::{ArtifactKind, ArxivArtifactData},
+    relevance::Relevance,
+    summary::ArxivSummaryData,
+    Extracter, ExtracterError, Provenance,
+};
 
-use super::{Extracter, ExtracterError};
+/// The `topic` prompt shipped with the crate.
+pub fn default_topic_prompt() -> PromptTemplate {
+    PromptTemplate::new("topic", "1", include_str!("../../prompts/topic.md"))
+}
 
 #[derive(Builder)]
 #[builder(pattern = "owned")]
 pub struct TopicExtracter {
-    #[builder(default="prompts/topic.md".to_string())]
-    prompt: String,
+    #[builder(default = "Arc::new(default_topic_prompt())", setter(into))]
+    prompt: Arc<PromptTemplate>,
     llm: Box<dyn LLM>,
+    /// Model name behind `llm`, recorded with every extraction.
+    #[builder(default = "\"unknown\".to_string()", setter(into))]
+    model: String,
 }
 
 impl Clone for TopicExtracter {
@@ -29,16 +49,19 @@ impl Clone for TopicExtracter {
         Self {
             prompt: self.prompt.clone(),
             llm: self.llm.clone_box(),
+            model: self.model.clone(),
         }
     }
 }
 
 impl Default for TopicExtracter {
     fn default() -> Self {
-        let llm = Ollama::default().with_model("llama3.2:3b");
+        let model = "llama3.2:3b";
+        let llm = Ollama::default().with_model(model);
         Self {
-            prompt: "prompts/topic.md".to_string(),
+            prompt: Arc::new(default_topic_prompt()),
             llm: Box::new(llm),
+            model: model.to_string(),
         }
     }
 }
@@ -48,24 +71,55 @@ impl Extracter for TopicExtracter {
     type Output = ArxivTopicData;
 
     async fn extract(&self, ctx: &Self::Input) -> Result<Self::Output, ExtracterError> {
+        self.extract_with_usage(ctx).await.0
+    }
+
+    async fn extract_with_usage(
+        &self,
+        ctx: &Self::Input,
+    ) -> (
+        Result<Self::Output, ExtracterError>,
+        Option<ExtractionUsage>,
+    ) {
         let args = prompt_args![
             "title" => ctx.title,
             "summary" => ctx.summary];
-        let data = self
-            .invoke(args)
-            .await
-            .map_err(|err| ExtracterError::ChainError(err, format!("{:?}", ctx)))?;
-        let json_data = parse_json_md(&data)
-            .map_err(|err| ExtracterError::Other(err, format!("{:?}", data)))?;
-
-        Ok(serde_json::from_value(json_data.clone())
-            .map_err(|err| ExtracterError::Other(Box::new(err), format!("{:?}", json_data)))?)
+        let res = match self.call(args).await {
+            Ok(res) => res,
+            Err(err) => return (Err(ExtracterError::ChainError(err, ctx.id.clone())), None),
+        };
+        let usage = usage_or_estimate(res.tokens, self.prompt_tokens(ctx), &res.generation);
+        let usage = ExtractionUsage::new(&self.model, usage);
+        (Self::parse(&ctx.id, &res.generation), Some(usage))
+    }
+
+    fn estimate_tokens(&self, ctx: &Self::Input) -> u32 {
+        // ~4 characters per token for English text
+        ((self.prompt.content.len() + ctx.title.len() + ctx.summary.len()) / 4) as u32
+    }
+
+    fn provenance(&self) -> Option<Provenance> {
+        Some(Provenance::new("topic", &self.prompt, &self.model))
+    }
+}
+
+impl TopicExtracter {
+    /// Tokens of the prompt sent for `ctx`.
+    fn prompt_tokens(&self, ctx: &Arxiv) -> u32 {
+        self.prompt.tokens + count_tokens(&ctx.title) + count_tokens(&ctx.summary)
+    }
+
+    fn parse(id: &str, data: &str) -> Result<ArxivTopicData, ExtracterError> {
+        let parse_error = |err| ExtracterError::ParseError(err, id.to_string(), data.to_string());
+        let json_data = parse_json_md(data).map_err(parse_error)?;
+
+        serde_json::from_value(json_data).map_err(|err| parse_error(Box::new(err)))
     }
 }
 
 impl AgentApp for TopicExtracter {
     fn get_prompt(&self) -> Box<dyn FormatPrompter> {
-        let topic_prompt = fs::read_to_string(&self.prompt).unwrap();
+        let topic_prompt = self.prompt.content.clone();
         let prompt = message_formatter![fmt_template!(HumanMessagePromptTemplate::new(
             template_fstring!(topic_prompt, "title", "summary")
         ))];
@@ -77,12 +131,76 @@ impl AgentApp for TopicExtracter {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct ArxivTopicData {
     pub title: String,
     pub solved_problem: Vec<String>,
     pub research_field: Vec<String>,
     pub techniques_used: Vec<String>,
+    /// Agreement on each keyword when several extracters voted, see
+    /// [`EnsembleExtracter`](super::ensemble::EnsembleExtracter).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<TopicConfidence>,
+    /// Set by the `summary` stage of a
+    /// [`PipelineExtracter`](super::pipeline::PipelineExtracter).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ArxivSummaryData>,
+    /// Set by the `artifact` stage of a
+    /// [`PipelineExtracter`](super::pipeline::PipelineExtracter).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifacts: Option<ArxivArtifactData>,
+    /// Language of the abstract, set by
+    /// [`LanguageStage`](super::pipeline::LanguageStage), e.g. `en`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Relevance to the subscription's topic, set by
+    /// [`RelevanceFilter`](super::relevance::RelevanceFilter).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relevance: Option<Relevance>,
+    /// Optional pipeline stages that failed on this paper.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_stages: Vec<String>,
+}
+
+/// Share of ensemble members, in `0.0..=1.0`, that produced each keyword of a facet.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TopicConfidence {
+    pub solved_problem: HashMap<String, f64>,
+    pub research_field: HashMap<String, f64>,
+    pub techniques_used: HashMap<String, f64>,
+}
+
+impl ArxivTopicData {
+    /// Confidence of a keyword of `techniques_used`, 1.0 without votes.
+    pub fn technique_confidence(&self, keyword: &str) -> f64 {
+        self.confidence
+            .as_ref()
+            .and_then(|c| c.techniques_used.get(keyword).copied())
+            .unwrap_or(1.0)
+    }
+
+    /// Artifacts of `kind`, none if they were not extracted. Pass it to
+    /// [`TrendingAnalyzer::top_k`](crate::analyzers::TrendingAnalyzer::top_k)
+    /// to rank benchmarks or datasets.
+    pub fn artifact_names(&self, kind: ArtifactKind) -> Vec<String> {
+        self.artifacts
+            .as_ref()
+            .map(|a| a.names(kind).to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Drop the keywords fewer than `min` of the ensemble agreed on.
+    pub fn keep_confident(mut self, min: f64) -> Self {
+        if let Some(confidence) = &self.confidence {
+            let keep = |facet: &mut Vec<String>, scores: &HashMap<String, f64>| {
+                facet.retain(|k| scores.get(k).copied().unwrap_or(1.0) >= min)
+            };
+            keep(&mut self.solved_problem, &confidence.solved_problem);
+            keep(&mut self.research_field, &confidence.research_field);
+            keep(&mut self.techniques_used, &confidence.techniques_used);
+        }
+        self
+    }
 }
 
 #[cfg(test)]
@@ -103,9 +221,7 @@ mod test {
             .build()
             .unwrap();
 
-        let mut ctx = Arxiv::default();
-        ctx.title = "Deep Residual Learning for Image Recognition".to_string();
-        ctx.summary = r#"
+        let summary = r#"
             Deeper neural networks are more difficult to train. We
             present a residual learning framework to ease the training
             of networks that are substantially deeper than those used
@@ -124,6 +240,11 @@ mod test {
             , where we also won the 1st
             places on the tasks of ImageNet detection, ImageNet localization, COCO detection, and COCO segmentation
             "#.to_string();
+        let ctx = Arxiv {
+            title: "Deep Residual Learning for Image Recognition".to_string(),
+            summary,
+            ..Default::default()
+        };
 
         let res: ArxivTopicData = extracter.extract(&ctx).await.unwrap();
 
@@ -81,7 +81,7 @@ mod test {
     #[test]
     fn test_query_adaptor() {
         let query = "machine learning";
-        let res = ArxivFetcher::query_adaptor(&query);
+        let res = ArxivFetcher::query_adaptor(query);
         println!("{}", res);
     }
 }
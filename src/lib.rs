@@ -4,8 +4,12 @@ mod dao;
 pub mod db;
 pub mod extracters;
 pub mod fetchers;
+pub mod llms;
 mod models;
+pub mod normalizers;
+pub mod prompts;
 pub mod sciffer;
+pub mod taxonomy;
 pub mod tools;
 pub mod log;
 pub mod config;
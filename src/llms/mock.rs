@@ -0,0 +1,268 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::Path,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use futures::{stream, Stream};
+use langchain_rust::{
+    embedding::{Embedder, EmbedderError},
+    language_models::{llm::LLM, GenerateResult, LLMError},
+    schemas::{Message, StreamData},
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::time::sleep;
+
+/// A canned reply of [`MockLlm`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum MockResponse {
+    Text(String),
+    Error(String),
+    /// A connection failure, transient unlike [`MockResponse::Error`].
+    Unreachable(String),
+    Delayed(u64, Box<MockResponse>),
+}
+
+impl MockResponse {
+    pub fn text<S: Into<String>>(s: S) -> Self {
+        Self::Text(s.into())
+    }
+
+    pub fn error<S: Into<String>>(s: S) -> Self {
+        Self::Error(s.into())
+    }
+
+    pub fn unreachable<S: Into<String>>(s: S) -> Self {
+        Self::Unreachable(s.into())
+    }
+
+    pub fn delayed(delay: Duration, response: MockResponse) -> Self {
+        Self::Delayed(delay.as_millis() as u64, Box::new(response))
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    script: VecDeque<MockResponse>,
+    calls: Vec<String>,
+}
+
+/// Deterministic [`LLM`] for offline tests and benchmarks.
+///
+/// A response is picked by, in order: the first fixture whose key occurs in
+/// the prompt, the next scripted response, and finally the default response.
+/// Clones share the script and the call log.
+#[derive(Clone)]
+pub struct MockLlm {
+    fixtures: Vec<(String, MockResponse)>,
+    default: MockResponse,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl Default for MockLlm {
+    fn default() -> Self {
+        Self {
+            fixtures: Vec::new(),
+            default: MockResponse::error("MockLlm: no response scripted"),
+            state: Arc::new(Mutex::new(MockState::default())),
+        }
+    }
+}
+
+impl MockLlm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue responses returned one per call, in order.
+    pub fn with_responses<I: IntoIterator<Item = MockResponse>>(self, responses: I) -> Self {
+        self.state.lock().unwrap().script.extend(responses);
+        self
+    }
+
+    /// Reply with `response` whenever the prompt contains `key`.
+    pub fn with_fixture<S: Into<String>>(mut self, key: S, response: MockResponse) -> Self {
+        self.fixtures.push((key.into(), response));
+        self
+    }
+
+    pub fn with_default(mut self, response: MockResponse) -> Self {
+        self.default = response;
+        self
+    }
+
+    /// Load fixtures from a JSON object mapping prompt keys to responses.
+    ///
+    /// A value is either a plain string (a text reply) or a tagged
+    /// [`MockResponse`], e.g. `{"type": "error", "value": "boom"}`.
+    pub fn with_fixture_file<P: AsRef<Path>>(mut self, path: P) -> Result<Self, LLMError> {
+        let content = fs::read_to_string(path)?;
+        let fixtures: HashMap<String, Value> = serde_json::from_str(&content)?;
+        let mut fixtures = fixtures
+            .into_iter()
+            .map(|(key, value)| {
+                let response = match value {
+                    Value::String(s) => MockResponse::Text(s),
+                    other => serde_json::from_value(other)?,
+                };
+                Ok((key, response))
+            })
+            .collect::<Result<Vec<_>, LLMError>>()?;
+        // longer keys are more specific, keep the lookup deterministic
+        fixtures.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.0.cmp(&b.0)));
+        self.fixtures.extend(fixtures);
+        Ok(self)
+    }
+
+    /// Prompts received so far, in call order.
+    pub fn calls(&self) -> Vec<String> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    pub fn call_count(&self) -> usize {
+        self.state.lock().unwrap().calls.len()
+    }
+
+    fn next_response(&self, prompt: &str) -> MockResponse {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(prompt.to_string());
+        if let Some((_, response)) = self.fixtures.iter().find(|(key, _)| prompt.contains(key)) {
+            return response.clone();
+        }
+        state.script.pop_front().unwrap_or(self.default.clone())
+    }
+
+    async fn respond(response: MockResponse) -> Result<String, LLMError> {
+        let mut response = response;
+        loop {
+            match response {
+                MockResponse::Text(s) => return Ok(s),
+                MockResponse::Error(e) => return Err(LLMError::OtherError(e)),
+                MockResponse::Unreachable(e) => {
+                    return Err(LLMError::IoError(std::io::Error::other(e)))
+                }
+                MockResponse::Delayed(ms, inner) => {
+                    sleep(Duration::from_millis(ms)).await;
+                    response = *inner;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl LLM for MockLlm {
+    async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
+        let prompt = self.messages_to_string(messages);
+        let response = self.next_response(&prompt);
+        let generation = Self::respond(response).await?;
+        Ok(GenerateResult {
+            tokens: None,
+            generation,
+        })
+    }
+
+    async fn stream(
+        &self,
+        messages: &[Message],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
+        let generation = self.generate(messages).await?.generation;
+        let data = StreamData::new(Value::String(generation.clone()), None, generation);
+        Ok(Box::pin(stream::iter(vec![Ok(data)])))
+    }
+}
+
+/// Dimension of the vectors produced by [`MockEmbedder`].
+pub const MOCK_EMBEDDING_DIM: usize = 256;
+
+/// Deterministic [`Embedder`] for offline tests.
+///
+/// Texts are embedded as hashed character trigrams, so strings that share
+/// most of their spelling end up close. Fixed vectors can be given per text
+/// to stand in for real semantic similarity.
+#[derive(Clone, Default)]
+pub struct MockEmbedder {
+    fixtures: HashMap<String, Vec<f64>>,
+}
+
+impl MockEmbedder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Embed `text` as `vector` instead of its trigrams.
+    pub fn with_vector<S: Into<String>>(mut self, text: S, vector: Vec<f64>) -> Self {
+        self.fixtures.insert(text.into(), vector);
+        self
+    }
+
+    fn embed(&self, text: &str) -> Vec<f64> {
+        if let Some(vector) = self.fixtures.get(text) {
+            return vector.clone();
+        }
+        let padded: Vec<char> = format!("  {} ", text.to_lowercase()).chars().collect();
+        let mut vector = vec![0.0; MOCK_EMBEDDING_DIM];
+        for trigram in padded.windows(3) {
+            // FNV-1a over the trigram
+            let hash = trigram.iter().fold(0xcbf29ce484222325u64, |h, c| {
+                (h ^ *c as u64).wrapping_mul(0x100000001b3)
+            });
+            vector[(hash % MOCK_EMBEDDING_DIM as u64) as usize] += 1.0;
+        }
+        let norm = vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+#[async_trait]
+impl Embedder for MockEmbedder {
+    async fn embed_documents(&self, documents: &[String]) -> Result<Vec<Vec<f64>>, EmbedderError> {
+        Ok(documents.iter().map(|doc| self.embed(doc)).collect())
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f64>, EmbedderError> {
+        Ok(self.embed(text))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use langchain_rust::language_models::llm::LLM;
+
+    use super::{MockLlm, MockResponse};
+
+    #[tokio::test]
+    async fn test_mock_llm_script() {
+        let llm = MockLlm::new()
+            .with_responses(vec![MockResponse::text("a"), MockResponse::error("boom")])
+            .with_fixture("paper", MockResponse::text("fixture"));
+
+        assert_eq!(llm.invoke("hello").await.unwrap(), "a");
+        assert_eq!(llm.invoke("a paper").await.unwrap(), "fixture");
+        assert!(llm.invoke("hello").await.is_err());
+        assert!(llm.invoke("hello").await.is_err());
+        assert_eq!(llm.clone().call_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_mock_llm_delay() {
+        let llm = MockLlm::new().with_default(MockResponse::delayed(
+            Duration::from_millis(50),
+            MockResponse::text("late"),
+        ));
+        let start = std::time::Instant::now();
+        assert_eq!(llm.invoke("hello").await.unwrap(), "late");
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
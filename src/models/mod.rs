@@ -9,6 +9,8 @@ pub struct Paper {
     pub publish_date: Option<String>,
     pub insert_date: String,
     pub url: String,
+    /// The arXiv comment, often naming the venue.
+    pub comment: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -17,8 +19,233 @@ pub struct Keyword {
     pub keyword: String,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct PaperKeyword {
     pub paper_id: i32,
     pub keyword_id: i32,
+    pub extraction_id: Option<i64>,
+    /// Share of ensemble members that agreed on the keyword.
+    pub confidence: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct FailedExtraction {
+    pub id: i64,
+    pub arxiv_id: String,
+    pub title: String,
+    pub summary: String,
+    pub publish_date: String,
+    pub url: String,
+    pub error_kind: String,
+    pub error: String,
+    pub attempts: i64,
+    pub last_attempt: String,
+    /// Permanent failures are kept for inspection but never retried.
+    pub transient: bool,
+}
+
+/// One extracter run over a paper, with the prompt and model that produced it.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PaperExtraction {
+    pub id: i64,
+    pub paper_id: i64,
+    pub extracter: String,
+    pub prompt_name: String,
+    pub prompt_version: String,
+    pub prompt_hash: String,
+    pub model: String,
+    pub created_at: String,
+    pub run_id: Option<i64>,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    /// USD, `None` if the model has no price.
+    pub cost: Option<f64>,
+    /// The whole output as JSON, e.g. every stage of a pipeline.
+    pub output: Option<String>,
+}
+
+/// A stored paper with the output of its latest extraction.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PaperOutput {
+    pub id: i64,
+    pub title: String,
+    pub abstract_text: Option<String>,
+    pub publish_date: Option<String>,
+    pub insert_date: String,
+    pub url: String,
+    pub comment: Option<String>,
+    /// JSON array of the author names, the first author first.
+    pub authors: String,
+    /// JSON array of the stored keywords of the extraction, normalized,
+    /// e.g. `[{"keyword": "RAG", "confidence": null}]`.
+    pub keywords: String,
+    /// JSON, see [`PaperExtraction::output`].
+    pub output: String,
+}
+
+/// Reading summary of a paper, lists are JSON arrays of strings.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PaperSummary {
+    pub id: i64,
+    pub paper_id: i64,
+    pub extraction_id: Option<i64>,
+    pub tldr: String,
+    pub contributions: String,
+    pub results: String,
+    pub limitations: String,
+    pub created_at: String,
+}
+
+/// A dataset, benchmark or metric, `key` is the
+/// [`rule_key`](crate::normalizers::keyword::rule_key) of the first `name` seen.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Artifact {
+    pub id: i64,
+    pub kind: String,
+    pub name: String,
+    pub key: String,
+}
+
+/// A score a paper reports on a benchmark.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ArtifactScore {
+    pub id: i64,
+    pub paper_id: i64,
+    pub benchmark_id: i64,
+    pub metric_id: Option<i64>,
+    pub value: String,
+    pub extraction_id: Option<i64>,
+}
+
+/// An artifact with the number of papers using it.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ArtifactCount {
+    pub name: String,
+    pub papers: i64,
+}
+
+/// A keyword of a paper.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PaperKeywordName {
+    pub paper_id: i64,
+    pub keyword: String,
+}
+
+/// An author of a paper.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PaperAuthorName {
+    pub paper_id: i64,
+    pub name: String,
+    /// Publish date of the paper, or insertion date when unknown.
+    pub published: String,
+}
+
+/// Papers published on `day`.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct DayCount {
+    /// `YYYY-MM-DD`
+    pub day: String,
+    pub papers: i64,
+}
+
+/// A paper with one of its keywords.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct KeywordPaper {
+    pub keyword: String,
+    pub title: String,
+    pub url: String,
+    /// Publish date, or insertion date when unknown.
+    pub published: String,
+}
+
+/// Papers published on `day` with a keyword.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct KeywordDayCount {
+    pub keyword: String,
+    /// `YYYY-MM-DD`
+    pub day: String,
+    pub papers: i64,
+}
+
+/// Relevance of a fetched paper to the subscription's topic.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PaperRelevance {
+    pub arxiv_id: String,
+    pub title: String,
+    pub score: f64,
+    pub relevant: bool,
+    /// Whether the paper was extracted rather than dropped.
+    pub kept: bool,
+    pub run_id: Option<i64>,
+    pub created_at: String,
+}
+
+/// One fit of a topic model over the stored abstracts.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TopicModelRun {
+    pub id: i64,
+    pub method: String,
+    pub papers: i64,
+    pub created_at: String,
+}
+
+/// A topic of a topic model.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct StoredTopic {
+    pub id: i64,
+    pub model_id: i64,
+    pub position: i64,
+    pub label: String,
+    /// JSON array of strings
+    pub terms: String,
+    pub prevalence: f64,
+}
+
+/// Weight of a topic in a paper.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PaperTopic {
+    pub paper_id: i64,
+    pub topic_id: i64,
+    pub weight: f64,
+}
+
+/// An extracted keyword of the papers of a topic, weighted by the topic's
+/// weight in each paper.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TopicKeyword {
+    pub keyword: String,
+    pub weight: f64,
+}
+
+/// One fetch-and-extract cycle of the sniffer.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Run {
+    pub id: i64,
+    pub created_at: String,
+    pub elapsed_ms: i64,
+    pub successes: i64,
+    pub failures: i64,
+    pub unfinished: i64,
+}
+
+/// Tokens one model spent during a run.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct RunUsage {
+    pub run_id: i64,
+    pub model: String,
+    pub extractions: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub cost: Option<f64>,
+}
+
+/// A raw keyword string mapped to (or, when rejected, kept apart from) a canonical keyword.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct KeywordAlias {
+    pub id: i64,
+    pub alias: String,
+    pub keyword: String,
+    pub status: String,
+    pub score: Option<f64>,
 }
@@ -0,0 +1,565 @@
+use std::collections::{HashMap, HashSet};
+
+use derive_builder::Builder;
+use langchain_rust::embedding::Embedder;
+use sqlx::SqlitePool;
+use tokio::sync::Mutex;
+
+use crate::{
+    dao::{KeywordAliasDAO, KeywordDAO},
+    models::Keyword,
+    tools::similarity::cosine,
+};
+
+use super::NormalizerError;
+
+/// Cosine similarity above which two keywords are merged.
+pub const DEFAULT_SIMILARITY: f64 = 0.9;
+
+/// Plural-looking words that are singular already.
+const INVARIANT: &[&str] = &["bias", "lens", "news", "series", "species"];
+
+/// Words skipped when building the acronym of a phrase.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "for", "in", "of", "on", "the", "to", "with",
+];
+
+/// Why a raw keyword ended up on its canonical keyword.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeReason {
+    /// First time the concept is seen, the keyword becomes canonical.
+    New,
+    /// Same keyword up to case, punctuation and plural.
+    Rule,
+    /// Acronym of the canonical keyword, or the other way round.
+    Acronym,
+    /// Embeddings closer than the threshold, with their cosine similarity.
+    Embedding(f64),
+    /// Merge set by a user.
+    Pinned,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalized {
+    pub raw: String,
+    pub canonical: String,
+    pub reason: MergeReason,
+}
+
+impl Normalized {
+    fn score(&self) -> Option<f64> {
+        match self.reason {
+            MergeReason::Embedding(score) => Some(score),
+            _ => None,
+        }
+    }
+}
+
+/// Canonical keywords of `merged`, deduplicated, in first-seen order.
+pub fn canonical_keywords(merged: &[Normalized]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    merged
+        .iter()
+        .filter(|n| seen.insert(n.canonical.as_str()))
+        .map(|n| n.canonical.clone())
+        .collect()
+}
+
+/// Lowercase, strip punctuation and singularize the last word:
+/// `"Large-Language Models"` and `"large language model"` share a key.
+pub fn rule_key(keyword: &str) -> String {
+    let cleaned: String = keyword
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '+' || c == '#' {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    let mut words: Vec<String> = cleaned.split_whitespace().map(str::to_lowercase).collect();
+    if let (Some(last), Some(raw)) = (words.pop(), cleaned.split_whitespace().last()) {
+        words.push(match raw.strip_suffix('s') {
+            // acronym plurals, `NNs` is too short for the plural rules
+            Some(stem) if stem.len() >= 2 && stem.chars().all(|c| !c.is_lowercase()) => {
+                stem.to_lowercase()
+            }
+            _ => singularize(&last),
+        });
+    }
+    words.join(" ")
+}
+
+fn singularize(word: &str) -> String {
+    if word.len() <= 3
+        || INVARIANT.contains(&word)
+        || ["ss", "us", "is", "ics"].iter().any(|s| word.ends_with(s))
+    {
+        return word.to_string();
+    }
+    if let Some(stem) = word.strip_suffix("ies") {
+        return format!("{}y", stem);
+    }
+    if ["sses", "ches", "shes", "xes"]
+        .iter()
+        .any(|s| word.ends_with(s))
+    {
+        return word[..word.len() - 2].to_string();
+    }
+    word.strip_suffix('s').unwrap_or(word).to_string()
+}
+
+/// `"large language model"` -> `"llm"`, `None` for single words.
+fn initials(key: &str) -> Option<String> {
+    let words: Vec<&str> = key.split(' ').filter(|w| !STOP_WORDS.contains(w)).collect();
+    if words.len() < 2 {
+        return None;
+    }
+    words.iter().map(|w| w.chars().next()).collect()
+}
+
+/// Whether `raw` is spelled as an acronym: capitals (`"LLMs"`) or a digit
+/// (`"gpt4"`). Short words like `"art"` or `"tool"` are not.
+fn is_acronym(raw: &str) -> bool {
+    let raw = raw.trim();
+    let raw = raw.strip_suffix('s').unwrap_or(raw);
+    (2..=6).contains(&raw.chars().count())
+        && raw.chars().all(char::is_alphanumeric)
+        && (raw.chars().all(|c| !c.is_lowercase()) || raw.chars().any(|c| c.is_ascii_digit()))
+}
+
+/// Acronyms keep their capitals (`"LLMs"` -> `"LLM"`), everything else is its key.
+fn display(raw: &str, key: &str) -> String {
+    let raw = raw.trim();
+    let raw = raw.strip_suffix('s').unwrap_or(raw);
+    let upper = raw.chars().all(|c| c.is_uppercase() || c.is_ascii_digit());
+    if is_acronym(raw) && upper && raw.to_lowercase() == key {
+        raw.to_string()
+    } else {
+        key.to_string()
+    }
+}
+
+struct Concept {
+    canonical: String,
+    key: String,
+    initials: Option<String>,
+    embedding: Option<Vec<f64>>,
+}
+
+#[derive(Default)]
+struct State {
+    concepts: Vec<Concept>,
+    /// Rule key of every known keyword and alias to its concept.
+    keys: HashMap<String, usize>,
+    pinned: HashMap<String, usize>,
+    rejected: HashSet<(String, usize)>,
+}
+
+impl State {
+    /// Concept whose canonical keyword is `canonical`, created if unknown.
+    fn concept(&mut self, canonical: &str) -> usize {
+        let key = rule_key(canonical);
+        if let Some(&idx) = self.keys.get(&key) {
+            return idx;
+        }
+        self.push(canonical.trim().to_string(), key, None)
+    }
+
+    fn push(&mut self, canonical: String, key: String, embedding: Option<Vec<f64>>) -> usize {
+        let idx = self.concepts.len();
+        self.concepts.push(Concept {
+            canonical,
+            initials: initials(&key),
+            key: key.clone(),
+            embedding,
+        });
+        self.keys.insert(key, idx);
+        idx
+    }
+
+    fn pin(&mut self, alias: &str, canonical: &str) {
+        let idx = self.concept(canonical);
+        let key = rule_key(alias);
+        self.rejected.remove(&(key.clone(), idx));
+        self.pinned.insert(key.clone(), idx);
+        self.keys.insert(key, idx);
+    }
+
+    fn reject(&mut self, alias: &str, canonical: &str) {
+        let idx = self.concept(canonical);
+        let key = rule_key(alias);
+        if self.pinned.get(&key) == Some(&idx) {
+            self.pinned.remove(&key);
+        }
+        if self.keys.get(&key) == Some(&idx) && self.concepts[idx].key != key {
+            self.keys.remove(&key);
+        }
+        self.rejected.insert((key, idx));
+    }
+
+    fn place(
+        &mut self,
+        raw: &str,
+        key: &str,
+        embedding: Option<&Vec<f64>>,
+        threshold: f64,
+    ) -> (usize, MergeReason) {
+        if let Some(&idx) = self.pinned.get(key) {
+            return (idx, MergeReason::Pinned);
+        }
+        if let Some(&idx) = self.keys.get(key) {
+            return (idx, MergeReason::Rule);
+        }
+
+        let allowed = |idx: &usize| !self.rejected.contains(&(key.to_string(), *idx));
+        let key_initials = initials(key);
+        let acronym = (0..self.concepts.len()).filter(allowed).find(|&idx| {
+            let concept = &self.concepts[idx];
+            (is_acronym(raw) && concept.initials.as_deref() == Some(key))
+                || (is_acronym(&concept.canonical) && key_initials.as_deref() == Some(&concept.key))
+        });
+        let merged = match acronym {
+            Some(idx) => Some((idx, MergeReason::Acronym)),
+            None => embedding.and_then(|embedding| {
+                (0..self.concepts.len())
+                    .filter(allowed)
+                    .filter_map(|idx| {
+                        let other = self.concepts[idx].embedding.as_ref()?;
+                        Some((idx, cosine(embedding, other)))
+                    })
+                    .filter(|(_, score)| *score >= threshold)
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(idx, score)| (idx, MergeReason::Embedding(score)))
+            }),
+        };
+
+        match merged {
+            Some((idx, reason)) => {
+                self.keys.insert(key.to_string(), idx);
+                (idx, reason)
+            }
+            None => {
+                let idx = self.push(display(raw, key), key.to_string(), embedding.cloned());
+                (idx, MergeReason::New)
+            }
+        }
+    }
+}
+
+/// Maps raw keywords to canonical ones.
+///
+/// Keywords equal up to case, punctuation and plural are merged by rule, an
+/// acronym is merged with the phrase it abbreviates, and with an embedder the
+/// remaining keywords are merged with their closest known keyword when the
+/// cosine similarity reaches `threshold`. Pinned merges always apply, rejected
+/// ones never do; the rules alone cannot be overridden by a rejection.
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct KeywordNormalizer {
+    #[builder(default, setter(strip_option))]
+    embedder: Option<Box<dyn Embedder>>,
+    #[builder(default = "DEFAULT_SIMILARITY")]
+    threshold: f64,
+    #[builder(setter(skip))]
+    state: Mutex<State>,
+}
+
+impl Default for KeywordNormalizer {
+    /// Rules and acronyms only.
+    fn default() -> Self {
+        Self {
+            embedder: None,
+            threshold: DEFAULT_SIMILARITY,
+            state: Mutex::default(),
+        }
+    }
+}
+
+impl KeywordNormalizer {
+    pub async fn normalize(&self, keywords: &[String]) -> Result<Vec<Normalized>, NormalizerError> {
+        let mut state = self.state.lock().await;
+
+        let mut embeddings = HashMap::new();
+        if let Some(embedder) = &self.embedder {
+            // embed the keywords the rules can't place, with concepts not embedded yet
+            let mut pending: Vec<String> = state
+                .concepts
+                .iter()
+                .filter(|c| c.embedding.is_none())
+                .map(|c| c.key.clone())
+                .collect();
+            for key in keywords.iter().map(|k| rule_key(k)) {
+                if !key.is_empty() && !state.keys.contains_key(&key) && !pending.contains(&key) {
+                    pending.push(key);
+                }
+            }
+            if !pending.is_empty() {
+                let vectors = embedder.embed_documents(&pending).await?;
+                embeddings.extend(pending.into_iter().zip(vectors));
+            }
+            for concept in state.concepts.iter_mut() {
+                if concept.embedding.is_none() {
+                    concept.embedding = embeddings.get(&concept.key).cloned();
+                }
+            }
+        }
+
+        let mut merged = Vec::with_capacity(keywords.len());
+        for raw in keywords {
+            let key = rule_key(raw);
+            if key.is_empty() {
+                continue;
+            }
+            let (idx, reason) = state.place(raw, &key, embeddings.get(&key), self.threshold);
+            merged.push(Normalized {
+                raw: raw.clone(),
+                canonical: state.concepts[idx].canonical.clone(),
+                reason,
+            });
+        }
+        Ok(merged)
+    }
+
+    /// Canonical keywords of `keywords`, see [`canonical_keywords`].
+    pub async fn canonicalize(&self, keywords: &[String]) -> Result<Vec<String>, NormalizerError> {
+        Ok(canonical_keywords(&self.normalize(keywords).await?))
+    }
+
+    /// Always map `alias` to `canonical`.
+    pub async fn pin(&self, alias: &str, canonical: &str) {
+        self.state.lock().await.pin(alias, canonical);
+    }
+
+    /// Never merge `alias` into `canonical` by acronym or embedding.
+    pub async fn reject(&self, alias: &str, canonical: &str) {
+        self.state.lock().await.reject(alias, canonical);
+    }
+
+    /// Known keywords and the aliases stored in `keyword_aliases`, with the
+    /// user decisions on them.
+    pub async fn load(&self, pool: &SqlitePool) -> Result<(), NormalizerError> {
+        let keywords = KeywordDAO::list_keywords(pool).await?;
+        let aliases = KeywordAliasDAO::list_aliases(pool).await?;
+
+        let mut state = self.state.lock().await;
+        for keyword in keywords.iter() {
+            state.concept(&keyword.keyword);
+        }
+        for alias in aliases.iter() {
+            match alias.status.as_str() {
+                "pinned" => state.pin(&alias.alias, &alias.keyword),
+                "rejected" => state.reject(&alias.alias, &alias.keyword),
+                _ => {
+                    let idx = state.concept(&alias.keyword);
+                    let key = rule_key(&alias.alias);
+                    if !state.rejected.contains(&(key.clone(), idx)) {
+                        state.keys.entry(key).or_insert(idx);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Store the merges of `merged` that changed the keyword.
+    pub async fn save(pool: &SqlitePool, merged: &[Normalized]) -> Result<(), NormalizerError> {
+        for n in merged {
+            if n.raw == n.canonical || n.reason == MergeReason::Pinned {
+                continue;
+            }
+            let keyword_id = KeywordDAO::create_keyword(
+                pool,
+                &Keyword {
+                    id: 0,
+                    keyword: n.canonical.clone(),
+                },
+            )
+            .await?;
+            KeywordAliasDAO::record_alias(pool, &n.raw, keyword_id, n.score()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Persist a user merge of `alias` into `canonical`, picked up by [`KeywordNormalizer::load`].
+pub async fn pin_alias(
+    pool: &SqlitePool,
+    alias: &str,
+    canonical: &str,
+) -> Result<(), NormalizerError> {
+    set_alias_status(pool, alias, canonical, "pinned").await
+}
+
+/// Persist a user veto on merging `alias` into `canonical`.
+pub async fn reject_alias(
+    pool: &SqlitePool,
+    alias: &str,
+    canonical: &str,
+) -> Result<(), NormalizerError> {
+    set_alias_status(pool, alias, canonical, "rejected").await
+}
+
+/// Stored aliases as `(alias, canonical, status, score)`.
+pub async fn list_aliases(
+    pool: &SqlitePool,
+) -> Result<Vec<(String, String, String, Option<f64>)>, NormalizerError> {
+    Ok(KeywordAliasDAO::list_aliases(pool)
+        .await?
+        .into_iter()
+        .map(|a| (a.alias, a.keyword, a.status, a.score))
+        .collect())
+}
+
+async fn set_alias_status(
+    pool: &SqlitePool,
+    alias: &str,
+    canonical: &str,
+    status: &str,
+) -> Result<(), NormalizerError> {
+    let keyword_id = KeywordDAO::create_keyword(
+        pool,
+        &Keyword {
+            id: 0,
+            keyword: canonical.to_string(),
+        },
+    )
+    .await?;
+    KeywordAliasDAO::set_status(pool, alias, keyword_id, status).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::llms::mock::MockEmbedder;
+
+    use super::{
+        pin_alias, reject_alias, rule_key, KeywordNormalizer, KeywordNormalizerBuilder, MergeReason,
+    };
+
+    fn strings(keywords: &[&str]) -> Vec<String> {
+        keywords.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_rule_key() {
+        assert_eq!(rule_key("Large-Language Models"), "large language model");
+        assert_eq!(rule_key("LLMs"), "llm");
+        assert_eq!(rule_key("NNs"), "nn");
+        assert_eq!(rule_key("gas"), "gas");
+        assert_eq!(rule_key("  Diffusion   Processes "), "diffusion process");
+        assert_eq!(rule_key("Policies"), "policy");
+        assert_eq!(rule_key("Robotics"), "robotics");
+        assert_eq!(rule_key("Inductive bias"), "inductive bias");
+    }
+
+    #[tokio::test]
+    async fn test_rules_and_acronyms() {
+        let normalizer = KeywordNormalizer::default();
+        let keywords = strings(&[
+            "LLM",
+            "Large Language Model",
+            "LLMs",
+            "large language models",
+        ]);
+        let merged = normalizer.normalize(&keywords).await.unwrap();
+
+        assert!(merged.iter().all(|n| n.canonical == "LLM"));
+        let reasons: Vec<MergeReason> = merged.iter().map(|n| n.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                MergeReason::New,
+                MergeReason::Acronym,
+                MergeReason::Rule,
+                MergeReason::Rule
+            ]
+        );
+        assert_eq!(
+            normalizer.canonicalize(&keywords).await.unwrap(),
+            vec!["LLM"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_short_words_are_not_acronyms() {
+        let normalizer = KeywordNormalizer::default();
+        let keywords = strings(&["automated repair tool", "art", "code"]);
+        let merged = normalizer.normalize(&keywords).await.unwrap();
+
+        let canonical: Vec<&str> = merged.iter().map(|n| n.canonical.as_str()).collect();
+        assert_eq!(canonical, vec!["automated repair tool", "art", "code"]);
+        assert_eq!(merged[1].reason, MergeReason::New);
+    }
+
+    #[tokio::test]
+    async fn test_embedding_merge_and_overrides() {
+        let embedder = MockEmbedder::new()
+            .with_vector("transformer", vec![1.0, 0.0])
+            .with_vector("attention network", vec![0.96, 0.28])
+            .with_vector("self attention", vec![0.95, 0.31]);
+        let normalizer = KeywordNormalizerBuilder::default()
+            .embedder(Box::new(embedder))
+            .build()
+            .unwrap();
+
+        normalizer.reject("self attention", "transformer").await;
+        let merged = normalizer
+            .normalize(&strings(&[
+                "Transformers",
+                "attention network",
+                "self-attention",
+            ]))
+            .await
+            .unwrap();
+        assert_eq!(merged[1].canonical, "transformer");
+        assert!(matches!(merged[1].reason, MergeReason::Embedding(s) if s > 0.9));
+        assert_eq!(merged[2].canonical, "self attention");
+
+        normalizer.pin("Attention Networks", "self attention").await;
+        let merged = normalizer
+            .normalize(&strings(&["attention network"]))
+            .await
+            .unwrap();
+        assert_eq!(merged[0].canonical, "self attention");
+        assert_eq!(merged[0].reason, MergeReason::Pinned);
+    }
+
+    #[tokio::test]
+    async fn test_overrides_are_persisted() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        pin_alias(&pool, "NN", "neural network").await.unwrap();
+        reject_alias(&pool, "GAN", "graph attention network")
+            .await
+            .unwrap();
+
+        let normalizer = KeywordNormalizer::default();
+        normalizer.load(&pool).await.unwrap();
+        let merged = normalizer
+            .normalize(&strings(&["NNs", "GAN", "generative adversarial networks"]))
+            .await
+            .unwrap();
+        assert_eq!(merged[0].canonical, "neural network");
+        assert_eq!(merged[1].canonical, "GAN");
+        assert_eq!(merged[2].canonical, "GAN");
+
+        KeywordNormalizer::save(&pool, &merged).await.unwrap();
+        let fresh = KeywordNormalizer::default();
+        fresh.load(&pool).await.unwrap();
+        let merged = fresh
+            .normalize(&strings(&["generative adversarial networks"]))
+            .await
+            .unwrap();
+        assert_eq!(merged[0].canonical, "GAN");
+        assert_eq!(merged[0].reason, MergeReason::Rule);
+    }
+}
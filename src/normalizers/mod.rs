@@ -0,0 +1,41 @@
+use std::{error::Error, fmt::Display};
+
+use langchain_rust::embedding::EmbedderError;
+
+pub mod keyword;
+
+#[derive(Debug)]
+pub enum NormalizerError {
+    EmbedderError(EmbedderError),
+    DatabaseError(sqlx::Error),
+}
+
+impl Display for NormalizerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmbedderError(err) => write!(f, "cannot embed keywords: {}", err),
+            Self::DatabaseError(err) => write!(f, "cannot access keyword aliases: {}", err),
+        }
+    }
+}
+
+impl Error for NormalizerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::EmbedderError(err) => Some(err),
+            Self::DatabaseError(err) => Some(err),
+        }
+    }
+}
+
+impl From<EmbedderError> for NormalizerError {
+    fn from(value: EmbedderError) -> Self {
+        Self::EmbedderError(value)
+    }
+}
+
+impl From<sqlx::Error> for NormalizerError {
+    fn from(value: sqlx::Error) -> Self {
+        Self::DatabaseError(value)
+    }
+}
@@ -0,0 +1,162 @@
+use std::{collections::HashMap, fs, io, path::Path, sync::Arc};
+
+use crate::tools::usage::count_tokens;
+
+/// A prompt with enough identity to tell which one produced a result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub version: String,
+    /// FNV-1a hash of `content`, stable across builds and platforms.
+    pub hash: String,
+    pub content: String,
+    /// Tokens of `content`, placeholders included.
+    pub tokens: u32,
+}
+
+impl PromptTemplate {
+    pub fn new<N: Into<String>, V: Into<String>, C: Into<String>>(
+        name: N,
+        version: V,
+        content: C,
+    ) -> Self {
+        let content = content.into();
+        Self {
+            name: name.into(),
+            version: version.into(),
+            hash: content_hash(&content),
+            tokens: count_tokens(&content),
+            content,
+        }
+    }
+
+    /// Load `<name>.md` (version 1) or `<name>.v<version>.md`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let (name, version) = match stem.rsplit_once(".v") {
+            Some((name, version)) if !version.is_empty() => (name, version),
+            _ => (stem, "1"),
+        };
+        Ok(Self::new(name, version, content))
+    }
+
+    /// `name@version`, the form accepted by [`PromptRegistry::get`].
+    pub fn id(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+fn content_hash(content: &str) -> String {
+    let hash = content.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Prompts loaded once at start-up, keyed by name, each with its versions.
+#[derive(Debug, Default, Clone)]
+pub struct PromptRegistry {
+    prompts: HashMap<String, Vec<Arc<PromptTemplate>>>,
+}
+
+impl PromptRegistry {
+    /// Register every `*.md` file of `dir`.
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let mut registry = Self::default();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "md") {
+                registry.register(PromptTemplate::from_file(&path)?);
+            }
+        }
+        Ok(registry)
+    }
+
+    pub fn register(&mut self, prompt: PromptTemplate) -> Arc<PromptTemplate> {
+        let prompt = Arc::new(prompt);
+        let versions = self.prompts.entry(prompt.name.clone()).or_default();
+        versions.retain(|p| p.version != prompt.version);
+        versions.push(prompt.clone());
+        versions.sort_by(|a, b| compare_versions(&a.version, &b.version));
+        prompt
+    }
+
+    /// Look up `name` (latest version) or `name@version`.
+    pub fn get(&self, id: &str) -> Option<Arc<PromptTemplate>> {
+        let (name, version) = match id.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (id, None),
+        };
+        let versions = self.prompts.get(name)?;
+        match version {
+            Some(version) => versions.iter().find(|p| p.version == version).cloned(),
+            None => versions.last().cloned(),
+        }
+    }
+
+    /// A registered prompt id, or else a path to a prompt file.
+    pub fn resolve(&self, spec: &str) -> io::Result<Arc<PromptTemplate>> {
+        match self.get(spec) {
+            Some(prompt) => Ok(prompt),
+            None => PromptTemplate::from_file(spec).map(Arc::new),
+        }
+    }
+
+    /// All versions of every prompt, sorted by name then version.
+    pub fn prompts(&self) -> Vec<Arc<PromptTemplate>> {
+        let mut names: Vec<_> = self.prompts.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .flat_map(|name| self.prompts[name].iter().cloned())
+            .collect()
+    }
+}
+
+/// Numeric versions compare as numbers, anything else lexically.
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PromptRegistry, PromptTemplate};
+
+    #[test]
+    fn test_prompt_registry() {
+        let mut registry = PromptRegistry::default();
+        registry.register(PromptTemplate::new("topic", "1", "v1 {title}"));
+        registry.register(PromptTemplate::new("topic", "10", "v10 {title}"));
+        registry.register(PromptTemplate::new("topic", "2", "v2 {title}"));
+        registry.register(PromptTemplate::new("summary", "1", "{summary}"));
+
+        assert_eq!(registry.get("topic").unwrap().version, "10");
+        assert_eq!(registry.get("topic@2").unwrap().content, "v2 {title}");
+        assert!(registry.get("topic@3").is_none());
+        assert_eq!(registry.prompts().len(), 4);
+    }
+
+    #[test]
+    fn test_prompt_from_file() {
+        let registry = PromptRegistry::load_dir("prompts").unwrap();
+        let topic = registry.get("topic").unwrap();
+        assert_eq!(topic.id(), "topic@1");
+        assert_eq!(topic.hash.len(), 16);
+        assert_eq!(
+            topic.hash,
+            PromptTemplate::new("other", "9", topic.content.clone()).hash
+        );
+        assert_eq!(
+            registry.resolve("prompts/topic.md").unwrap().hash,
+            topic.hash
+        );
+    }
+}
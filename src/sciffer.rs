@@ -1,10 +1,20 @@
 use crate::config::ScifferConfig;
 use crate::dao::add_paper_with_keywords;
+use crate::dao::{
+    ArtifactDAO, AuthorDAO, FailedExtractionDAO, PaperDAO, PaperSummaryDAO, RelevanceDAO, RunDAO,
+};
+use crate::extracters::artifact::{ArtifactKind, ArxivArtifactData};
 use crate::extracters::topic::ArxivTopicData;
-use crate::models::{Keyword, Paper};
+use crate::models::{
+    Artifact, ArtifactScore, FailedExtraction, Keyword, Paper, PaperExtraction, PaperRelevance,
+    PaperSummary, Run, RunUsage,
+};
+use crate::normalizers::keyword::{rule_key, KeywordNormalizer};
+use crate::tools::rate_limiter::RateLimiter;
+use crate::tools::usage::{ExtractionUsage, UsageSummary};
 use crate::{
     db,
-    extracters::Extracter,
+    extracters::{Extracter, ExtracterError},
     fetchers::{Fetcher, FetcherError},
 };
 use arxiv::Arxiv;
@@ -12,17 +22,26 @@ use async_trait::async_trait;
 use chrono::Local;
 use derive_builder::Builder;
 use futures::{stream::FuturesUnordered, StreamExt};
-use log::{error, info};
-use std::time::Duration;
+use langchain_rust::language_models::TokenUsage;
+use log::{error, info, warn};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::pending;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use std::{
     error::Error,
     fmt::{Debug, Display},
 };
-use tokio::time::sleep;
+use tokio::sync::watch;
+use tokio::time::{sleep, timeout};
 
 #[derive(Debug)]
 enum ScifferError {
     FetcherError(FetcherError),
+    /// `max_cost` is set but the model has no price.
+    Unpriced(String),
     #[allow(dead_code)]
     ExtracterError,
 }
@@ -31,20 +50,100 @@ impl Display for ScifferError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::FetcherError(err) => write!(f, "{:?}", err),
+            Self::Unpriced(model) => write!(
+                f,
+                "max_cost is set but model {} has no price, add it to [prices]",
+                model
+            ),
             Self::ExtracterError => write!(f, "extracter error"),
         }
     }
 }
 impl Error for ScifferError {}
 
+pub type SnifferResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Outcome of a single extraction.
+#[derive(Debug)]
+pub struct Extraction<I, T> {
+    pub input: I,
+    pub result: T,
+    pub elapsed: Duration,
+    pub tokens: Option<TokenUsage>,
+    /// USD, `None` if the model has no price.
+    pub cost: Option<f64>,
+}
+
+/// Everything that happened during one fetch-and-extract cycle.
+#[derive(Debug)]
+pub struct SnifferReport<I, O> {
+    pub successes: Vec<Extraction<I, O>>,
+    pub failures: Vec<Extraction<I, ExtracterError>>,
+    /// Dropped as off-topic by a relevance filter, with their scores.
+    pub filtered: Vec<Extraction<I, f64>>,
+    /// Not extracted before the cycle was cancelled or hit its deadline.
+    pub unfinished: Vec<I>,
+    pub elapsed: Duration,
+    /// Tokens and cost per model.
+    pub usage: UsageSummary,
+}
+
+impl<I, O> SnifferReport<I, O> {
+    pub fn token_usage(&self) -> TokenUsage {
+        let successes = self.successes.iter().filter_map(|e| e.tokens.as_ref());
+        let failures = self.failures.iter().filter_map(|e| e.tokens.as_ref());
+        let filtered = self.filtered.iter().filter_map(|e| e.tokens.as_ref());
+        successes
+            .chain(failures)
+            .chain(filtered)
+            .fold(TokenUsage::default(), |acc, t| acc.sum(t))
+    }
+
+    pub fn into_pairs(self) -> Vec<(I, O)> {
+        self.successes
+            .into_iter()
+            .map(|e| (e.input, e.result))
+            .collect()
+    }
+}
+
+impl<O> Display for SnifferReport<Arxiv, O> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "extracted {} papers, {} failed, {} unfinished",
+            self.successes.len(),
+            self.failures.len(),
+            self.unfinished.len(),
+        )?;
+        if !self.filtered.is_empty() {
+            write!(f, ", {} off-topic", self.filtered.len())?;
+        }
+        writeln!(
+            f,
+            " in {:.1?} ({} tokens, ${:.4})",
+            self.elapsed,
+            self.token_usage().total_tokens,
+            self.usage.total_cost()
+        )?;
+        for failure in self.failures.iter() {
+            writeln!(f, "  [{}] {}", failure.result.kind(), failure.result)?;
+        }
+        Ok(())
+    }
+}
+
 pub trait Sniffer {
     type Input;
     type Output;
     fn sniffer_parallel(
         &self,
-    ) -> impl std::future::Future<
-        Output = Result<Vec<(Self::Input, Self::Output)>, Box<dyn std::error::Error + Send + Sync>>,
-    > + Send;
+    ) -> impl std::future::Future<Output = SnifferResult<Vec<(Self::Input, Self::Output)>>> + Send;
+
+    /// Like `sniffer_parallel`, but also reports failures, timings and token usage.
+    fn sniffer_report(
+        &self,
+    ) -> impl std::future::Future<Output = SnifferResult<SnifferReport<Self::Input, Self::Output>>> + Send;
 }
 
 #[async_trait]
@@ -52,11 +151,248 @@ pub trait SnifferServer: Sniffer {
     async fn start_server(&self) -> Result<(), sqlx::Error>;
 }
 
+/// Cooperative cancellation shared by a sniffer and whoever stops it.
+#[derive(Clone)]
+pub struct Cancellation(Arc<watch::Sender<bool>>);
+
+impl Default for Cancellation {
+    fn default() -> Self {
+        Self(Arc::new(watch::channel(false).0))
+    }
+}
+
+impl Cancellation {
+    pub fn cancel(&self) {
+        self.0.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    pub async fn cancelled(&self) {
+        let mut rx = self.0.subscribe();
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
 #[derive(Builder)]
 pub struct ArxivSciffer<F, E> {
     fetcher: F,
     extracter: E,
+    #[builder(default)]
     config: ScifferConfig,
+    /// Merges synonymous keywords before they are stored.
+    #[builder(default, setter(strip_option))]
+    normalizer: Option<Arc<KeywordNormalizer>>,
+    #[builder(setter(skip))]
+    limiter: OnceLock<Option<RateLimiter>>,
+    #[builder(setter(skip))]
+    cancellation: Cancellation,
+    /// Papers that timed out or were never started, retried next cycle.
+    #[builder(setter(skip))]
+    retry: Mutex<Vec<Arxiv>>,
+    /// Failed attempts per paper id, retries stop at `max_attempts`.
+    #[builder(setter(skip))]
+    attempts: Mutex<HashMap<String, u32>>,
+    /// USD spent across cycles, checked against `max_cost`.
+    #[builder(setter(skip))]
+    spent: Mutex<f64>,
+}
+
+impl<F, E> ArxivSciffer<F, E> {
+    /// Rate limiter of the configured provider, shared across cycles.
+    fn limiter(&self) -> Option<&RateLimiter> {
+        self.limiter
+            .get_or_init(|| self.config.rate_limit().map(RateLimiter::new))
+            .as_ref()
+    }
+
+    /// Handle to stop the sniffer: no new extraction is started, in-flight
+    /// ones finish and their results are returned.
+    pub fn cancellation(&self) -> Cancellation {
+        self.cancellation.clone()
+    }
+
+    /// Cancel on Ctrl-C. Never completes, meant to be raced against the work.
+    pub async fn cancel_on_ctrl_c(&self) {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("received Ctrl-C, finishing in-flight extractions");
+            self.cancellation.cancel();
+        }
+        pending::<()>().await
+    }
+
+    /// Papers waiting to be retried in the next cycle.
+    pub fn pending_retries(&self) -> Vec<Arxiv> {
+        self.retry.lock().unwrap().clone()
+    }
+
+    /// USD spent by all cycles so far.
+    pub fn spent(&self) -> f64 {
+        *self.spent.lock().unwrap()
+    }
+
+    /// Whether `cost` more fits under `max_cost`, cancels the sniffer if not.
+    fn within_budget(&self, cost: f64) -> bool {
+        let Some(max_cost) = self.config.max_cost else {
+            return true;
+        };
+        let spent = self.spent();
+        if spent + cost <= max_cost {
+            return true;
+        }
+        if !self.cancellation.is_cancelled() {
+            warn!(
+                "spending cap of ${:.2} reached (${:.4} spent), stopping extraction",
+                max_cost, spent
+            );
+            self.cancellation.cancel();
+        }
+        false
+    }
+
+    fn charge(&self, cost: f64) {
+        *self.spent.lock().unwrap() += cost;
+        self.within_budget(0.0);
+    }
+
+    /// Count a failed attempt of `paper` and queue it for the next cycle,
+    /// unless it has used up `max_attempts`.
+    fn retry_later(&self, paper: &Arxiv) {
+        let mut attempts = self.attempts.lock().unwrap();
+        let attempt = attempts.entry(paper.id.clone()).or_default();
+        *attempt += 1;
+        if *attempt >= self.config.max_attempts() {
+            warn!("giving up on {} after {} attempts", paper.id, attempt);
+            attempts.remove(&paper.id);
+            return;
+        }
+        drop(attempts);
+        self.queue([paper.clone()]);
+    }
+
+    /// Queue papers for the next cycle without counting an attempt.
+    fn queue<I: IntoIterator<Item = Arxiv>>(&self, papers: I) {
+        let mut retry = self.retry.lock().unwrap();
+        for paper in papers {
+            if !retry.iter().any(|d| d.id == paper.id) {
+                retry.push(paper);
+            }
+        }
+    }
+
+    /// Forget the failed attempts of a paper that needs no retry.
+    fn settled(&self, paper: &Arxiv) {
+        self.attempts.lock().unwrap().remove(&paper.id);
+    }
+}
+
+type ExtractOutcome<O> = (Result<O, ExtracterError>, Option<ExtractionUsage>, Duration);
+
+impl<F, E> ArxivSciffer<F, E>
+where
+    E: Extracter<Input = Arxiv> + Sync,
+{
+    /// Extract a batch planned by the extracter, an outcome per paper.
+    /// Outcomes are `None` if the sniffer was cancelled before the batch started.
+    async fn extract_batch<'a>(
+        &self,
+        batch: &'a [Arxiv],
+    ) -> Vec<(&'a Arxiv, Option<ExtractOutcome<E::Output>>)>
+    where
+        E::Output: Send,
+    {
+        let acquire = async {
+            if let Some(limiter) = self.limiter() {
+                limiter
+                    .acquire_many(
+                        self.extracter.estimate_requests(batch),
+                        self.extracter.estimate_batch_tokens(batch),
+                    )
+                    .await;
+            }
+        };
+        let cancelled = || batch.iter().map(|ctx| (ctx, None)).collect();
+        tokio::select! {
+            biased;
+            _ = self.cancellation.cancelled() => return cancelled(),
+            _ = acquire => {}
+        }
+        // prompt tokens only, priced at the dearest model, the completion is
+        // unknown until it's done, and batches already running are not
+        // counted: `max_cost` can be overshot
+        let tokens = self.extracter.estimate_batch_tokens(batch);
+        let estimate = self
+            .models()
+            .iter()
+            .filter_map(|model| self.config.price(model))
+            .map(|price| price.cost(tokens, 0))
+            .fold(0.0, f64::max);
+        if !self.within_budget(estimate) {
+            return cancelled();
+        }
+
+        let start = Instant::now();
+        let extraction = self.extracter.extract_batch(batch);
+        let results = match self.config.extract_timeout() {
+            Some(limit) => match timeout(limit, extraction).await {
+                Ok(results) => results,
+                Err(_) => batch
+                    .iter()
+                    .map(|ctx| (Err(ExtracterError::Timeout(limit, ctx.id.clone())), None))
+                    .collect(),
+            },
+            None => extraction.await,
+        };
+        let elapsed = start.elapsed();
+        batch
+            .iter()
+            .zip(results)
+            .map(|(ctx, (res, tokens))| (ctx, Some((res, tokens, elapsed))))
+            .collect()
+    }
+
+    /// Fails if `max_cost` is set but a model has no price, the cap could
+    /// not be enforced. Every model of an ensemble or pipeline needs one.
+    pub fn check_budget(&self) -> SnifferResult<()> {
+        if self.config.max_cost.is_none() {
+            return Ok(());
+        }
+        match self
+            .models()
+            .into_iter()
+            .find(|model| self.config.price(model).is_none())
+        {
+            Some(model) => Err(ScifferError::Unpriced(model).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Models the extracter calls, `unknown` without provenance.
+    fn models(&self) -> Vec<String> {
+        match self.extracter.provenance() {
+            Some(provenance) => provenance.models().map(str::to_string).collect(),
+            None => vec!["unknown".to_string()],
+        }
+    }
+
+    /// Split `papers` as planned by the extracter, one paper per batch for
+    /// whatever the plan leaves out.
+    fn batches<'a>(&self, papers: &'a [Arxiv]) -> Vec<&'a [Arxiv]> {
+        let mut batches = Vec::new();
+        let mut rest = papers;
+        for size in self.extracter.plan_batches(papers) {
+            if size == 0 || rest.is_empty() {
+                continue;
+            }
+            let (batch, tail) = rest.split_at(size.min(rest.len()));
+            batches.push(batch);
+            rest = tail;
+        }
+        batches.extend(rest.chunks(1));
+        batches
+    }
 }
 
 impl<F, E> Sniffer for ArxivSciffer<F, E>
@@ -66,35 +402,434 @@ where
 {
     type Input = Arxiv;
     type Output = ArxivTopicData;
-    async fn sniffer_parallel(
-        &self,
-    ) -> Result<Vec<(Self::Input, Self::Output)>, Box<dyn Error + Send + Sync>> {
-        let fetched_data = self
+    async fn sniffer_parallel(&self) -> SnifferResult<Vec<(Self::Input, Self::Output)>> {
+        Ok(self.sniffer_report().await?.into_pairs())
+    }
+
+    async fn sniffer_report(&self) -> SnifferResult<SnifferReport<Self::Input, Self::Output>> {
+        self.check_budget()?;
+        let start = Instant::now();
+        let mut fetched_data = self
             .fetcher
             .fetch()
             .await
-            .map_err(|err| ScifferError::FetcherError(err))?;
-        // let extracted_data = fetched_data.into_iter().map(|ctx| async {}).collect();
+            .map_err(ScifferError::FetcherError)?;
+        let retry = std::mem::take(&mut *self.retry.lock().unwrap());
+        for ctx in retry {
+            if !fetched_data.iter().any(|d| d.id == ctx.id) {
+                fetched_data.push(ctx);
+            }
+        }
+
+        // papers a batch left out are queued again, each as its own batch
+        let mut pending_batches: VecDeque<&[Arxiv]> = self.batches(&fetched_data).into();
+        let mut in_flight = FuturesUnordered::new();
+        let mut outcomes = Vec::new().into_iter();
+        let mut rescheduled: HashSet<&str> = HashSet::new();
 
-        let mut futures = FuturesUnordered::new();
-        for ctx in fetched_data.iter() {
-            let extracter = &self.extracter;
-            futures.push(async move { (ctx.clone(), extracter.extract(&ctx).await) });
+        let deadline = self.config.cycle_deadline();
+        let deadline = async move {
+            match deadline {
+                Some(deadline) => sleep(deadline).await,
+                None => pending().await,
+            }
+        };
+        tokio::pin!(deadline);
+
+        let mut usage = UsageSummary::default();
+        let mut successes = Vec::new();
+        let mut failures = Vec::new();
+        let mut filtered = Vec::new();
+        let mut unfinished: HashSet<&str> = fetched_data.iter().map(|d| d.id.as_str()).collect();
+        loop {
+            let Some((ctx, result)) = outcomes.next() else {
+                // only `max_in_flight` batches are polled at a time
+                while in_flight.len() < self.config.max_in_flight() {
+                    let Some(batch) = pending_batches.pop_front() else {
+                        break;
+                    };
+                    in_flight.push(self.extract_batch(batch));
+                }
+                tokio::select! {
+                    next = in_flight.next() => match next {
+                        Some(next) => outcomes = next.into_iter(),
+                        None => break,
+                    },
+                    _ = &mut deadline => {
+                        warn!("cycle deadline reached, {} papers unfinished", unfinished.len());
+                        break;
+                    }
+                }
+                continue;
+            };
+            let Some((res, spent, elapsed)) = result else {
+                continue;
+            };
+            // each model at its own price
+            let cost = spent.as_ref().and_then(|spent| {
+                spent
+                    .models()
+                    .filter_map(|(model, tokens)| {
+                        usage.add(model, tokens, self.config.price(model))
+                    })
+                    .reduce(|a, b| a + b)
+            });
+            let tokens = spent.as_ref().map(ExtractionUsage::total);
+            self.charge(cost.unwrap_or_default());
+            if matches!(res, Err(ExtracterError::Unanswered(_))) && rescheduled.insert(&ctx.id) {
+                pending_batches.push_back(std::slice::from_ref(ctx));
+                continue;
+            }
+            unfinished.remove(ctx.id.as_str());
+            if !matches!(&res, Err(err) if err.is_transient()) {
+                self.settled(ctx);
+            }
+            match res {
+                Ok(d) => successes.push(Extraction {
+                    input: ctx.clone(),
+                    result: d,
+                    elapsed,
+                    tokens,
+                    cost,
+                }),
+                Err(ExtracterError::Irrelevant(score, id)) => {
+                    info!("dropping off-topic paper {}, relevance {:.2}", id, score);
+                    filtered.push(Extraction {
+                        input: ctx.clone(),
+                        result: score,
+                        elapsed,
+                        tokens,
+                        cost,
+                    });
+                }
+                Err(err) => {
+                    if err.is_transient() {
+                        warn!("{}", err);
+                        self.retry_later(ctx);
+                    } else {
+                        error!("{}", err);
+                    }
+                    failures.push(Extraction {
+                        input: ctx.clone(),
+                        result: err,
+                        elapsed,
+                        tokens,
+                        cost,
+                    });
+                }
+            }
+        }
+        drop(in_flight);
+
+        let unfinished: Vec<Arxiv> = fetched_data
+            .iter()
+            .filter(|d| unfinished.contains(d.id.as_str()))
+            .cloned()
+            .collect();
+        self.queue(unfinished.iter().cloned());
+
+        Ok(SnifferReport {
+            successes,
+            failures,
+            filtered,
+            unfinished,
+            elapsed: start.elapsed(),
+            usage,
+        })
+    }
+}
+
+impl<F, E> ArxivSciffer<F, E>
+where
+    E: Extracter,
+{
+    async fn persist_report(
+        &self,
+        pool: &SqlitePool,
+        report: &SnifferReport<Arxiv, ArxivTopicData>,
+    ) -> Result<(), sqlx::Error> {
+        let date_str = Local::now().to_string();
+        let run = Run {
+            id: 0,
+            created_at: date_str.clone(),
+            elapsed_ms: report.elapsed.as_millis() as i64,
+            successes: report.successes.len() as i64,
+            failures: report.failures.len() as i64,
+            unfinished: report.unfinished.len() as i64,
+        };
+        let run_id = RunDAO::create_run(pool, &run).await?;
+        for usage in report.usage.models() {
+            let usage = RunUsage {
+                run_id,
+                model: usage.model.clone(),
+                extractions: usage.extractions as i64,
+                prompt_tokens: usage.prompt_tokens as i64,
+                completion_tokens: usage.completion_tokens as i64,
+                cost: usage.cost,
+            };
+            RunDAO::add_usage(pool, &usage).await?;
+        }
+
+        let provenance = self.extracter.provenance();
+        for extraction in report.successes.iter() {
+            let meta = extraction.input.clone();
+            let paper = Paper {
+                id: 0,
+                title: meta.title,
+                abstract_text: Some(meta.summary),
+                publish_date: Some(meta.published),
+                insert_date: date_str.clone(),
+                url: meta.pdf_url,
+                comment: meta.comment,
+            };
+
+            let keywords = self.keywords_of(pool, &extraction.result).await;
+            let record = provenance.as_ref().map(|provenance| PaperExtraction {
+                run_id: Some(run_id),
+                prompt_tokens: extraction.tokens.as_ref().map(|t| t.prompt_tokens as i64),
+                completion_tokens: extraction
+                    .tokens
+                    .as_ref()
+                    .map(|t| t.completion_tokens as i64),
+                cost: extraction.cost,
+                output: serde_json::to_string(&extraction.result).ok(),
+                ..provenance.to_extraction(&date_str)
+            });
+
+            let extraction_id =
+                match add_paper_with_keywords(pool, &paper, &keywords, record.as_ref()).await {
+                    Ok(extraction_id) => extraction_id,
+                    Err(err) => {
+                        error!("add_paper_with_keywords error: {:?}", err);
+                        continue;
+                    }
+                };
+            if let Err(err) = AuthorDAO::add_authors(pool, &paper, &meta.authors).await {
+                error!("add_authors error: {:?}", err);
+            }
+            if let Some(summary) = &extraction.result.summary {
+                let summary = PaperSummary {
+                    id: 0,
+                    paper_id: 0,
+                    extraction_id,
+                    tldr: summary.tldr.clone(),
+                    contributions: json!(summary.contributions).to_string(),
+                    results: json!(summary.results).to_string(),
+                    limitations: json!(summary.limitations).to_string(),
+                    created_at: date_str.clone(),
+                };
+                if let Err(err) = PaperSummaryDAO::save_summary(pool, &paper, &summary).await {
+                    error!("save_summary error: {:?}", err);
+                }
+            }
+            if let Some(artifacts) = &extraction.result.artifacts {
+                if let Err(err) =
+                    Self::persist_artifacts(pool, &paper, artifacts, extraction_id).await
+                {
+                    error!("persist_artifacts error: {:?}", err);
+                }
+            }
+            if let Some(relevance) = &extraction.result.relevance {
+                let relevance = PaperRelevance {
+                    arxiv_id: meta.id.clone(),
+                    title: paper.title.clone(),
+                    score: relevance.score,
+                    relevant: relevance.relevant,
+                    kept: true,
+                    run_id: Some(run_id),
+                    created_at: date_str.clone(),
+                };
+                if let Err(err) = RelevanceDAO::save_relevance(pool, &relevance).await {
+                    error!("save_relevance error: {:?}", err);
+                }
+            }
+            if let Err(err) = FailedExtractionDAO::delete_failure(pool, &meta.id).await {
+                error!("delete_failure error: {:?}", err);
+            }
+        }
+
+        for filtered in report.filtered.iter() {
+            let relevance = PaperRelevance {
+                arxiv_id: filtered.input.id.clone(),
+                title: filtered.input.title.clone(),
+                score: filtered.result,
+                relevant: false,
+                kept: false,
+                run_id: Some(run_id),
+                created_at: date_str.clone(),
+            };
+            if let Err(err) = RelevanceDAO::save_relevance(pool, &relevance).await {
+                error!("save_relevance error: {:?}", err);
+            }
+            // a paper retried after a failure may be dropped this time
+            if let Err(err) = FailedExtractionDAO::delete_failure(pool, &filtered.input.id).await {
+                error!("delete_failure error: {:?}", err);
+            }
+        }
+
+        let failures = report.failures.iter().map(|f| {
+            (
+                &f.input,
+                f.result.kind(),
+                f.result.to_string(),
+                f.result.is_transient(),
+            )
+        });
+        let unfinished = report.unfinished.iter().map(|ctx| {
+            (
+                ctx,
+                "unfinished",
+                "not extracted before the cycle ended".to_string(),
+                true,
+            )
+        });
+        for (ctx, kind, error, transient) in failures.chain(unfinished) {
+            let failure = FailedExtraction {
+                id: 0,
+                arxiv_id: ctx.id.clone(),
+                title: ctx.title.clone(),
+                summary: ctx.summary.clone(),
+                publish_date: ctx.published.clone(),
+                url: ctx.pdf_url.clone(),
+                error_kind: kind.to_string(),
+                error,
+                attempts: 1,
+                last_attempt: date_str.clone(),
+                transient,
+            };
+            FailedExtractionDAO::record_failure(pool, &failure).await?;
+        }
+        Ok(())
+    }
+
+    /// Canonical keywords of the techniques of `data` with their ensemble
+    /// confidence. Keywords are kept raw without a normalizer or when
+    /// normalization fails.
+    async fn keywords_of(
+        &self,
+        pool: &SqlitePool,
+        data: &ArxivTopicData,
+    ) -> Vec<(Keyword, Option<f64>)> {
+        let raw = &data.techniques_used;
+        let merged: Vec<(String, String)> = match &self.normalizer {
+            Some(normalizer) => match normalizer.normalize(raw).await {
+                Ok(merged) => {
+                    if let Err(err) = KeywordNormalizer::save(pool, &merged).await {
+                        error!("save keyword aliases error: {}", err);
+                    }
+                    merged.into_iter().map(|n| (n.raw, n.canonical)).collect()
+                }
+                Err(err) => {
+                    warn!(
+                        "keyword normalization failed, keeping raw keywords: {}",
+                        err
+                    );
+                    raw.iter().map(|k| (k.clone(), k.clone())).collect()
+                }
+            },
+            None => raw.iter().map(|k| (k.clone(), k.clone())).collect(),
+        };
+
+        let mut keywords: Vec<(Keyword, Option<f64>)> = Vec::new();
+        for (raw, canonical) in merged {
+            let confidence = data
+                .confidence
+                .as_ref()
+                .map(|_| data.technique_confidence(&raw));
+            match keywords.iter_mut().find(|(k, _)| k.keyword == canonical) {
+                // merged synonyms keep the best agreement among them
+                Some((_, best)) => {
+                    *best = match (*best, confidence) {
+                        (Some(a), Some(b)) => Some(a.max(b)),
+                        (a, b) => a.or(b),
+                    }
+                }
+                None => keywords.push((
+                    Keyword {
+                        id: 0,
+                        keyword: canonical,
+                    },
+                    confidence,
+                )),
+            }
         }
+        keywords
+    }
 
-        let mut res = Vec::new();
-        while let Some(result) = futures.next().await {
-            if let Ok(d) = result.1 {
-                res.push((result.0, d));
-            } else {
-                println!(
-                    "error when processing, arxiv id: {:?} with err {:?}",
-                    result.0.id, result.1
-                );
+    /// Store the artifacts of `paper`, names merged by their [`rule_key`].
+    async fn persist_artifacts(
+        pool: &SqlitePool,
+        paper: &Paper,
+        artifacts: &ArxivArtifactData,
+        extraction_id: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        let paper_id = PaperDAO::create_paper(pool, paper).await?;
+        let create = |kind: ArtifactKind, name: &str| {
+            let artifact = Artifact {
+                id: 0,
+                kind: kind.as_str().to_string(),
+                name: name.to_string(),
+                key: rule_key(name),
+            };
+            async move { ArtifactDAO::create_artifact(pool, &artifact).await }
+        };
+        for kind in ArtifactKind::ALL {
+            for name in artifacts.names(kind) {
+                let artifact_id = create(kind, name).await?;
+                ArtifactDAO::associate_artifact_with_paper(
+                    pool,
+                    paper_id,
+                    artifact_id,
+                    extraction_id,
+                )
+                .await?;
             }
         }
+        for score in artifacts.scores.iter() {
+            let metric_id = match score.metric.as_str() {
+                "" => None,
+                metric => Some(create(ArtifactKind::Metric, metric).await?),
+            };
+            let score = ArtifactScore {
+                id: 0,
+                paper_id,
+                benchmark_id: create(ArtifactKind::Benchmark, &score.benchmark).await?,
+                metric_id,
+                value: score.value.clone(),
+                extraction_id,
+            };
+            ArtifactDAO::add_score(pool, &score).await?;
+        }
+        for url in artifacts.code_urls.iter() {
+            ArtifactDAO::add_code_link(pool, paper_id, url, extraction_id).await?;
+        }
+        Ok(())
+    }
 
-        Ok(res)
+    /// Queue previously failed papers for the next cycle.
+    async fn load_failures(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        let failures =
+            FailedExtractionDAO::list_retryable(pool, self.config.max_attempts() as i64).await?;
+        let mut attempts = self.attempts.lock().unwrap();
+        let papers: Vec<Arxiv> = failures
+            .into_iter()
+            .map(|f| {
+                // the stored count survives restarts, the in-memory one may be ahead
+                let attempt = attempts.entry(f.arxiv_id.clone()).or_default();
+                *attempt = (*attempt).max(f.attempts as u32);
+                Arxiv {
+                    id: f.arxiv_id,
+                    title: f.title,
+                    summary: f.summary,
+                    published: f.publish_date,
+                    pdf_url: f.url,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        drop(attempts);
+        self.queue(papers);
+        Ok(())
     }
 }
 
@@ -105,46 +840,186 @@ where
     E: Extracter<Input = Arxiv, Output = ArxivTopicData> + Sync,
 {
     async fn start_server(&self) -> Result<(), sqlx::Error> {
+        if let Err(err) = self.check_budget() {
+            error!("{}", err);
+            return Ok(());
+        }
         let pool = db::get_db_pool().await?;
-        loop {
-            if let Ok(res) = self.sniffer_parallel().await {
-                let today = Local::now();
-                let date_str = today.to_string();
-                let data = res
-                    .iter()
-                    .map(|(meta, ext_meta)| {
-                        let meta = meta.clone();
-                        let ext_meta = ext_meta;
-                        let paper = Paper {
-                            id: 0,
-                            title: meta.title,
-                            abstract_text: Some(meta.summary),
-                            publish_date: Some(meta.published),
-                            insert_date: date_str.clone(),
-                            url: meta.pdf_url,
-                        };
-
-                        let keywords: Vec<Keyword> = ext_meta
-                            .techniques_used
-                            .iter()
-                            .map(|s| Keyword {
-                                id: 0,
-                                keyword: s.to_string(),
-                            })
-                            .collect();
-                        (paper, keywords)
-                    })
-                    .collect::<Vec<_>>();
+        let serve = async {
+            loop {
+                if let Err(err) = self.load_failures(&pool).await {
+                    error!("load failed extractions error: {:?}", err);
+                }
+                // picks up merges pinned or rejected since the last cycle
+                if let Some(normalizer) = &self.normalizer {
+                    if let Err(err) = normalizer.load(&pool).await {
+                        error!("load keyword aliases error: {}", err);
+                    }
+                }
 
-                for (paper, keywords) in data {
-                    if let Err(err) = add_paper_with_keywords(&pool, &paper, &keywords).await {
-                        error!("add_paper_with_keywords error: {:?}", err);
+                match self.sniffer_report().await {
+                    Ok(report) => {
+                        if let Err(err) = self.persist_report(&pool, &report).await {
+                            error!("persist report error: {:?}", err);
+                        }
+                        info!("add papers finished @ {}: {}", Local::now(), report);
                     }
+                    Err(err) => error!("sniffer error: {}", err),
                 }
 
-                info!("add papers finished @ {}: {:?}", date_str, res);
-                sleep(Duration::from_secs(self.config.time_interval)).await;
+                if self.cancellation.is_cancelled() {
+                    info!("sciffer server stopped");
+                    return Ok(());
+                }
+                tokio::select! {
+                    _ = sleep(Duration::from_secs(self.config.time_interval)) => {}
+                    _ = self.cancellation.cancelled() => {}
+                }
             }
+        };
+
+        tokio::select! {
+            res = serve => res,
+            _ = self.cancel_on_ctrl_c() => Ok(()),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use arxiv::Arxiv;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+
+    use crate::extracters::artifact::ArxivArtifactData;
+    use crate::extracters::topic::{ArxivTopicData, TopicExtracter, TopicExtracterBuilder};
+    use crate::fetchers::arxiv::ArxivFetcher;
+    use crate::llms::mock::MockLlm;
+
+    use super::{ArxivSciffer, ArxivScifferBuilder, Extraction, SnifferReport};
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        pool
+    }
+
+    /// Makes every insert into `table` matching `when` fail.
+    async fn abort_inserts(pool: &SqlitePool, table: &str, when: &str) {
+        sqlx::query(&format!(
+            "CREATE TRIGGER abort_{table} BEFORE INSERT ON {table} WHEN {when}
+             BEGIN SELECT RAISE(ABORT, 'aborted by test'); END"
+        ))
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn count(pool: &SqlitePool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    fn sciffer() -> ArxivSciffer<ArxivFetcher, TopicExtracter> {
+        ArxivScifferBuilder::default()
+            .fetcher(ArxivFetcher::default())
+            .extracter(
+                TopicExtracterBuilder::default()
+                    .llm(Box::new(MockLlm::new()))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    /// Paper `i` with `result`.
+    fn extraction<T>(i: usize, result: T) -> Extraction<Arxiv, T> {
+        Extraction {
+            input: Arxiv {
+                id: format!("http://arxiv.org/abs/2504.{:05}v1", i),
+                title: format!("paper {}", i),
+                pdf_url: format!("https://arxiv.org/pdf/2504.{:05}v1", i),
+                ..Default::default()
+            },
+            result,
+            elapsed: Duration::ZERO,
+            tokens: None,
+            cost: None,
+        }
+    }
+
+    fn report() -> SnifferReport<Arxiv, ArxivTopicData> {
+        SnifferReport {
+            successes: vec![],
+            failures: vec![],
+            filtered: vec![],
+            unfinished: vec![],
+            elapsed: Duration::ZERO,
+            usage: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_persist_report_artifact_failure() {
+        let pool = pool().await;
+        abort_inserts(&pool, "artifacts", "NEW.name = 'broken'").await;
+        let artifacts = |i: usize, benchmark: &str| ArxivTopicData {
+            title: format!("paper {}", i),
+            artifacts: Some(ArxivArtifactData {
+                benchmarks: vec![benchmark.to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let report = SnifferReport {
+            successes: vec![
+                extraction(0, artifacts(0, "Defects4J")),
+                extraction(1, artifacts(1, "broken")),
+                extraction(2, artifacts(2, "SWE-bench")),
+            ],
+            ..report()
+        };
+
+        sciffer().persist_report(&pool, &report).await.unwrap();
+
+        // the paper whose artifacts failed is kept, the others get theirs
+        assert_eq!(count(&pool, "papers").await, 3);
+        assert_eq!(count(&pool, "paper_artifacts").await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_persist_report_relevance_failure() {
+        let pool = pool().await;
+        abort_inserts(&pool, "paper_relevance", "NEW.title = 'paper 1'").await;
+        for i in 0..3 {
+            sqlx::query(
+                "INSERT INTO failed_extractions
+                 (arxiv_id, title, summary, publish_date, url, error_kind, error, last_attempt)
+                 VALUES (?, '', '', '', '', 'timeout', '', '')",
+            )
+            .bind(format!("http://arxiv.org/abs/2504.{:05}v1", i))
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let report = SnifferReport {
+            filtered: (0..3).map(|i| extraction(i, 0.1)).collect(),
+            ..report()
+        };
+
+        sciffer().persist_report(&pool, &report).await.unwrap();
+
+        // the failed relevance row stops neither the others nor the cleanup
+        assert_eq!(count(&pool, "paper_relevance").await, 2);
+        assert_eq!(count(&pool, "failed_extractions").await, 0);
+    }
+}
@@ -0,0 +1,170 @@
+use std::{collections::HashSet, sync::Arc};
+
+use derive_builder::Builder;
+use langchain_rust::embedding::{Embedder, EmbedderError};
+use tokio::sync::OnceCell;
+
+use crate::{normalizers::keyword::rule_key, tools::similarity::cosine};
+
+use super::Taxonomy;
+
+pub const DEFAULT_MIN_SCORE: f64 = 0.5;
+pub const DEFAULT_MAX_MATCHES: usize = 3;
+
+/// A keyword assigned to a taxonomy node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxonomyMatch {
+    pub keyword: String,
+    pub node: String,
+    /// 1.0 for an exact label or synonym match.
+    pub score: f64,
+}
+
+/// Assigns keywords to taxonomy nodes.
+///
+/// A keyword is scored against the label and synonyms of every node by word
+/// overlap of their [`rule_key`]s, and with an embedder by cosine similarity
+/// too, keeping the better of both. Nodes scoring at least `min_score` are
+/// kept, best first, at most `max_matches` of them.
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct TaxonomyMapper {
+    taxonomy: Arc<Taxonomy>,
+    #[builder(default, setter(strip_option))]
+    embedder: Option<Box<dyn Embedder>>,
+    #[builder(default = "DEFAULT_MIN_SCORE")]
+    min_score: f64,
+    #[builder(default = "DEFAULT_MAX_MATCHES")]
+    max_matches: usize,
+    /// Embeddings of every node term, computed on first use.
+    #[builder(setter(skip))]
+    term_embeddings: OnceCell<Vec<(usize, Vec<f64>)>>,
+}
+
+fn overlap(a: &str, b: &str) -> f64 {
+    let a: HashSet<&str> = a.split(' ').collect();
+    let b: HashSet<&str> = b.split(' ').collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        0.0
+    } else {
+        a.intersection(&b).count() as f64 / union as f64
+    }
+}
+
+impl TaxonomyMapper {
+    pub fn taxonomy(&self) -> &Taxonomy {
+        &self.taxonomy
+    }
+
+    /// `(node index, rule key)` of every label and synonym.
+    fn terms(&self) -> Vec<(usize, String)> {
+        self.taxonomy
+            .nodes()
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, node)| {
+                std::iter::once(&node.label)
+                    .chain(node.synonyms.iter())
+                    .map(move |term| (idx, rule_key(term)))
+            })
+            .collect()
+    }
+
+    async fn term_embeddings(
+        &self,
+        embedder: &dyn Embedder,
+    ) -> Result<&[(usize, Vec<f64>)], EmbedderError> {
+        let embeddings = self
+            .term_embeddings
+            .get_or_try_init(|| async {
+                let (nodes, terms): (Vec<usize>, Vec<String>) = self.terms().into_iter().unzip();
+                let vectors = embedder.embed_documents(&terms).await?;
+                Ok::<_, EmbedderError>(nodes.into_iter().zip(vectors).collect())
+            })
+            .await?;
+        Ok(embeddings)
+    }
+
+    pub async fn map(&self, keyword: &str) -> Result<Vec<TaxonomyMatch>, EmbedderError> {
+        let key = rule_key(keyword);
+        let mut scores = vec![0.0f64; self.taxonomy.nodes().len()];
+        for (idx, term) in self.terms() {
+            scores[idx] = scores[idx].max(overlap(&key, &term));
+        }
+        if let Some(embedder) = &self.embedder {
+            let query = embedder.embed_query(&key).await?;
+            for (idx, vector) in self.term_embeddings(embedder.as_ref()).await? {
+                scores[*idx] = scores[*idx].max(cosine(&query, vector));
+            }
+        }
+
+        let mut matches: Vec<TaxonomyMatch> = scores
+            .into_iter()
+            .enumerate()
+            .filter(|(_, score)| *score >= self.min_score)
+            .map(|(idx, score)| TaxonomyMatch {
+                keyword: keyword.to_string(),
+                node: self.taxonomy.nodes()[idx].id.clone(),
+                score,
+            })
+            .collect();
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.node.cmp(&b.node)));
+        matches.truncate(self.max_matches);
+        Ok(matches)
+    }
+
+    /// Matches of every keyword, deduplicated.
+    pub async fn map_all<S: AsRef<str>>(
+        &self,
+        keywords: &[S],
+    ) -> Result<Vec<TaxonomyMatch>, EmbedderError> {
+        let mut seen = HashSet::new();
+        let mut matches = Vec::new();
+        for keyword in keywords.iter().map(|s| s.as_ref()) {
+            if seen.insert(keyword) {
+                matches.extend(self.map(keyword).await?);
+            }
+        }
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{llms::mock::MockEmbedder, taxonomy::Taxonomy};
+
+    use super::TaxonomyMapperBuilder;
+
+    #[tokio::test]
+    async fn test_map_keywords() {
+        let taxonomy = Arc::new(Taxonomy::from_file("taxonomies/acm_ccs.json").unwrap());
+        let mapper = TaxonomyMapperBuilder::default()
+            .taxonomy(taxonomy.clone())
+            .build()
+            .unwrap();
+
+        let matches = mapper.map("Program Repair").await.unwrap();
+        assert_eq!(matches[0].node, "software-testing-and-debugging");
+        assert_eq!(matches[0].score, 1.0);
+        let matches = mapper.map("neural program repair").await.unwrap();
+        assert_eq!(matches[0].node, "software-testing-and-debugging");
+        assert!(matches[0].score < 1.0);
+        assert!(mapper.map("quantum chemistry").await.unwrap().is_empty());
+
+        let embedder = MockEmbedder::new()
+            .with_vector("protein folding", vec![0.0, 1.0])
+            .with_vector("machine learning approach", vec![0.1, 0.9]);
+        let mapper = TaxonomyMapperBuilder::default()
+            .taxonomy(taxonomy)
+            .embedder(Box::new(embedder))
+            .min_score(0.9)
+            .build()
+            .unwrap();
+        let matches = mapper.map("protein folding").await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].node, "machine-learning-approaches");
+    }
+}
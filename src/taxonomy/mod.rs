@@ -0,0 +1,146 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use serde::Deserialize;
+
+pub mod mapper;
+
+/// A concept of a [`Taxonomy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaxonomyNode {
+    pub id: String,
+    pub label: String,
+    /// Other names of the concept, matched like the label.
+    pub synonyms: Vec<String>,
+    pub parent: Option<String>,
+    /// 0 for roots.
+    pub depth: usize,
+}
+
+/// Node of a taxonomy file, children nested in their parent.
+#[derive(Deserialize)]
+struct TaxonomyEntry {
+    id: String,
+    label: String,
+    #[serde(default)]
+    synonyms: Vec<String>,
+    #[serde(default)]
+    children: Vec<TaxonomyEntry>,
+}
+
+/// A fixed concept hierarchy, e.g. an excerpt of the ACM CCS or the arXiv
+/// category tree, that keywords are mapped onto.
+#[derive(Debug, Default, Clone)]
+pub struct Taxonomy {
+    nodes: Vec<TaxonomyNode>,
+    index: HashMap<String, usize>,
+}
+
+impl Taxonomy {
+    /// Load a JSON array of `{"id", "label", "synonyms", "children"}` roots,
+    /// see `taxonomies/acm_ccs.json`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let roots: Vec<TaxonomyEntry> = serde_json::from_str(json)?;
+        let mut taxonomy = Self::default();
+        for root in roots {
+            taxonomy.insert(root, None, 0)?;
+        }
+        Ok(taxonomy)
+    }
+
+    fn insert(
+        &mut self,
+        entry: TaxonomyEntry,
+        parent: Option<String>,
+        depth: usize,
+    ) -> io::Result<()> {
+        if self.index.contains_key(&entry.id) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("duplicate taxonomy node {}", entry.id),
+            ));
+        }
+        self.index.insert(entry.id.clone(), self.nodes.len());
+        self.nodes.push(TaxonomyNode {
+            id: entry.id.clone(),
+            label: entry.label,
+            synonyms: entry.synonyms,
+            parent,
+            depth,
+        });
+        for child in entry.children {
+            self.insert(child, Some(entry.id.clone()), depth + 1)?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&TaxonomyNode> {
+        self.index.get(id).map(|&idx| &self.nodes[idx])
+    }
+
+    /// Every node, parents before their children.
+    pub fn nodes(&self) -> &[TaxonomyNode] {
+        &self.nodes
+    }
+
+    pub fn children<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a TaxonomyNode> {
+        self.nodes
+            .iter()
+            .filter(move |node| node.parent.as_deref() == Some(id))
+    }
+
+    /// `id` and its ancestors, from `id` up to its root.
+    pub fn path(&self, id: &str) -> Vec<&TaxonomyNode> {
+        let mut path = Vec::new();
+        let mut current = self.get(id);
+        while let Some(node) = current {
+            path.push(node);
+            current = node.parent.as_deref().and_then(|parent| self.get(parent));
+        }
+        path
+    }
+
+    /// The ancestor of `id` at `depth`, `id` itself if it is not deeper.
+    pub fn ancestor_at(&self, id: &str, depth: usize) -> Option<&TaxonomyNode> {
+        self.path(id).into_iter().find(|node| node.depth <= depth)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Taxonomy;
+
+    #[test]
+    fn test_taxonomy_hierarchy() {
+        let taxonomy = Taxonomy::from_file("taxonomies/acm_ccs.json").unwrap();
+
+        let path: Vec<&str> = taxonomy
+            .path("software-testing-and-debugging")
+            .iter()
+            .map(|node| node.id.as_str())
+            .collect();
+        assert_eq!(
+            path,
+            vec![
+                "software-testing-and-debugging",
+                "software-verification-and-validation",
+                "software-creation-and-management",
+                "software-and-its-engineering"
+            ]
+        );
+        assert_eq!(
+            taxonomy
+                .ancestor_at("software-testing-and-debugging", 1)
+                .unwrap()
+                .id,
+            "software-creation-and-management"
+        );
+        assert_eq!(taxonomy.children("artificial-intelligence").count(), 3);
+
+        let duplicate = r#"[{"id": "a", "label": "A", "children": [{"id": "a", "label": "A"}]}]"#;
+        assert!(Taxonomy::from_json(duplicate).is_err());
+    }
+}
@@ -1 +1,5 @@
+pub mod language;
 pub mod parser;
+pub mod rate_limiter;
+pub mod similarity;
+pub mod usage;
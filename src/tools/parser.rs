@@ -2,7 +2,7 @@ use regex::Regex;
 use serde_json::Value;
 use std::error::Error as StdError;
 
-pub fn parse_json_md(ctx: &str) -> Result<Value, Box<dyn StdError>> {
+pub fn parse_json_md(ctx: &str) -> Result<Value, Box<dyn StdError + Send + Sync>> {
     let re = Regex::new(r"```json\s*(.*[\s\S]*?)\s*```").unwrap();
     let re_raw = Regex::new(r"\s*(\{.*[\s\S]*\})\s*").unwrap();
 
@@ -21,9 +21,25 @@ pub fn parse_json_md(ctx: &str) -> Result<Value, Box<dyn StdError>> {
     }
 }
 
+/// Like `parse_json_md`, for answers made of a JSON array.
+pub fn parse_json_array_md(ctx: &str) -> Result<Vec<Value>, Box<dyn StdError + Send + Sync>> {
+    let re = Regex::new(r"```json\s*(.*[\s\S]*?)\s*```").unwrap();
+    let re_raw = Regex::new(r"\s*(\[.*[\s\S]*\])\s*").unwrap();
+
+    if let Some(captures) = re.captures(ctx).or(re_raw.captures(ctx)) {
+        let json_str = captures.get(1).unwrap().as_str();
+        match serde_json::from_str(json_str)? {
+            Value::Array(values) => Ok(values),
+            _ => Err("JSON is not an array".into()),
+        }
+    } else {
+        Err("No valid JSON array found".into())
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::parse_json_md;
+    use super::{parse_json_array_md, parse_json_md};
 
     #[test]
     fn test_parse_json_md() {
@@ -43,4 +59,12 @@ mod test {
             Err(e) => println!("Error: {}", e),
         }
     }
+
+    #[test]
+    fn test_parse_json_array_md() {
+        let md_content = "Here you go:\n```json\n[{\"id\": \"1\"}, {\"id\": \"2\"}]\n```";
+        assert_eq!(parse_json_array_md(md_content).unwrap().len(), 2);
+        assert_eq!(parse_json_array_md("[]").unwrap().len(), 0);
+        assert!(parse_json_array_md("{\"id\": \"1\"}").is_err());
+    }
 }
@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use tokio::{
+    sync::Mutex,
+    time::{sleep, Instant},
+};
+
+use crate::config::RateLimitConfig;
+
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    refill_per_sec: f64,
+}
+
+impl Bucket {
+    fn per_minute(limit: u32) -> Self {
+        let capacity = limit as f64;
+        Self {
+            capacity,
+            available: capacity,
+            refill_per_sec: capacity / 60.0,
+        }
+    }
+
+    fn refill(&mut self, elapsed: Duration) {
+        self.available =
+            (self.available + elapsed.as_secs_f64() * self.refill_per_sec).min(self.capacity);
+    }
+
+    /// Time until `amount` is available, clamped to the capacity so an
+    /// oversized request waits for a full bucket instead of forever.
+    fn wait_for(&self, amount: f64) -> Duration {
+        let missing = amount.min(self.capacity) - self.available;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / self.refill_per_sec)
+        }
+    }
+
+    fn take(&mut self, amount: f64) {
+        self.available -= amount.min(self.capacity);
+    }
+}
+
+struct State {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+    last: Instant,
+}
+
+/// Requests-per-minute and tokens-per-minute budget of one LLM provider.
+///
+/// Waiters are served in FIFO order: the head of the queue holds the lock
+/// while it sleeps, so later callers cannot starve it.
+pub struct RateLimiter {
+    state: Mutex<State>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            state: Mutex::new(State {
+                requests: config.requests_per_minute.map(Bucket::per_minute),
+                tokens: config.tokens_per_minute.map(Bucket::per_minute),
+                last: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until one request using `tokens` tokens fits in the budget.
+    pub async fn acquire(&self, tokens: u32) {
+        self.acquire_many(1, tokens).await
+    }
+
+    /// Wait until `requests` requests using `tokens` tokens in total fit in
+    /// the budget.
+    pub async fn acquire_many(&self, requests: u32, tokens: u32) {
+        let mut state = self.state.lock().await;
+        loop {
+            let State {
+                requests: request_bucket,
+                tokens: token_bucket,
+                last,
+            } = &mut *state;
+            let now = Instant::now();
+            let elapsed = now - *last;
+            *last = now;
+
+            let mut buckets = [
+                (request_bucket, requests as f64),
+                (token_bucket, tokens as f64),
+            ];
+            let wait = buckets
+                .iter_mut()
+                .filter_map(|(bucket, amount)| {
+                    bucket.as_mut().map(|b| {
+                        b.refill(elapsed);
+                        b.wait_for(*amount)
+                    })
+                })
+                .max()
+                .unwrap_or(Duration::ZERO);
+
+            if wait.is_zero() {
+                for (bucket, amount) in buckets {
+                    if let Some(b) = bucket.as_mut() {
+                        b.take(amount);
+                    }
+                }
+                return;
+            }
+            sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::config::RateLimitConfig;
+
+    use super::RateLimiter;
+
+    #[tokio::test]
+    async fn test_requests_per_minute() {
+        // 600 rpm: a burst of 600, then one request every 100ms
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            requests_per_minute: Some(600),
+            tokens_per_minute: None,
+        });
+        let start = Instant::now();
+        for _ in 0..602 {
+            limiter.acquire(0).await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(190));
+    }
+
+    #[tokio::test]
+    async fn test_acquire_many() {
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            requests_per_minute: Some(600),
+            tokens_per_minute: None,
+        });
+        let start = Instant::now();
+        limiter.acquire_many(600, 0).await;
+        assert!(start.elapsed() < Duration::from_millis(50));
+        // the burst is spent by a single call
+        limiter.acquire_many(2, 0).await;
+        assert!(start.elapsed() >= Duration::from_millis(190));
+    }
+
+    #[tokio::test]
+    async fn test_tokens_per_minute() {
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            requests_per_minute: None,
+            tokens_per_minute: Some(6000),
+        });
+        let start = Instant::now();
+        limiter.acquire(6000).await;
+        assert!(start.elapsed() < Duration::from_millis(50));
+        // the bucket is empty, 100 tokens refill in one second
+        limiter.acquire(100).await;
+        assert!(start.elapsed() >= Duration::from_millis(990));
+    }
+}
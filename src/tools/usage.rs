@@ -0,0 +1,267 @@
+use std::{collections::BTreeMap, fmt::Display, sync::OnceLock, time::Duration};
+
+use langchain_rust::language_models::TokenUsage;
+use sqlx::SqlitePool;
+use tiktoken_rs::CoreBPE;
+
+use crate::{config::ModelPrice, dao::RunDAO, models::RunUsage};
+
+fn tokenizer() -> Option<&'static CoreBPE> {
+    static BPE: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    BPE.get_or_init(|| tiktoken_rs::cl100k_base().ok()).as_ref()
+}
+
+/// Tokens of `text` with the `cl100k_base` encoding, ~4 characters per
+/// token if the tokenizer cannot be loaded.
+pub fn count_tokens(text: &str) -> u32 {
+    match tokenizer() {
+        Some(bpe) => bpe.encode_with_special_tokens(text).len() as u32,
+        None => (text.len() / 4) as u32,
+    }
+}
+
+/// Usage reported by the backend, or `prompt_tokens` and the counted tokens
+/// of `completion` when it reported none.
+pub fn usage_or_estimate(
+    reported: Option<TokenUsage>,
+    prompt_tokens: u32,
+    completion: &str,
+) -> TokenUsage {
+    reported.unwrap_or_else(|| TokenUsage::new(prompt_tokens, count_tokens(completion)))
+}
+
+/// Tokens one extraction spent per model, ensembles and pipelines call
+/// several.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionUsage {
+    models: BTreeMap<String, TokenUsage>,
+}
+
+impl ExtractionUsage {
+    pub fn new(model: &str, tokens: TokenUsage) -> Self {
+        Self {
+            models: BTreeMap::from([(model.to_string(), tokens)]),
+        }
+    }
+
+    /// Usage of both, model by model.
+    pub fn sum(mut self, other: &ExtractionUsage) -> Self {
+        for (model, tokens) in other.models.iter() {
+            self.models.entry(model.clone()).or_default().add(tokens);
+        }
+        self
+    }
+
+    pub fn models(&self) -> impl Iterator<Item = (&str, &TokenUsage)> {
+        self.models
+            .iter()
+            .map(|(model, tokens)| (model.as_str(), tokens))
+    }
+
+    /// Tokens of all the models.
+    pub fn total(&self) -> TokenUsage {
+        self.models
+            .values()
+            .fold(TokenUsage::default(), |acc, t| acc.sum(t))
+    }
+}
+
+/// Tokens and cost of the extractions done with one model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelUsage {
+    pub model: String,
+    pub extractions: u32,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    /// `None` if the model has no price.
+    pub cost: Option<f64>,
+}
+
+impl ModelUsage {
+    pub fn total_tokens(&self) -> u32 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+/// Usage aggregated per model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageSummary {
+    models: BTreeMap<String, ModelUsage>,
+}
+
+impl UsageSummary {
+    /// Count one extraction, returns its cost.
+    pub fn add(
+        &mut self,
+        model: &str,
+        usage: &TokenUsage,
+        price: Option<&ModelPrice>,
+    ) -> Option<f64> {
+        let cost = price.map(|p| p.cost(usage.prompt_tokens, usage.completion_tokens));
+        let entry = self
+            .models
+            .entry(model.to_string())
+            .or_insert_with(|| ModelUsage {
+                model: model.to_string(),
+                cost: price.map(|_| 0.0),
+                ..Default::default()
+            });
+        entry.extractions += 1;
+        entry.prompt_tokens += usage.prompt_tokens;
+        entry.completion_tokens += usage.completion_tokens;
+        if let (Some(total), Some(cost)) = (entry.cost.as_mut(), cost) {
+            *total += cost;
+        }
+        cost
+    }
+
+    pub fn models(&self) -> impl Iterator<Item = &ModelUsage> {
+        self.models.values()
+    }
+
+    pub fn total_tokens(&self) -> u32 {
+        self.models().map(ModelUsage::total_tokens).sum()
+    }
+
+    /// Cost of the priced models.
+    pub fn total_cost(&self) -> f64 {
+        self.models().filter_map(|m| m.cost).sum()
+    }
+}
+
+impl FromIterator<ModelUsage> for UsageSummary {
+    fn from_iter<T: IntoIterator<Item = ModelUsage>>(iter: T) -> Self {
+        Self {
+            models: iter.into_iter().map(|m| (m.model.clone(), m)).collect(),
+        }
+    }
+}
+
+impl From<RunUsage> for ModelUsage {
+    fn from(usage: RunUsage) -> Self {
+        Self {
+            model: usage.model,
+            extractions: usage.extractions as u32,
+            prompt_tokens: usage.prompt_tokens as u32,
+            completion_tokens: usage.completion_tokens as u32,
+            cost: usage.cost,
+        }
+    }
+}
+
+/// A stored sniffer cycle.
+#[derive(Debug, Clone)]
+pub struct RunSummary {
+    pub created_at: String,
+    pub elapsed: Duration,
+    pub successes: i64,
+    pub failures: i64,
+    pub unfinished: i64,
+    pub usage: UsageSummary,
+}
+
+/// The latest `limit` runs, newest first.
+pub async fn recent_runs(pool: &SqlitePool, limit: i64) -> Result<Vec<RunSummary>, sqlx::Error> {
+    let mut runs = Vec::new();
+    for run in RunDAO::list_runs(pool, limit).await? {
+        let usage = RunDAO::list_usage(pool, run.id).await?;
+        runs.push(RunSummary {
+            created_at: run.created_at,
+            elapsed: Duration::from_millis(run.elapsed_ms as u64),
+            successes: run.successes,
+            failures: run.failures,
+            unfinished: run.unfinished,
+            usage: usage.into_iter().map(ModelUsage::from).collect(),
+        });
+    }
+    Ok(runs)
+}
+
+/// Usage per model over the runs stored since `since`, e.g. `2025-03-01`.
+pub async fn usage_since(pool: &SqlitePool, since: &str) -> Result<UsageSummary, sqlx::Error> {
+    Ok(RunDAO::usage_by_model(pool, since)
+        .await?
+        .into_iter()
+        .map(ModelUsage::from)
+        .collect())
+}
+
+impl Display for UsageSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<32} {:>11} {:>13} {:>17} {:>10}",
+            "model", "extractions", "prompt tokens", "completion tokens", "cost"
+        )?;
+        for m in self.models() {
+            let cost = m
+                .cost
+                .map(|c| format!("${:.4}", c))
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(
+                f,
+                "{:<32} {:>11} {:>13} {:>17} {:>10}",
+                m.model, m.extractions, m.prompt_tokens, m.completion_tokens, cost
+            )?;
+        }
+        write!(
+            f,
+            "{} tokens, ${:.4} total",
+            self.total_tokens(),
+            self.total_cost()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use langchain_rust::language_models::TokenUsage;
+
+    use crate::config::ModelPrice;
+
+    use super::{count_tokens, usage_or_estimate, ExtractionUsage, UsageSummary};
+
+    #[test]
+    fn test_usage_summary() {
+        let price = ModelPrice {
+            prompt: 1.0,
+            completion: 2.0,
+        };
+        let mut summary = UsageSummary::default();
+        let cost = summary.add("gpt", &TokenUsage::new(1000, 500), Some(&price));
+        assert_eq!(cost, Some(0.002));
+        summary.add("gpt", &TokenUsage::new(1000, 500), Some(&price));
+        assert_eq!(summary.add("local", &TokenUsage::new(10, 10), None), None);
+
+        let models: Vec<_> = summary.models().collect();
+        assert_eq!(models[0].model, "gpt");
+        assert_eq!(models[0].extractions, 2);
+        assert_eq!(models[0].cost, Some(0.004));
+        assert_eq!(models[1].cost, None);
+        assert_eq!(summary.total_tokens(), 3020);
+        assert!((summary.total_cost() - 0.004).abs() < 1e-12);
+        assert!(summary.to_string().ends_with("3020 tokens, $0.0040 total"));
+    }
+
+    #[test]
+    fn test_extraction_usage() {
+        let usage = ExtractionUsage::new("a", TokenUsage::new(10, 5))
+            .sum(&ExtractionUsage::new("b", TokenUsage::new(1, 1)))
+            .sum(&ExtractionUsage::new("a", TokenUsage::new(10, 5)));
+        let models: Vec<_> = usage
+            .models()
+            .map(|(model, t)| (model, t.prompt_tokens, t.completion_tokens))
+            .collect();
+        assert_eq!(models, vec![("a", 20, 10), ("b", 1, 1)]);
+        assert_eq!(usage.total().total_tokens, 32);
+    }
+
+    #[test]
+    fn test_usage_or_estimate() {
+        assert_eq!(count_tokens("hello world"), 2);
+        let usage = usage_or_estimate(None, 2, "hi");
+        assert_eq!((usage.prompt_tokens, usage.completion_tokens), (2, 1));
+        let usage = usage_or_estimate(Some(TokenUsage::new(7, 3)), 2, "hi");
+        assert_eq!(usage.total_tokens, 10);
+    }
+}
@@ -0,0 +1,58 @@
+//! Builders shared by the integration tests, each test crate uses some.
+#![allow(dead_code)]
+
+use arxiv::Arxiv;
+use sciffer_rs::extracters::topic::ArxivTopicData;
+use serde_json::json;
+
+/// Paper `i` of April 2025, titled `paper i`.
+pub fn paper(i: usize) -> Arxiv {
+    Arxiv {
+        id: format!("http://arxiv.org/abs/2504.{:05}v1", i),
+        title: format!("paper {}", i),
+        ..Default::default()
+    }
+}
+
+/// Paper `i` titled `title`, with a one-sentence abstract.
+pub fn titled(i: usize, title: &str) -> Arxiv {
+    Arxiv {
+        title: title.to_string(),
+        summary: format!("The abstract of {}.", title),
+        ..paper(i)
+    }
+}
+
+/// A paper published at noon on `day`, e.g. `2025-04-01`.
+pub fn dated(day: &str) -> Arxiv {
+    Arxiv {
+        published: format!("{}T12:00:00Z", day),
+        ..Default::default()
+    }
+}
+
+/// Owned copies of `items`.
+pub fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+/// `raw` with the problems and techniques extracted from it.
+pub fn extracted(raw: Arxiv, problems: &[&str], techniques: &[&str]) -> (Arxiv, ArxivTopicData) {
+    let ctx = ArxivTopicData {
+        solved_problem: strings(problems),
+        techniques_used: strings(techniques),
+        ..Default::default()
+    };
+    (raw, ctx)
+}
+
+/// Answer to the topic prompt for a paper titled `title`.
+pub fn topic_json(title: &str, techniques: &[&str]) -> String {
+    json!({
+        "title": title,
+        "solved_problem": ["p"],
+        "research_field": ["f"],
+        "techniques_used": techniques,
+    })
+    .to_string()
+}
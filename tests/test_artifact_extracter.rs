@@ -0,0 +1,106 @@
+use arxiv::Arxiv;
+use sciffer_rs::analyzers::simple::SimpleArixvTrendingAnalyzerBuilder;
+use sciffer_rs::analyzers::TrendingAnalyzer;
+use sciffer_rs::extracters::artifact::{ArtifactExtracterBuilder, ArtifactKind};
+use sciffer_rs::extracters::pipeline::{PipelineExtracterBuilder, TopicStage};
+use sciffer_rs::extracters::topic::TopicExtracterBuilder;
+use sciffer_rs::extracters::Extracter;
+use sciffer_rs::llms::mock::{MockLlm, MockResponse};
+
+mod common;
+use common::topic_json;
+
+const ARTIFACT_JSON: &str = r#"```json
+{
+  "title": "Paper",
+  "datasets": ["The Stack"],
+  "benchmarks": ["HumanEval", "humaneval."],
+  "metrics": [],
+  "scores": [{"benchmark": "MBPP", "metric": "pass@1", "value": "71.2%"}],
+  "code_urls": ["https://GitHub.com/org/repo/"]
+}
+```"#;
+
+/// A paper linking its code and data in its arXiv comment.
+fn paper(i: usize) -> Arxiv {
+    Arxiv {
+        summary: "We generate code.".to_string(),
+        comment: Some(
+            "10 pages. Code: https://github.com/org/repo.git, data at https://hf.co/org/data."
+                .to_string(),
+        ),
+        ..common::paper(i)
+    }
+}
+
+#[tokio::test]
+async fn test_artifact_extracter() {
+    let llm = MockLlm::new().with_default(MockResponse::text(ARTIFACT_JSON));
+    let extracter = ArtifactExtracterBuilder::default()
+        .llm(Box::new(llm))
+        .model("mock")
+        .build()
+        .unwrap();
+
+    let (res, usage) = extracter.extract_with_usage(&paper(0)).await;
+    let artifacts = res.unwrap();
+    assert_eq!(artifacts.datasets, vec!["The Stack"]);
+    assert_eq!(artifacts.benchmarks, vec!["HumanEval", "MBPP"]);
+    assert_eq!(artifacts.metrics, vec!["pass@1"]);
+    assert_eq!(artifacts.scores[0].value, "71.2%");
+    // the comment's links are merged with the model's
+    assert_eq!(
+        artifacts.code_urls,
+        vec!["https://github.com/org/repo", "https://hf.co/org/data"]
+    );
+    assert!(usage.unwrap().total().prompt_tokens > 0);
+    assert!(artifacts.to_string().contains("MBPP pass@1: 71.2%"));
+}
+
+#[tokio::test]
+async fn test_artifacts_top_k() {
+    // the artifact prompt is the only one talking about evaluation artifacts
+    let llm = MockLlm::new()
+        .with_fixture("evaluation artifacts", MockResponse::text(ARTIFACT_JSON))
+        .with_default(MockResponse::text(topic_json("paper", &["t"])));
+    let extracter = PipelineExtracterBuilder::default()
+        .stage(TopicStage::new(
+            TopicExtracterBuilder::default()
+                .llm(Box::new(llm.clone()))
+                .model("mock")
+                .build()
+                .unwrap(),
+        ))
+        .stage(
+            ArtifactExtracterBuilder::default()
+                .llm(Box::new(llm))
+                .model("other")
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+
+    let provenance = extracter.provenance().unwrap();
+    assert_eq!(provenance.extracter, "topic+artifact");
+    assert_eq!(provenance.model, "mock+other");
+
+    let papers = [paper(0), paper(1)];
+    let mut data = Vec::new();
+    for (paper, (res, usage)) in papers.iter().zip(extracter.extract_batch(&papers).await) {
+        let topics = res.unwrap();
+        assert_eq!(topics.title, "paper");
+        assert!(usage.is_some());
+        data.push((paper.clone(), topics));
+    }
+
+    let top = SimpleArixvTrendingAnalyzerBuilder::default()
+        .build()
+        .unwrap()
+        .top_k(&data, |d| d.artifact_names(ArtifactKind::Benchmark), 10);
+    let top: Vec<(&str, usize)> = top
+        .iter()
+        .map(|t| (t.keyword.as_str(), t.papers.len()))
+        .collect();
+    assert_eq!(top, vec![("MBPP", 2), ("HumanEval", 2)]);
+}
@@ -0,0 +1,93 @@
+use arxiv::Arxiv;
+use chrono::NaiveDate;
+use sciffer_rs::analyzers::author::{authored_of, AuthorAnalyzerBuilder, AuthorIndex};
+use sciffer_rs::analyzers::graph::EdgeWeight;
+use sciffer_rs::analyzers::trend::Granularity;
+use sciffer_rs::extracters::topic::ArxivTopicData;
+
+mod common;
+use common::{dated, extracted, strings};
+
+fn paper(day: &str, authors: &[&str], techniques: &[&str]) -> (Arxiv, ArxivTopicData) {
+    let raw = Arxiv {
+        authors: strings(authors),
+        ..dated(day)
+    };
+    extracted(raw, &[], techniques)
+}
+
+/// A fuzzing group turning to LLMs in April, joined there by a newcomer, and
+/// an unrelated verification group.
+fn index() -> AuthorIndex {
+    let data = vec![
+        paper(
+            "2025-03-03",
+            &["Andreas Zeller", "Marcel Böhme"],
+            &["fuzzing"],
+        ),
+        paper("2025-03-10", &["A. Zeller", "M. Böhme"], &["fuzzing"]),
+        paper("2025-03-17", &["Marcel Bohme"], &["fuzzing", "grammars"]),
+        paper("2025-04-07", &["Andreas Zeller", "Lin Tan"], &["LLM"]),
+        paper("2025-04-14", &["Lin Tan", "A. Zeller"], &["LLM", "fuzzing"]),
+        paper("2025-04-21", &["Lin Tan", "Marcel Böhme"], &["LLM"]),
+        paper("2025-03-05", &["Leslie Lamport", "Markus Kuppe"], &["TLA+"]),
+        paper("2025-04-05", &["L. Lamport", "Markus Kuppe"], &["TLA+"]),
+    ];
+    AuthorIndex::new(authored_of(&data, |d| d.techniques_used.clone()))
+}
+
+#[test]
+fn test_top_authors_and_entrants() {
+    let index = index();
+    assert_eq!(index.authors().len(), 5);
+    let zeller = &index.authors()[index.find("A. Zeller").unwrap()];
+    assert_eq!(zeller.name, "Andreas Zeller");
+    assert_eq!(zeller.papers, 4);
+
+    let analyzer = AuthorAnalyzerBuilder::default().build().unwrap();
+    let top = analyzer.top_authors(&index, "fuzzing");
+    let names: Vec<&str> = top.iter().map(|a| a.name.as_str()).collect();
+    // as many papers, more of them as first author
+    assert_eq!(names, vec!["Andreas Zeller", "Marcel Böhme"]);
+    assert_eq!(top[1].papers, 3);
+    assert_eq!(top[0].first_author, 2);
+
+    let april = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+    let entrants = analyzer.new_entrants(&index, "LLM", april);
+    let names: Vec<&str> = entrants.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["Lin Tan", "Andreas Zeller"]);
+    assert_eq!(entrants[0].prior_papers, 0);
+    assert_eq!(entrants[1].prior_papers, 2);
+    assert_eq!(
+        entrants[0].to_string(),
+        "Lin Tan: since 2025-04-07, 3 papers, 0 before"
+    );
+}
+
+#[test]
+fn test_collaborations_and_drift() {
+    let index = index();
+    let analyzer = AuthorAnalyzerBuilder::default()
+        .granularity(Granularity::Month)
+        .build()
+        .unwrap();
+
+    let graph = analyzer.collaborations(&index, EdgeWeight::Count);
+    let mut communities = graph.communities();
+    communities.iter_mut().for_each(|c| c.sort());
+    communities.sort();
+    assert_eq!(
+        communities,
+        vec![
+            vec!["Andreas Zeller", "Lin Tan", "Marcel Böhme"],
+            vec!["Leslie Lamport", "Markus Kuppe"],
+        ]
+    );
+
+    let drifts = analyzer.drift(&index);
+    assert_eq!(drifts[0].name, "Marcel Böhme");
+    assert_eq!(drifts[0].drift, 1.0);
+    assert_eq!(drifts[0].windows.len(), 2);
+    let lamport = drifts.iter().find(|d| d.name == "Leslie Lamport").unwrap();
+    assert!(lamport.drift.abs() < 1e-9);
+}
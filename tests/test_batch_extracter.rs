@@ -0,0 +1,154 @@
+use arxiv::Arxiv;
+use sciffer_rs::extracters::batch::{default_topic_batch_prompt, BatchTopicExtracterBuilder};
+use sciffer_rs::extracters::topic::TopicExtracterBuilder;
+use sciffer_rs::extracters::Extracter;
+use sciffer_rs::llms::mock::{MockLlm, MockResponse};
+
+mod common;
+use common::topic_json;
+
+/// A paper with an abstract of about `summary_len / 4` tokens.
+fn paper(i: usize, title: &str, summary_len: usize) -> Arxiv {
+    Arxiv {
+        summary: "a".repeat(summary_len),
+        ..common::titled(i, title)
+    }
+}
+
+#[tokio::test]
+async fn test_batch_reports_missing_papers() {
+    let papers = vec![
+        paper(1, "Alpha", 10),
+        paper(2, "Beta", 10),
+        paper(3, "Gamma", 10),
+    ];
+    let batch_answer = r#"```json
+[
+  {"id": "2504.00002v1", "title": "Beta", "solved_problem": [], "research_field": [], "techniques_used": ["Beta technique"]},
+  {"id": "2504.00001v1", "title": "Alpha", "solved_problem": [], "research_field": [], "techniques_used": ["Alpha technique"]}
+]
+```"#;
+    let llm = MockLlm::new()
+        .with_fixture("Id: 2504.00001v1", MockResponse::text(batch_answer))
+        .with_fixture(
+            "Paper: \"Gamma\"",
+            MockResponse::text(topic_json("Gamma", &["Gamma technique"])),
+        );
+    let single = TopicExtracterBuilder::default()
+        .llm(Box::new(llm.clone()))
+        .model("mock")
+        .build()
+        .unwrap();
+    let extracter = BatchTopicExtracterBuilder::default()
+        .single(single)
+        .build()
+        .unwrap();
+
+    assert_eq!(extracter.plan_batches(&papers), vec![3]);
+    let results = extracter.extract_batch(&papers).await;
+    let techniques: Vec<String> = results[..2]
+        .iter()
+        .map(|(res, _)| res.as_ref().unwrap().techniques_used[0].clone())
+        .collect();
+    assert_eq!(techniques, vec!["Alpha technique", "Beta technique"]);
+    // the missed paper is left to the sniffer, which extracts it alone
+    let missed = results[2].0.as_ref().unwrap_err();
+    assert_eq!(missed.kind(), "unanswered");
+    assert!(missed.is_transient());
+    assert_eq!(llm.call_count(), 1);
+    assert!(llm.calls()[0].contains("Id: 2504.00003v1"));
+
+    let (res, _) = extracter.extract_batch(&papers[2..]).await.remove(0);
+    assert_eq!(res.unwrap().techniques_used, vec!["Gamma technique"]);
+    assert_eq!(llm.call_count(), 2);
+    assert_eq!(extracter.provenance().unwrap().prompt, "topic_batch");
+}
+
+#[tokio::test]
+async fn test_batch_failure_leaves_every_paper() {
+    let papers = vec![paper(1, "Alpha", 10), paper(2, "Beta", 10)];
+    let llm = MockLlm::new()
+        .with_fixture("Id: 2504.00001v1", MockResponse::text("sorry, no JSON"))
+        .with_fixture(
+            "Paper: \"Alpha\"",
+            MockResponse::text(topic_json("Alpha", &["Alpha technique"])),
+        )
+        .with_fixture("Paper: \"Beta\"", MockResponse::error("boom"));
+    let extracter = BatchTopicExtracterBuilder::default()
+        .single(
+            TopicExtracterBuilder::default()
+                .llm(Box::new(llm.clone()))
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+
+    let results = extracter.extract_batch(&papers).await;
+    assert!(results
+        .iter()
+        .all(|(res, _)| res.as_ref().unwrap_err().kind() == "unanswered"));
+    // the unusable answer is still paid for
+    assert!(results[0].1.is_some());
+    assert_eq!(llm.call_count(), 1);
+
+    let results = extracter.extract_batch(&papers[1..]).await;
+    assert_eq!(results[0].0.as_ref().unwrap_err().kind(), "chain");
+}
+
+#[tokio::test]
+async fn test_batch_call_failure_keeps_its_class() {
+    let papers = vec![paper(1, "Alpha", 10), paper(2, "Beta", 10)];
+    for (response, transient) in [
+        (MockResponse::unreachable("connection refused"), true),
+        (MockResponse::error("bad request"), false),
+    ] {
+        let llm = MockLlm::new().with_fixture("Id: 2504.00001v1", response);
+        let extracter = BatchTopicExtracterBuilder::default()
+            .single(
+                TopicExtracterBuilder::default()
+                    .llm(Box::new(llm.clone()))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        // each paper fails as the call did, none is left to `single`
+        let results = extracter.extract_batch(&papers).await;
+        for ((res, usage), paper) in results.iter().zip(&papers) {
+            let err = res.as_ref().unwrap_err();
+            assert_ne!(err.kind(), "unanswered");
+            assert_eq!(err.is_transient(), transient);
+            assert_eq!(err.paper_id(), paper.id);
+            assert!(usage.is_none());
+        }
+        assert_eq!(llm.call_count(), 1);
+    }
+}
+
+#[test]
+fn test_plan_batches() {
+    let single = TopicExtracterBuilder::default()
+        .llm(Box::new(MockLlm::new()))
+        .build()
+        .unwrap();
+    let papers: Vec<Arxiv> = (0..5).map(|i| paper(i, "Paper", 400)).collect();
+
+    let by_size = BatchTopicExtracterBuilder::default()
+        .single(single.clone())
+        .max_batch_size(2)
+        .build()
+        .unwrap();
+    assert_eq!(by_size.plan_batches(&papers), vec![2, 2, 1]);
+
+    // room for the prompt and about two 100-token papers
+    let budget = default_topic_batch_prompt().content.len() as u32 / 4 + 250;
+    let by_budget = BatchTopicExtracterBuilder::default()
+        .single(single)
+        .token_budget(budget)
+        .build()
+        .unwrap();
+    assert_eq!(by_budget.plan_batches(&papers), vec![2, 2, 1]);
+    assert!(by_budget.estimate_batch_tokens(&papers[..2]) <= budget);
+}
@@ -0,0 +1,65 @@
+use arxiv::Arxiv;
+use chrono::{Days, NaiveDate};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use sciffer_rs::analyzers::burst::{daily_totals_of, BurstDetectorBuilder};
+use sciffer_rs::analyzers::trend::daily_counts_of;
+use sciffer_rs::extracters::topic::ArxivTopicData;
+
+const BACKGROUND: [&str; 4] = [
+    "large language models",
+    "reinforcement learning",
+    "program debugging",
+    "image recognition",
+];
+
+/// A paper published on `day` using a random background technique and, with
+/// probability `p`, "diffusion".
+fn generate_paper(day: NaiveDate, i: usize, p: f64) -> (Arxiv, ArxivTopicData) {
+    let mut rng = rand::thread_rng();
+    let mut techniques = vec![BACKGROUND.choose(&mut rng).unwrap().to_string()];
+    if rng.gen_bool(p) {
+        techniques.push("diffusion".to_string());
+    }
+    let raw = Arxiv {
+        id: format!("{}-{}", day, i),
+        published: format!("{}T{:02}:00:00Z", day, rng.gen_range(0..24)),
+        ..Default::default()
+    };
+    let ctx = ArxivTopicData {
+        techniques_used: techniques,
+        ..Default::default()
+    };
+    (raw, ctx)
+}
+
+#[test]
+fn test_burst_detection() {
+    let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    let mut data = Vec::new();
+    for day in 0..90 {
+        let date = start + Days::new(day);
+        // diffusion is rare, except for a week in February
+        let p = if (40..47).contains(&day) { 0.6 } else { 0.02 };
+        for i in 0..30 {
+            data.push(generate_paper(date, i, p));
+        }
+    }
+
+    let counts = daily_counts_of(&data, |d| d.techniques_used.clone());
+    let totals = daily_totals_of(&data);
+    let bursts = BurstDetectorBuilder::default()
+        .build()
+        .unwrap()
+        .detect(&counts, &totals);
+
+    let burst = &bursts[0];
+    assert_eq!(burst.keyword, "diffusion");
+    assert!((start + Days::new(38)..=start + Days::new(41)).contains(&burst.start));
+    assert!((start + Days::new(45)..=start + Days::new(48)).contains(&burst.end));
+    assert!(burst.papers > 50);
+    // the background techniques are steady
+    assert!(bursts
+        .iter()
+        .all(|b| b.keyword == "diffusion" || b.intensity < burst.intensity / 4.0));
+}
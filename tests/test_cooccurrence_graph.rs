@@ -0,0 +1,83 @@
+use arxiv::Arxiv;
+use sciffer_rs::analyzers::graph::{CooccurrenceAnalyzerBuilder, GraphFormat};
+use sciffer_rs::extracters::topic::ArxivTopicData;
+
+mod common;
+use common::extracted;
+
+fn paper(i: usize, problems: &[&str], techniques: &[&str]) -> (Arxiv, ArxivTopicData) {
+    extracted(common::paper(i), problems, techniques)
+}
+
+/// Two research clusters sharing no keyword.
+fn data() -> Vec<(Arxiv, ArxivTopicData)> {
+    let mut data = Vec::new();
+    for i in 0..6 {
+        data.push(paper(
+            i,
+            &["program repair"],
+            &["LLM", "fault localization"],
+        ));
+        data.push(paper(
+            10 + i,
+            &["image segmentation"],
+            &["diffusion", "U-Net & co"],
+        ));
+    }
+    data.push(paper(20, &["program repair"], &["genetic programming"]));
+    data
+}
+
+#[test]
+fn test_cooccurrence_communities() {
+    let graph = CooccurrenceAnalyzerBuilder::default()
+        .build()
+        .unwrap()
+        .graph_of(&data(), |d| {
+            d.solved_problem
+                .iter()
+                .chain(d.techniques_used.iter())
+                .cloned()
+                .collect()
+        });
+
+    // genetic programming is in a single paper
+    assert_eq!(graph.nodes.len(), 6);
+    let mut communities = graph.communities();
+    for community in communities.iter_mut() {
+        community.sort();
+    }
+    communities.sort();
+    assert_eq!(
+        communities,
+        vec![
+            vec!["LLM", "fault localization", "program repair"],
+            vec!["U-Net & co", "diffusion", "image segmentation"],
+        ]
+    );
+    assert!(graph.edges.iter().all(|e| e.papers == 6 && e.weight > 0.0));
+    assert!(graph.nodes.iter().all(|n| n.degree == 0.4));
+}
+
+#[test]
+fn test_cooccurrence_export() {
+    let graph = CooccurrenceAnalyzerBuilder::default()
+        .build()
+        .unwrap()
+        .graph_of(&data(), |d| d.techniques_used.clone());
+
+    let graphml = graph.export(GraphFormat::GraphMl);
+    assert!(graphml.starts_with("<?xml"));
+    assert!(graphml.contains("<data key=\"label\">U-Net &amp; co</data>"));
+    assert_eq!(graphml.matches("<node ").count(), 4);
+    assert_eq!(graphml.matches("<edge ").count(), 2);
+
+    let gexf = graph.export(GraphFormat::Gexf);
+    assert!(gexf.contains("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">"));
+    assert!(gexf.contains("label=\"U-Net &amp; co\""));
+    assert_eq!(gexf.matches("<edge ").count(), 2);
+
+    let json: serde_json::Value = serde_json::from_str(&graph.export(GraphFormat::Json)).unwrap();
+    assert_eq!(json["nodes"].as_array().unwrap().len(), 4);
+    assert_eq!(json["edges"][0]["papers"], 6);
+}
@@ -1,6 +1,7 @@
+#![allow(dead_code)]
+
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use serde_json;
 use std::collections::HashMap;
 use std::error::Error;
 
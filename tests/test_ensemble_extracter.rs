@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use arxiv::Arxiv;
+use sciffer_rs::extracters::ensemble::EnsembleExtracterBuilder;
+use sciffer_rs::extracters::topic::{TopicExtracter, TopicExtracterBuilder};
+use sciffer_rs::extracters::Extracter;
+use sciffer_rs::llms::mock::{MockLlm, MockResponse};
+use sciffer_rs::normalizers::keyword::KeywordNormalizer;
+
+mod common;
+use common::topic_json;
+
+fn member(model: &str, techniques: &[&str]) -> TopicExtracter {
+    let json = topic_json("Paper", techniques);
+    TopicExtracterBuilder::default()
+        .llm(Box::new(
+            MockLlm::new().with_default(MockResponse::text(json)),
+        ))
+        .model(model)
+        .build()
+        .unwrap()
+}
+
+fn paper() -> Arxiv {
+    Arxiv {
+        title: "Paper".to_string(),
+        ..common::paper(1)
+    }
+}
+
+#[tokio::test]
+async fn test_ensemble_votes() {
+    let ensemble = EnsembleExtracterBuilder::default()
+        .members(vec![
+            member("a", &["LLMs", "Prompt tuning"]),
+            member("b", &["LLM", "prompt-tuning", "RLHF"]),
+            member("c", &["LLM", "Chain of thought"]),
+        ])
+        .build()
+        .unwrap();
+
+    let res = ensemble.extract(&paper()).await.unwrap();
+    assert_eq!(
+        res.techniques_used,
+        vec!["LLM", "Prompt tuning", "RLHF", "Chain of thought"]
+    );
+    assert_eq!(res.technique_confidence("LLM"), 1.0);
+    assert!((res.technique_confidence("Prompt tuning") - 2.0 / 3.0).abs() < 1e-9);
+    assert!((res.technique_confidence("RLHF") - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(res.research_field, vec!["f"]);
+
+    let confident = res.keep_confident(0.5);
+    assert_eq!(confident.techniques_used, vec!["LLM", "Prompt tuning"]);
+
+    let provenance = ensemble.provenance().unwrap();
+    assert_eq!(provenance.extracter, "ensemble(topic+topic+topic)");
+    assert_eq!(provenance.model, "a+b+c");
+}
+
+#[tokio::test]
+async fn test_ensemble_normalizer_and_failures() {
+    let broken = TopicExtracterBuilder::default()
+        .llm(Box::new(
+            MockLlm::new().with_default(MockResponse::error("boom")),
+        ))
+        .build()
+        .unwrap();
+    let ensemble = EnsembleExtracterBuilder::default()
+        .members(vec![
+            member("a", &["Large Language Models"]),
+            member("b", &["LLM", "RLHF"]),
+            broken.clone(),
+        ])
+        .normalizer(Arc::new(KeywordNormalizer::default()))
+        .min_agreement(0.6)
+        .build()
+        .unwrap();
+
+    // the failed member votes for nothing, the acronym merges with its expansion
+    let res = ensemble.extract(&paper()).await.unwrap();
+    assert_eq!(res.techniques_used, vec!["large language model"]);
+    assert_eq!(res.technique_confidence("large language model"), 2.0 / 3.0);
+
+    let halved = EnsembleExtracterBuilder::default()
+        .members(vec![member("b", &["LLM"]), broken.clone()])
+        .build()
+        .unwrap();
+    let res = halved.extract(&paper()).await.unwrap();
+    assert_eq!(res.technique_confidence("LLM"), 0.5);
+
+    let all_broken = EnsembleExtracterBuilder::default()
+        .members(vec![broken.clone(), broken])
+        .build()
+        .unwrap();
+    assert!(all_broken.extract(&paper()).await.is_err());
+}
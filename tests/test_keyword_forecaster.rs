@@ -0,0 +1,88 @@
+use chrono::{Months, NaiveDate};
+use sciffer_rs::analyzers::forecast::{ForecastMethod, KeywordForecasterBuilder};
+use sciffer_rs::analyzers::trend::{Granularity, KeywordSeries};
+
+/// Two years of monthly counts: agents growing 15% a month, LLM steady,
+/// fuzzing losing a paper a month.
+fn counts() -> Vec<(String, NaiveDate, usize)> {
+    let start = NaiveDate::from_ymd_opt(2023, 1, 15).unwrap();
+    let mut counts = Vec::new();
+    for m in 0..24 {
+        let day = start + Months::new(m);
+        let m = m as i32;
+        counts.push(("agents".to_string(), day, (2.0 * 1.15f64.powi(m)) as usize));
+        counts.push(("LLM".to_string(), day, 30 + m as usize % 3));
+        counts.push(("fuzzing".to_string(), day, (40 - m) as usize));
+    }
+    counts
+}
+
+#[test]
+fn test_forecast_ranking() {
+    let forecasts = KeywordForecasterBuilder::default()
+        .build()
+        .unwrap()
+        .forecast(&counts());
+    let keywords: Vec<&str> = forecasts.iter().map(|f| f.keyword.as_str()).collect();
+    assert_eq!(keywords, vec!["agents", "LLM", "fuzzing"]);
+
+    let agents = &forecasts[0];
+    assert_eq!(agents.method, ForecastMethod::Holt);
+    assert_eq!(agents.points.len(), 3);
+    assert_eq!(
+        agents.points[0].bucket,
+        NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+    );
+    assert!(agents.growth().unwrap() > 0.2);
+    for point in agents.points.iter() {
+        assert!(point.lower <= point.papers && point.papers <= point.upper);
+    }
+    let fuzzing = &forecasts[2];
+    assert!((fuzzing.points[0].papers - 16.0).abs() < 0.5);
+    assert!(fuzzing.to_string().starts_with("fuzzing: "));
+}
+
+#[test]
+fn test_invalid_options() {
+    for level in [0.0, 1.0, 1.5, -0.2, f64::NAN] {
+        assert!(KeywordForecasterBuilder::default()
+            .level(level)
+            .build()
+            .is_err());
+    }
+    assert!(KeywordForecasterBuilder::default()
+        .horizon(0)
+        .build()
+        .is_err());
+    assert!(KeywordForecasterBuilder::default()
+        .level(0.95)
+        .horizon(1)
+        .build()
+        .is_ok());
+}
+
+#[test]
+fn test_backtest() {
+    let counts: Vec<_> = counts()
+        .into_iter()
+        .filter(|(keyword, _, _)| keyword != "LLM")
+        .collect();
+    let series = KeywordSeries::new(
+        Granularity::Month,
+        NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+        &counts,
+    );
+    let forecaster = KeywordForecasterBuilder::default().build().unwrap();
+
+    let holt = forecaster.backtest_series(&series, ForecastMethod::Holt, 6);
+    // 2 keywords, 6 windows of 3 months
+    assert_eq!(holt.forecasts, 36);
+    // trends are better extrapolated than repeated
+    assert!(holt.mase.unwrap() < 1.0);
+    assert!((0.0..=1.0).contains(&holt.coverage));
+
+    let log_linear = forecaster.backtest_series(&series, ForecastMethod::LogLinear, 6);
+    assert_eq!(log_linear.forecasts, 36);
+    assert!(holt.to_string().starts_with("holt: MAE "));
+}
@@ -0,0 +1,95 @@
+use arxiv::Arxiv;
+use chrono::NaiveDate;
+use sciffer_rs::analyzers::matrix::MatrixAnalyzerBuilder;
+use sciffer_rs::analyzers::trend::Granularity;
+use sciffer_rs::extracters::topic::ArxivTopicData;
+
+mod common;
+use common::{dated, extracted};
+
+fn paper(day: &str, problems: &[&str], techniques: &[&str]) -> (Arxiv, ArxivTopicData) {
+    extracted(dated(day), problems, techniques)
+}
+
+/// Program repair moving from genetic programming to LLMs between March and
+/// April, while test generation keeps using search.
+fn data() -> Vec<(Arxiv, ArxivTopicData)> {
+    let mut data = Vec::new();
+    for _ in 0..5 {
+        data.push(paper(
+            "2025-03-10",
+            &["program repair"],
+            &["genetic programming"],
+        ));
+        data.push(paper("2025-03-12", &["test generation"], &["search"]));
+        data.push(paper("2025-04-14", &["test generation"], &["search"]));
+    }
+    data.push(paper("2025-03-20", &["program repair"], &["LLM"]));
+    for _ in 0..6 {
+        data.push(paper("2025-04-02", &["program repair"], &["LLM"]));
+    }
+    data.push(paper("2025-04-20", &["test generation"], &["LLM"]));
+    // no technique, left out of every matrix
+    data.push(paper("2025-04-21", &["program repair"], &[]));
+    data
+}
+
+#[test]
+fn test_matrix_gaps() {
+    let analyzer = MatrixAnalyzerBuilder::default().build().unwrap();
+    let matrix = analyzer.matrix(&data());
+    assert_eq!(matrix.papers, 23);
+    assert_eq!(matrix.problems.len(), 2);
+    assert_eq!(matrix.techniques.len(), 3);
+    assert_eq!(matrix.get("program repair", "LLM"), Some(7));
+    assert_eq!(matrix.get("program repair", "search"), Some(0));
+
+    // search is frequent but never tried on program repair
+    let gaps = matrix.gaps(1);
+    assert_eq!(gaps[0].problem, "program repair");
+    assert_eq!(gaps[0].technique, "search");
+    assert_eq!(gaps[0].observed, 0);
+    let over = matrix.over_explored(10);
+    assert!(over
+        .iter()
+        .any(|c| c.problem == "test generation" && c.technique == "search"));
+    assert!(over.iter().all(|c| c.observed as f64 > c.expected));
+
+    // every row and column of the printed matrix
+    let printed = matrix.to_string();
+    assert!(printed.contains("program repair"));
+    assert!(printed.contains("genetic programming"));
+}
+
+#[test]
+fn test_matrix_windows() {
+    let analyzer = MatrixAnalyzerBuilder::default().build().unwrap();
+    let windows = analyzer.windows(&data(), Granularity::Month);
+    let months: Vec<NaiveDate> = windows.iter().map(|(month, _)| *month).collect();
+    assert_eq!(
+        months,
+        vec![
+            NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 4, 1).unwrap()
+        ]
+    );
+    let (march, april) = (&windows[0].1, &windows[1].1);
+    assert_eq!(march.techniques, april.techniques);
+    assert_eq!((march.papers, april.papers), (11, 12));
+
+    let changes = march.compare(april);
+    let repair = |technique: &str| {
+        changes
+            .iter()
+            .find(|c| c.problem == "program repair" && c.technique == technique)
+            .unwrap()
+    };
+    assert_eq!((repair("LLM").before, repair("LLM").after), (1, 6));
+    assert!(repair("LLM").change > 0.4);
+    assert!(repair("genetic programming").change < -0.4);
+    assert!(changes[0].change.abs() >= changes[changes.len() - 1].change.abs());
+    // pairs empty in both windows are left out
+    assert!(!changes
+        .iter()
+        .any(|c| c.technique == "search" && c.problem == "program repair"));
+}
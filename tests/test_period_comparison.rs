@@ -0,0 +1,78 @@
+use arxiv::Arxiv;
+use sciffer_rs::analyzers::compare::{KeywordChange, Period, PeriodComparerBuilder};
+use sciffer_rs::analyzers::simple::SimpleArixvTrendingAnalyzerBuilder;
+use sciffer_rs::extracters::topic::ArxivTopicData;
+
+mod common;
+use common::{dated, extracted};
+
+fn paper(i: usize, day: &str, techniques: &[&str]) -> (Arxiv, ArxivTopicData) {
+    let raw = Arxiv {
+        title: format!("paper {}", i),
+        ..dated(day)
+    };
+    extracted(raw, &[], techniques)
+}
+
+/// Two editions of a conference, a year apart, with papers in between.
+fn data() -> Vec<(Arxiv, ArxivTopicData)> {
+    let mut data = Vec::new();
+    for i in 0..10 {
+        let techniques: &[&str] = match i {
+            0..=5 => &["fuzzing"],
+            6..=7 => &["LLM", "fuzzing"],
+            _ => &["symbolic execution"],
+        };
+        data.push(paper(i, "2024-04-16", techniques));
+    }
+    for i in 10..20 {
+        let techniques: &[&str] = match i {
+            10..=16 => &["LLM"],
+            17..=18 => &["fuzzing", "agents"],
+            _ => &["agents"],
+        };
+        data.push(paper(i, "2025-04-29", techniques));
+    }
+    data.push(paper(20, "2024-11-01", &["symbolic execution"]));
+    data
+}
+
+#[test]
+fn test_compare_editions() {
+    let data = data();
+    let (icse24, icse25): (Period, Period) = (
+        "2024-04-14..2024-04-20".parse().unwrap(),
+        "2025-04-27..2025-05-03".parse().unwrap(),
+    );
+    let (before, after) = (icse24.select(&data), icse25.select(&data));
+    assert_eq!((before.len(), after.len()), (10, 10));
+
+    let analyzer = SimpleArixvTrendingAnalyzerBuilder::default()
+        .build()
+        .unwrap();
+    let comparison = PeriodComparerBuilder::default()
+        .build()
+        .unwrap()
+        .compare_with(&analyzer, &before, &after, |d| d.techniques_used.clone());
+
+    let keywords = |changes: &[KeywordChange]| -> Vec<String> {
+        changes.iter().map(|c| c.keyword.clone()).collect()
+    };
+    assert_eq!(keywords(&comparison.new), vec!["agents"]);
+    assert_eq!(keywords(&comparison.vanished), vec!["symbolic execution"]);
+    assert_eq!(keywords(&comparison.risers), vec!["LLM"]);
+    assert_eq!(keywords(&comparison.fallers), vec!["fuzzing"]);
+
+    let llm = &comparison.risers[0];
+    assert_eq!((llm.before, llm.after), (2, 7));
+    assert!((llm.change - 0.5).abs() < 1e-9);
+    // representative papers come from the later edition
+    assert_eq!(llm.papers.len(), 3);
+    assert!(llm.papers.iter().all(|p| p.published.starts_with("2025")));
+    let vanished = &comparison.vanished[0];
+    assert_eq!(vanished.papers[0].title, "paper 8");
+
+    let report = comparison.truncated(1).to_string();
+    assert!(report.starts_with("10 -> 10 papers\nNew:\n  agents: 0 -> 3 papers (+30.0%)"));
+    assert!(report.contains("    - paper 17"));
+}
@@ -0,0 +1,242 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use arxiv::Arxiv;
+use async_trait::async_trait;
+use sciffer_rs::config::ScifferConfig;
+use sciffer_rs::extracters::artifact::ArtifactExtracterBuilder;
+use sciffer_rs::extracters::batch::BatchTopicExtracterBuilder;
+use sciffer_rs::extracters::pipeline::{
+    LanguageStage, PipelineExtracterBuilder, Stage, TopicStage,
+};
+use sciffer_rs::extracters::summary::SummaryExtracterBuilder;
+use sciffer_rs::extracters::topic::{ArxivTopicData, TopicExtracterBuilder};
+use sciffer_rs::extracters::{ExtractResult, Extracter, ExtracterError};
+use sciffer_rs::fetchers::{Fetcher, FetcherError};
+use sciffer_rs::llms::mock::{MockLlm, MockResponse};
+use sciffer_rs::sciffer::{ArxivScifferBuilder, Sniffer};
+
+mod common;
+use common::topic_json;
+
+const SUMMARY_JSON: &str =
+    r#"{"tldr": "A tool that repairs programs.", "contributions": ["RepairBot"]}"#;
+
+const ARTIFACT_JSON: &str = r#"{"benchmarks": ["Defects4J"], "code_urls": []}"#;
+
+/// A paper with an abstract long enough to be told English.
+fn paper(i: usize) -> Arxiv {
+    Arxiv {
+        summary: "We present a tool that repairs the bugs of programs and we evaluate it on a benchmark of real bugs with the tests of the projects.".to_string(),
+        ..common::paper(i)
+    }
+}
+
+/// Answers every prompt of the crate with its fixture.
+fn llm() -> MockLlm {
+    MockLlm::new()
+        .with_fixture("reading group", MockResponse::text(SUMMARY_JSON))
+        .with_fixture("evaluation artifacts", MockResponse::text(ARTIFACT_JSON))
+        .with_default(MockResponse::text(topic_json("paper", &["LLM"])))
+}
+
+/// Tags the techniques found by the earlier stages, fails without them.
+struct TagStage;
+
+#[async_trait]
+impl Stage for TagStage {
+    fn name(&self) -> &str {
+        "tag"
+    }
+
+    async fn run(&self, ctx: &Arxiv, data: &mut ArxivTopicData) -> ExtractResult<()> {
+        if data.techniques_used.is_empty() {
+            let err = ExtracterError::Other("no techniques yet".into(), ctx.id.clone());
+            return (Err(err), None);
+        }
+        for technique in data.techniques_used.iter_mut() {
+            technique.push_str(" (tagged)");
+        }
+        (Ok(()), None)
+    }
+}
+
+/// Counts the runs in flight and keeps the highest count seen.
+#[derive(Clone, Default)]
+struct InFlightStage {
+    current: Arc<AtomicUsize>,
+    highest: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Stage for InFlightStage {
+    fn name(&self) -> &str {
+        "in-flight"
+    }
+
+    async fn run(&self, _ctx: &Arxiv, _data: &mut ArxivTopicData) -> ExtractResult<()> {
+        let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+        self.highest.fetch_max(current, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        self.current.fetch_sub(1, Ordering::SeqCst);
+        (Ok(()), None)
+    }
+}
+
+#[derive(Clone)]
+struct StaticFetcher(usize);
+
+impl Fetcher for StaticFetcher {
+    type Output = Arxiv;
+
+    async fn fetch(&self) -> Result<Vec<Self::Output>, FetcherError> {
+        Ok((0..self.0).map(paper).collect())
+    }
+}
+
+#[tokio::test]
+async fn test_pipeline() {
+    let llm = llm();
+    let pipeline = PipelineExtracterBuilder::default()
+        .stage(LanguageStage)
+        .stage(TagStage)
+        .stage(TopicStage::new(
+            TopicExtracterBuilder::default()
+                .llm(Box::new(llm.clone()))
+                .model("mock")
+                .build()
+                .unwrap(),
+        ))
+        .stage(TagStage)
+        .stage(
+            ArtifactExtracterBuilder::default()
+                .llm(Box::new(llm.clone()))
+                .model("mock")
+                .build()
+                .unwrap(),
+        )
+        .stage(
+            SummaryExtracterBuilder::default()
+                .llm(Box::new(llm.clone()))
+                .model("mock")
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+
+    let (res, usage) = pipeline.extract_with_usage(&paper(0)).await;
+    let data = res.unwrap();
+    assert_eq!(data.language.as_deref(), Some("en"));
+    // the first tag ran before the topics and failed, the second saw them
+    assert_eq!(data.failed_stages, vec!["tag"]);
+    assert_eq!(data.techniques_used, vec!["LLM (tagged)"]);
+    assert_eq!(data.artifacts.unwrap().benchmarks, vec!["Defects4J"]);
+    assert_eq!(data.summary.unwrap().contributions, vec!["RepairBot"]);
+    assert_eq!(llm.call_count(), 3);
+    assert!(usage.unwrap().total().prompt_tokens > 0);
+
+    let provenance = pipeline.provenance().unwrap();
+    assert_eq!(provenance.extracter, "topic+artifact+summary");
+    assert_eq!(provenance.model, "mock");
+}
+
+#[tokio::test]
+async fn test_pipeline_failures() {
+    let summarizer = MockLlm::new().with_default(MockResponse::text("not json"));
+    let mut pipeline = PipelineExtracterBuilder::default()
+        .stage(TopicStage::new(
+            TopicExtracterBuilder::default()
+                .llm(Box::new(
+                    MockLlm::new()
+                        .with_fixture("\"paper 1\"", MockResponse::error("boom"))
+                        .with_default(MockResponse::text(topic_json("paper", &["LLM"]))),
+                ))
+                .build()
+                .unwrap(),
+        ))
+        .stage(
+            SummaryExtracterBuilder::default()
+                .llm(Box::new(summarizer.clone()))
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+
+    let papers = [paper(0), paper(1)];
+    assert_eq!(pipeline.estimate_requests(&papers), 4);
+    let results = pipeline.extract_batch(&papers).await;
+    // a failed summary keeps the paper, failed topics drop it before the summary
+    let data = results[0].0.as_ref().unwrap();
+    assert!(data.summary.is_none());
+    assert_eq!(data.failed_stages, vec!["summary"]);
+    assert!(results[1].0.is_err());
+    assert_eq!(summarizer.call_count(), 1);
+
+    assert!(pipeline.set_enabled("summary", false));
+    assert_eq!(pipeline.estimate_requests(&papers), 2);
+    assert!(!pipeline.set_enabled("language", false));
+    assert_eq!(
+        pipeline.stages().collect::<Vec<_>>(),
+        vec![("topic", true), ("summary", false)]
+    );
+    let data = pipeline.extract(&paper(0)).await.unwrap();
+    assert!(data.failed_stages.is_empty());
+    assert_eq!(summarizer.call_count(), 1);
+    assert_eq!(pipeline.provenance().unwrap().extracter, "topic");
+}
+
+#[tokio::test]
+async fn test_pipeline_batches() {
+    let topics = BatchTopicExtracterBuilder::default()
+        .single(
+            TopicExtracterBuilder::default()
+                .llm(Box::new(llm()))
+                .build()
+                .unwrap(),
+        )
+        .max_batch_size(2)
+        .build()
+        .unwrap();
+    let pipeline = PipelineExtracterBuilder::default()
+        .stage(LanguageStage)
+        .stage(TopicStage::new(topics))
+        .build()
+        .unwrap();
+
+    let papers: Vec<Arxiv> = (0..5).map(paper).collect();
+    assert_eq!(pipeline.plan_batches(&papers), vec![2, 2, 1]);
+    // one request per batch, language detection sends none
+    assert_eq!(pipeline.estimate_requests(&papers[..2]), 1);
+    assert!(pipeline.plan_batches(&[]).is_empty());
+}
+
+#[tokio::test]
+async fn test_pipeline_unbatched_bounded() {
+    let stage = InFlightStage::default();
+    let pipeline = PipelineExtracterBuilder::default()
+        .stage(LanguageStage)
+        .stage(stage.clone())
+        .build()
+        .unwrap();
+    let papers: Vec<Arxiv> = (0..3).map(paper).collect();
+    assert_eq!(pipeline.plan_batches(&papers), vec![1, 1, 1]);
+
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher(50))
+        .extracter(pipeline)
+        .config(ScifferConfig {
+            max_in_flight: Some(4),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+    let res = sciffer.sniffer_parallel().await.unwrap();
+
+    assert_eq!(res.len(), 50);
+    // no stage batches, so every paper takes one of the 4 slots
+    let highest = stage.highest.load(Ordering::SeqCst);
+    assert!((1..=4).contains(&highest), "{} in flight", highest);
+}
@@ -0,0 +1,203 @@
+use arxiv::Arxiv;
+use async_trait::async_trait;
+use langchain_rust::embedding::{Embedder, EmbedderError};
+use langchain_rust::llm::client::OllamaError;
+use sciffer_rs::extracters::pipeline::{
+    PipelineExtracter, PipelineExtracterBuilder, Stage, TopicStage,
+};
+use sciffer_rs::extracters::relevance::{
+    Relevance, RelevanceFilterBuilder, RelevanceMode, RelevanceScorer,
+};
+use sciffer_rs::extracters::topic::{ArxivTopicData, TopicExtracterBuilder};
+use sciffer_rs::extracters::Extracter;
+use sciffer_rs::fetchers::{Fetcher, FetcherError};
+use sciffer_rs::llms::mock::{MockEmbedder, MockLlm, MockResponse};
+use sciffer_rs::sciffer::{ArxivScifferBuilder, Sniffer};
+
+mod common;
+use common::{titled, topic_json};
+
+const TOPIC: &str = "automated repair of software bugs";
+
+#[derive(Clone)]
+struct StaticFetcher {
+    papers: Vec<Arxiv>,
+}
+
+impl Fetcher for StaticFetcher {
+    type Output = Arxiv;
+
+    async fn fetch(&self) -> Result<Vec<Self::Output>, FetcherError> {
+        Ok(self.papers.clone())
+    }
+}
+
+/// Fails every call with the error of `fail`.
+struct FailingEmbedder(fn() -> EmbedderError);
+
+#[async_trait]
+impl Embedder for FailingEmbedder {
+    async fn embed_documents(&self, _: &[String]) -> Result<Vec<Vec<f64>>, EmbedderError> {
+        Err((self.0)())
+    }
+
+    async fn embed_query(&self, _: &str) -> Result<Vec<f64>, EmbedderError> {
+        Err((self.0)())
+    }
+}
+
+fn pipeline(judge: &MockLlm, topics: &MockLlm, mode: RelevanceMode) -> PipelineExtracter {
+    PipelineExtracterBuilder::default()
+        .stage(
+            RelevanceFilterBuilder::default()
+                .topic(TOPIC)
+                .scorer(RelevanceScorer::Llm(Box::new(judge.clone())))
+                .mode(mode)
+                .build()
+                .unwrap(),
+        )
+        .stage(TopicStage::new(
+            TopicExtracterBuilder::default()
+                .llm(Box::new(topics.clone()))
+                .build()
+                .unwrap(),
+        ))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_relevance_drops_off_topic_papers() {
+    let fetcher = StaticFetcher {
+        papers: vec![
+            titled(0, "Repairing flaky tests with LLMs"),
+            titled(1, "Self-healing concrete"),
+        ],
+    };
+    let judge = MockLlm::new()
+        .with_fixture("concrete", MockResponse::text("No"))
+        .with_default(MockResponse::text(
+            "<think>no doubt, it repairs tests</think>\nYes",
+        ));
+    let topics = MockLlm::new().with_default(MockResponse::text(topic_json("paper", &["LLM"])));
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(fetcher)
+        .extracter(pipeline(&judge, &topics, RelevanceMode::Drop))
+        .build()
+        .unwrap();
+
+    let report = sciffer.sniffer_report().await.unwrap();
+    assert!(report.failures.is_empty());
+    assert_eq!(report.successes.len(), 1);
+    assert_eq!(
+        report.successes[0].result.relevance,
+        Some(Relevance {
+            score: 1.0,
+            relevant: true
+        })
+    );
+    assert_eq!(report.filtered.len(), 1);
+    assert_eq!(report.filtered[0].input.title, "Self-healing concrete");
+    assert_eq!(report.filtered[0].result, 0.0);
+    // the off-topic paper never reached the topic extracter
+    assert_eq!(judge.call_count(), 2);
+    assert_eq!(topics.call_count(), 1);
+    assert!(report
+        .to_string()
+        .starts_with("extracted 1 papers, 0 failed, 0 unfinished, 1 off-topic"));
+}
+
+#[tokio::test]
+async fn test_relevance_flags_off_topic_papers() {
+    let judge = MockLlm::new()
+        .with_fixture("concrete", MockResponse::text("no"))
+        .with_fixture("graphs", MockResponse::text("maybe"))
+        .with_default(MockResponse::text("yes"));
+    let topics = MockLlm::new().with_default(MockResponse::text(topic_json("paper", &["LLM"])));
+    let pipeline = pipeline(&judge, &topics, RelevanceMode::Flag);
+
+    let papers = [
+        titled(0, "Repairing flaky tests with LLMs"),
+        titled(1, "Self-healing concrete"),
+        titled(2, "Random graphs"),
+    ];
+    let results = pipeline.extract_batch(&papers).await;
+    let relevance = |i: usize| results[i].0.as_ref().unwrap().relevance.unwrap();
+    assert!(relevance(0).relevant);
+    assert!(!relevance(1).relevant);
+    // an unusable answer fails the paper like a failed topic extraction
+    assert_eq!(results[2].0.as_ref().unwrap_err().kind(), "parse");
+    assert_eq!(topics.call_count(), 2);
+    assert!(results[1].1.as_ref().unwrap().total().prompt_tokens > 0);
+    assert_eq!(pipeline.provenance().unwrap().extracter, "relevance+topic");
+}
+
+#[tokio::test]
+async fn test_relevance_embeddings() {
+    let papers = [
+        titled(0, "Repairing flaky tests with LLMs"),
+        titled(1, "Self-healing concrete"),
+    ];
+    let text = |p: &Arxiv| format!("{}\n{}", p.title, p.summary);
+    let embedder = MockEmbedder::new()
+        .with_vector(TOPIC, vec![1.0, 0.0])
+        .with_vector(text(&papers[0]), vec![0.8, 0.6])
+        .with_vector(text(&papers[1]), vec![0.6, 0.8]);
+    let topics = MockLlm::new().with_default(MockResponse::text(topic_json("paper", &["LLM"])));
+    let pipeline = PipelineExtracterBuilder::default()
+        .stage(
+            RelevanceFilterBuilder::default()
+                .topic(TOPIC)
+                .scorer(RelevanceScorer::Embedding(Box::new(embedder)))
+                .threshold(0.7)
+                .build()
+                .unwrap(),
+        )
+        .stage(TopicStage::new(
+            TopicExtracterBuilder::default()
+                .llm(Box::new(topics.clone()))
+                .build()
+                .unwrap(),
+        ))
+        .build()
+        .unwrap();
+
+    let results = pipeline.extract_batch(&papers).await;
+    let relevance = results[0].0.as_ref().unwrap().relevance.unwrap();
+    assert!((relevance.score - 0.8).abs() < 1e-9);
+    assert!(relevance.relevant);
+    assert_eq!(results[1].0.as_ref().unwrap_err().kind(), "irrelevant");
+    assert_eq!(topics.call_count(), 1);
+    // embeddings cost nothing and leave the provenance to the topics
+    assert_eq!(pipeline.provenance().unwrap().extracter, "topic");
+    assert_eq!(
+        pipeline.estimate_tokens(&papers[0]),
+        TopicExtracterBuilder::default()
+            .llm(Box::new(topics))
+            .build()
+            .unwrap()
+            .estimate_tokens(&papers[0])
+    );
+}
+
+#[tokio::test]
+async fn test_relevance_embedding_errors() {
+    let unreachable = || EmbedderError::OllamaError(OllamaError::from("refused".to_string()));
+    let unusable = || EmbedderError::FastEmbedError("no such model".to_string());
+    let papers = [titled(0, "Repairing flaky tests"), titled(1, "Fuzzing")];
+    for (fail, transient) in [(unreachable as fn() -> _, true), (unusable, false)] {
+        let filter = RelevanceFilterBuilder::default()
+            .topic(TOPIC)
+            .scorer(RelevanceScorer::Embedding(Box::new(FailingEmbedder(fail))))
+            .build()
+            .unwrap();
+
+        let (res, _) = filter.run(&papers[0], &mut ArxivTopicData::default()).await;
+        assert_eq!(res.unwrap_err().is_transient(), transient);
+        let mut data = vec![ArxivTopicData::default(); 2];
+        for (res, _) in filter.run_batch(&papers, &mut data).await {
+            // an unreachable embedder is retried later, a broken one is not
+            assert_eq!(res.unwrap_err().is_transient(), transient);
+        }
+    }
+}
@@ -0,0 +1,507 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use arxiv::Arxiv;
+use sciffer_rs::config::{ModelPrice, RateLimitConfig, ScifferConfig};
+use sciffer_rs::extracters::batch::BatchTopicExtracterBuilder;
+use sciffer_rs::extracters::ensemble::EnsembleExtracterBuilder;
+use sciffer_rs::extracters::topic::TopicExtracterBuilder;
+use sciffer_rs::fetchers::{Fetcher, FetcherError};
+use sciffer_rs::llms::mock::{MockLlm, MockResponse};
+use sciffer_rs::sciffer::{ArxivScifferBuilder, Sniffer};
+use sciffer_rs::tools::usage::count_tokens;
+
+mod common;
+use common::{titled, topic_json};
+
+#[derive(Clone)]
+struct StaticFetcher {
+    titles: Vec<String>,
+}
+
+impl Fetcher for StaticFetcher {
+    type Output = Arxiv;
+
+    async fn fetch(&self) -> Result<Vec<Self::Output>, FetcherError> {
+        Ok(self
+            .titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| titled(i, title))
+            .collect())
+    }
+}
+
+#[tokio::test]
+async fn test_sniffer_parallel_drops_failures() {
+    let fetcher = StaticFetcher {
+        titles: vec!["good one".into(), "bad one".into(), "good two".into()],
+    };
+    let llm = MockLlm::new()
+        .with_fixture(
+            "good one",
+            MockResponse::text(topic_json("good one", &["t"])),
+        )
+        .with_fixture(
+            "good two",
+            MockResponse::text(topic_json("good two", &["t"])),
+        )
+        .with_fixture("bad one", MockResponse::text("not json at all"));
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm.clone()))
+        .build()
+        .unwrap();
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(fetcher)
+        .extracter(extracter)
+        .build()
+        .unwrap();
+
+    let res = sciffer.sniffer_parallel().await.unwrap();
+
+    let mut titles: Vec<_> = res.iter().map(|(_, d)| d.title.clone()).collect();
+    titles.sort();
+    assert_eq!(titles, vec!["good one", "good two"]);
+    assert_eq!(llm.call_count(), 3);
+}
+
+#[tokio::test]
+async fn test_sniffer_parallel_runs_concurrently() {
+    let titles: Vec<String> = (0..8).map(|i| format!("paper {}", i)).collect();
+    let mut llm = MockLlm::new();
+    for title in titles.iter() {
+        llm = llm.with_fixture(
+            format!("\"{}\"", title),
+            MockResponse::delayed(
+                Duration::from_millis(200),
+                MockResponse::text(topic_json(title, &["t"])),
+            ),
+        );
+    }
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm))
+        .build()
+        .unwrap();
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher { titles })
+        .extracter(extracter)
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    let res = sciffer.sniffer_parallel().await.unwrap();
+
+    assert_eq!(res.len(), 8);
+    assert!(start.elapsed() < Duration::from_millis(1600));
+}
+
+#[tokio::test]
+async fn test_sniffer_parallel_bounded() {
+    let titles: Vec<String> = (0..8).map(|i| format!("paper {}", i)).collect();
+    let llm = MockLlm::new().with_default(MockResponse::delayed(
+        Duration::from_millis(100),
+        MockResponse::text(topic_json("paper", &["t"])),
+    ));
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm))
+        .build()
+        .unwrap();
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher { titles })
+        .extracter(extracter)
+        .config(ScifferConfig {
+            max_in_flight: Some(2),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    let res = sciffer.sniffer_parallel().await.unwrap();
+
+    assert_eq!(res.len(), 8);
+    // 8 papers, 2 at a time, 100ms each
+    assert!(start.elapsed() >= Duration::from_millis(400));
+}
+
+#[tokio::test]
+async fn test_sniffer_parallel_rate_limited() {
+    let titles: Vec<String> = (0..3).map(|i| format!("paper {}", i)).collect();
+    let llm = MockLlm::new().with_default(MockResponse::text(topic_json("paper", &["t"])));
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm))
+        .build()
+        .unwrap();
+    let mut providers = HashMap::new();
+    providers.insert(
+        "mock".to_string(),
+        RateLimitConfig {
+            requests_per_minute: Some(120),
+            tokens_per_minute: None,
+        },
+    );
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher { titles })
+        .extracter(extracter)
+        .config(ScifferConfig {
+            provider: Some("mock".to_string()),
+            providers,
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+
+    // the first 120 requests are a burst, budget is shared across cycles
+    for _ in 0..40 {
+        assert_eq!(sciffer.sniffer_parallel().await.unwrap().len(), 3);
+    }
+    let start = Instant::now();
+    assert_eq!(sciffer.sniffer_parallel().await.unwrap().len(), 3);
+    assert!(start.elapsed() >= Duration::from_millis(1000));
+}
+
+#[tokio::test]
+async fn test_sniffer_parallel_timeout_retried() {
+    let llm = MockLlm::new().with_responses(vec![
+        MockResponse::delayed(
+            Duration::from_secs(3),
+            MockResponse::text(topic_json("slow", &["t"])),
+        ),
+        MockResponse::text(topic_json("slow", &["t"])),
+    ]);
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm.clone()))
+        .build()
+        .unwrap();
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher {
+            titles: vec!["slow".into()],
+        })
+        .extracter(extracter)
+        .config(ScifferConfig {
+            extract_timeout: Some(1),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    assert!(sciffer.sniffer_parallel().await.unwrap().is_empty());
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert_eq!(sciffer.pending_retries().len(), 1);
+
+    assert_eq!(sciffer.sniffer_parallel().await.unwrap().len(), 1);
+    assert!(sciffer.pending_retries().is_empty());
+    assert_eq!(llm.call_count(), 2);
+}
+
+#[tokio::test]
+async fn test_sniffer_parallel_retries_up_to_max_attempts() {
+    let llm = MockLlm::new().with_default(MockResponse::delayed(
+        Duration::from_secs(3),
+        MockResponse::text(topic_json("slow", &["t"])),
+    ));
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm))
+        .build()
+        .unwrap();
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher {
+            titles: vec!["slow".into()],
+        })
+        .extracter(extracter)
+        .config(ScifferConfig {
+            extract_timeout: Some(1),
+            max_attempts: Some(2),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+    assert!(sciffer.sniffer_parallel().await.unwrap().is_empty());
+    assert_eq!(sciffer.pending_retries().len(), 1);
+    assert!(sciffer.sniffer_parallel().await.unwrap().is_empty());
+    assert!(sciffer.pending_retries().is_empty());
+}
+
+#[tokio::test]
+async fn test_sniffer_parallel_cycle_deadline() {
+    let titles: Vec<String> = (0..3).map(|i| format!("paper {}", i)).collect();
+    let llm = MockLlm::new().with_default(MockResponse::delayed(
+        Duration::from_secs(3),
+        MockResponse::text(topic_json("paper", &["t"])),
+    ));
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm))
+        .build()
+        .unwrap();
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher { titles })
+        .extracter(extracter)
+        .config(ScifferConfig {
+            cycle_deadline: Some(1),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    assert!(sciffer.sniffer_parallel().await.unwrap().is_empty());
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert_eq!(sciffer.pending_retries().len(), 3);
+}
+
+#[tokio::test]
+async fn test_sniffer_parallel_cancel_keeps_in_flight() {
+    let titles: Vec<String> = (0..3).map(|i| format!("paper {}", i)).collect();
+    let llm = MockLlm::new().with_default(MockResponse::delayed(
+        Duration::from_millis(300),
+        MockResponse::text(topic_json("paper", &["t"])),
+    ));
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm.clone()))
+        .build()
+        .unwrap();
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher { titles })
+        .extracter(extracter)
+        .config(ScifferConfig {
+            max_in_flight: Some(1),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+
+    let cancellation = sciffer.cancellation();
+    let (res, _) = tokio::join!(sciffer.sniffer_parallel(), async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        cancellation.cancel();
+    });
+
+    assert_eq!(res.unwrap().len(), 1);
+    assert_eq!(llm.call_count(), 1);
+    assert_eq!(sciffer.pending_retries().len(), 2);
+}
+
+#[tokio::test]
+async fn test_sniffer_report() {
+    let fetcher = StaticFetcher {
+        titles: vec!["good one".into(), "bad one".into(), "broken one".into()],
+    };
+    let llm = MockLlm::new()
+        .with_fixture(
+            "good one",
+            MockResponse::text(topic_json("good one", &["t"])),
+        )
+        .with_fixture("bad one", MockResponse::text("not json at all"))
+        .with_fixture("broken one", MockResponse::error("connection reset"));
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm))
+        .build()
+        .unwrap();
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(fetcher)
+        .extracter(extracter)
+        .build()
+        .unwrap();
+
+    let report = sciffer.sniffer_report().await.unwrap();
+
+    assert_eq!(report.successes.len(), 1);
+    assert_eq!(report.successes[0].result.title, "good one");
+    assert!(report.unfinished.is_empty());
+    let mut kinds: Vec<_> = report
+        .failures
+        .iter()
+        .map(|f| (f.input.title.as_str(), f.result.kind()))
+        .collect();
+    kinds.sort();
+    assert_eq!(kinds, vec![("bad one", "parse"), ("broken one", "chain")]);
+    assert!(report
+        .to_string()
+        .starts_with("extracted 1 papers, 2 failed"));
+}
+
+#[tokio::test]
+async fn test_sniffer_report_batched() {
+    let fetcher = StaticFetcher {
+        titles: (0..5).map(|i| format!("paper {}", i)).collect(),
+    };
+    let batch_answer = |ids: &[usize]| {
+        let entries: Vec<String> = ids
+            .iter()
+            .map(|i| {
+                format!(
+                    r#"{{"id": "2504.{:05}v1", "title": "paper {}", "solved_problem": [], "research_field": [], "techniques_used": ["t"]}}"#,
+                    i, i
+                )
+            })
+            .collect();
+        MockResponse::text(format!("[{}]", entries.join(", ")))
+    };
+    let llm = MockLlm::new()
+        .with_fixture("Id: 2504.00000v1", batch_answer(&[0, 1]))
+        // paper 3 is missing from its batch and extracted alone
+        .with_fixture("Id: 2504.00002v1", batch_answer(&[2]))
+        .with_default(MockResponse::text(topic_json("alone", &["t"])));
+    let extracter = BatchTopicExtracterBuilder::default()
+        .single(
+            TopicExtracterBuilder::default()
+                .llm(Box::new(llm.clone()))
+                .build()
+                .unwrap(),
+        )
+        .max_batch_size(2)
+        .build()
+        .unwrap();
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(fetcher)
+        .extracter(extracter)
+        .build()
+        .unwrap();
+
+    let report = sciffer.sniffer_report().await.unwrap();
+    assert_eq!(report.successes.len(), 5);
+    assert!(report.failures.is_empty());
+    // batches [0, 1] and [2, 3], the fallback for 3 and paper 4 alone
+    assert_eq!(llm.call_count(), 4);
+    let mut titles: Vec<String> = report
+        .into_pairs()
+        .into_iter()
+        .map(|(_, x)| x.title)
+        .collect();
+    titles.sort();
+    assert_eq!(
+        titles,
+        vec!["alone", "alone", "paper 0", "paper 1", "paper 2"]
+    );
+}
+
+#[tokio::test]
+async fn test_sniffer_spending_cap() {
+    let titles: Vec<String> = (0..5).map(|i| format!("paper {}", i)).collect();
+    let answer = topic_json("paper", &["t"]);
+    let llm = MockLlm::new().with_default(MockResponse::text(answer.clone()));
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm.clone()))
+        .model("mock")
+        .build()
+        .unwrap();
+    // the mock reports no usage, $1 per estimated completion token
+    let cost = count_tokens(&answer) as f64;
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher { titles })
+        .extracter(extracter)
+        .config(ScifferConfig {
+            max_in_flight: Some(1),
+            prices: HashMap::from([(
+                "mock".to_string(),
+                ModelPrice {
+                    prompt: 0.0,
+                    completion: 1_000_000.0,
+                },
+            )]),
+            max_cost: Some(cost * 2.5),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+
+    let report = sciffer.sniffer_report().await.unwrap();
+    // the third extraction goes over the cap, the rest never starts
+    assert_eq!(report.successes.len(), 3);
+    assert_eq!(report.unfinished.len(), 2);
+    assert_eq!(llm.call_count(), 3);
+    assert_eq!(report.successes[0].cost, Some(cost));
+    assert_eq!(report.usage.total_cost(), cost * 3.0);
+    assert_eq!(sciffer.spent(), cost * 3.0);
+    assert!(report.usage.total_tokens() > 0);
+    assert!(sciffer.cancellation().is_cancelled());
+}
+
+#[tokio::test]
+async fn test_sniffer_spending_cap_needs_price() {
+    let llm = MockLlm::new().with_default(MockResponse::text(topic_json("paper", &["t"])));
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm.clone()))
+        .model("mock")
+        .build()
+        .unwrap();
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher {
+            titles: vec!["paper".into()],
+        })
+        .extracter(extracter)
+        .config(ScifferConfig {
+            max_cost: Some(1.0),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+
+    let err = sciffer.sniffer_report().await.unwrap_err();
+    assert!(err.to_string().contains("mock has no price"));
+    assert_eq!(llm.call_count(), 0);
+}
+
+#[tokio::test]
+async fn test_sniffer_prices_each_model() {
+    let answer = topic_json("paper", &["t"]);
+    let member = |model: &str| {
+        TopicExtracterBuilder::default()
+            .llm(Box::new(
+                MockLlm::new().with_default(MockResponse::text(answer.clone())),
+            ))
+            .model(model)
+            .build()
+            .unwrap()
+    };
+    let ensemble = EnsembleExtracterBuilder::default()
+        .members(vec![member("cheap"), member("dear")])
+        .build()
+        .unwrap();
+    let price = |completion: f64| ModelPrice {
+        prompt: 0.0,
+        completion,
+    };
+    let config = ScifferConfig {
+        prices: HashMap::from([
+            ("cheap".to_string(), price(1_000_000.0)),
+            ("dear".to_string(), price(2_000_000.0)),
+        ]),
+        max_cost: Some(1_000_000.0),
+        ..Default::default()
+    };
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher {
+            titles: vec!["paper".into()],
+        })
+        .extracter(ensemble.clone())
+        .config(config.clone())
+        .build()
+        .unwrap();
+
+    let report = sciffer.sniffer_report().await.unwrap();
+    // the mock reports no usage, $1 or $2 per estimated completion token
+    let tokens = count_tokens(&answer) as f64;
+    let models: Vec<_> = report
+        .usage
+        .models()
+        .map(|m| (m.model.as_str(), m.extractions, m.cost))
+        .collect();
+    assert_eq!(
+        models,
+        vec![("cheap", 1, Some(tokens)), ("dear", 1, Some(tokens * 2.0))]
+    );
+    assert_eq!(report.successes[0].cost, Some(tokens * 3.0));
+
+    let mut config = config;
+    config.prices.remove("dear");
+    let sciffer = ArxivScifferBuilder::default()
+        .fetcher(StaticFetcher { titles: vec![] })
+        .extracter(ensemble)
+        .config(config)
+        .build()
+        .unwrap();
+    let err = sciffer.check_budget().unwrap_err();
+    assert!(err.to_string().contains("dear has no price"), "{}", err);
+}
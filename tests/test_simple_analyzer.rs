@@ -1,14 +1,17 @@
 use std::collections::HashMap;
 
 use arxiv::Arxiv;
+use futures::stream;
 use rand::seq::SliceRandom;
 use rand::Rng;
 use sciffer_rs::analyzers::simple::SimpleArixvTrendingAnalyzerBuilder;
-use sciffer_rs::analyzers::TrendingAnalyzer;
+use sciffer_rs::analyzers::{TieBreak, TopK, TrendingAnalyzer};
 use sciffer_rs::extracters::topic::ArxivTopicData;
 
+mod common;
+
 fn generate_random_string(_: usize) -> String {
-    let keywords = vec![
+    let keywords = [
         "machine learning",
         "image recogniton",
         "artificial intelligence",
@@ -58,19 +61,25 @@ fn generate_dummy_topicdata() -> ArxivTopicData {
         techniques_used: (0..rand::thread_rng().gen_range(1..4)) // Random number of techniques (1-3)
             .map(|_| generate_random_string(15)) // Random techniques used
             .collect(),
+        confidence: None,
+        summary: None,
+        artifacts: None,
+        language: None,
+        relevance: None,
+        failed_stages: vec![],
     }
 }
 
-fn trending_problems_round(cnt: i32) {
+fn trending_problems_round(_cnt: i32) {
     let cnt = 5;
     let mut data = vec![];
-    (0..cnt).into_iter().for_each(|_| {
+    (0..cnt).for_each(|_| {
         data.push((generate_dummy_arxiv(), generate_dummy_topicdata()));
     });
 
     let mut cnt: HashMap<String, i32> = HashMap::new();
     data.iter().for_each(|(_, x)| {
-        x.solved_problem.iter().for_each(|s| {
+        x.research_field.iter().for_each(|s| {
             let entry = cnt.entry(s.to_string()).or_insert(0);
             *entry += 1
         });
@@ -85,19 +94,93 @@ fn trending_problems_round(cnt: i32) {
     let analyzer = SimpleArixvTrendingAnalyzerBuilder::default()
         .build()
         .unwrap();
-    let res = analyzer.top_k(&data, |x| x.research_field.clone());
+    let res = analyzer.top_k(&data, |x| x.research_field.clone(), 3);
+    assert_eq!(res.len(), oracle.len().min(3));
 
     // println!("{:#?}", res);
 
     for (x, y) in oracle.iter().zip(&res) {
-        assert!(x.0 == y.0, "{:?}, {:?}", oracle, res)
+        assert!(x.0 == y.keyword, "{:?}, {:?}", oracle, res);
+        assert_eq!(x.1 as usize, y.papers.len());
     }
 }
 
 #[test]
 fn test_trending_problems() {
     let epoch = 20;
-    (0..epoch)
-        .into_iter()
-        .for_each(|_| trending_problems_round(epoch));
+    (0..epoch).for_each(|_| trending_problems_round(epoch));
+}
+
+fn paper(problems: &[&str]) -> (Arxiv, ArxivTopicData) {
+    common::extracted(Arxiv::default(), problems, &[])
+}
+
+#[test]
+fn test_trends_options() {
+    let data = [
+        paper(&["repair", "fuzzing"]),
+        paper(&["fuzzing"]),
+        paper(&["verification", "repair"]),
+        paper(&["typing"]),
+    ];
+    let analyzer = SimpleArixvTrendingAnalyzerBuilder::default()
+        .build()
+        .unwrap();
+    let trends = |options: TopK| -> Vec<(String, Vec<usize>)> {
+        analyzer
+            .trends(
+                data.iter().enumerate().map(|(i, (raw, ctx))| (i, raw, ctx)),
+                |x| x.solved_problem.clone(),
+                options,
+            )
+            .into_iter()
+            .map(|t| (t.keyword, t.papers))
+            .collect()
+    };
+
+    let top = trends(TopK::new(2));
+    assert_eq!(
+        top,
+        vec![
+            ("repair".to_string(), vec![0, 2]),
+            ("fuzzing".to_string(), vec![0, 1])
+        ]
+    );
+    let ties = trends(TopK {
+        ties: TieBreak::FirstSeen,
+        ..TopK::new(3)
+    });
+    assert_eq!(ties[0].0, "repair");
+    assert_eq!(ties[2].0, "verification");
+    let supported = trends(TopK {
+        min_support: 2,
+        ..Default::default()
+    });
+    assert_eq!(supported.len(), 2);
+    assert!(trends(TopK::new(0)).is_empty());
+}
+
+#[tokio::test]
+async fn test_trends_stream() {
+    let papers = (0..5).map(|i| {
+        let (raw, ctx) = paper(if i % 2 == 0 {
+            &["repair"]
+        } else {
+            &["fuzzing"]
+        });
+        Ok::<_, ()>((i, raw, ctx))
+    });
+    let trends = SimpleArixvTrendingAnalyzerBuilder::default()
+        .build()
+        .unwrap()
+        .trends_stream(
+            stream::iter(papers),
+            |x| x.solved_problem.clone(),
+            TopK::new(1),
+        )
+        .await
+        .unwrap();
+    assert_eq!(trends.len(), 1);
+    assert_eq!(trends[0].keyword, "repair");
+    assert_eq!(trends[0].papers, vec![0, 2, 4]);
 }
@@ -0,0 +1,40 @@
+use arxiv::Arxiv;
+use sciffer_rs::extracters::summary::SummaryExtracterBuilder;
+use sciffer_rs::extracters::Extracter;
+use sciffer_rs::llms::mock::{MockLlm, MockResponse};
+
+mod common;
+use common::paper;
+
+const SUMMARY_JSON: &str = r#"```json
+{
+  "title": "Paper",
+  "tldr": "A tool that repairs programs.",
+  "contributions": ["RepairBot"],
+  "results": ["fixes 40% of bugs vs. 25% for the baseline"],
+  "limitations": []
+}
+```"#;
+
+#[tokio::test]
+async fn test_summary_extracter() {
+    let llm = MockLlm::new().with_default(MockResponse::text(SUMMARY_JSON));
+    let extracter = SummaryExtracterBuilder::default()
+        .llm(Box::new(llm))
+        .model("mock")
+        .build()
+        .unwrap();
+
+    let paper = Arxiv {
+        summary: "We repair programs.".to_string(),
+        ..paper(0)
+    };
+    let (res, usage) = extracter.extract_with_usage(&paper).await;
+    let summary = res.unwrap();
+    assert_eq!(summary.tldr, "A tool that repairs programs.");
+    assert_eq!(summary.contributions, vec!["RepairBot"]);
+    assert!(summary.limitations.is_empty());
+    assert!(usage.unwrap().total().prompt_tokens > 0);
+    assert!(summary.to_string().contains("Results:\n  - fixes 40%"));
+    assert!(!summary.to_string().contains("Limitations"));
+}
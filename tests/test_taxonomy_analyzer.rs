@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use arxiv::Arxiv;
+use sciffer_rs::analyzers::taxonomy::TaxonomyTrendingAnalyzerBuilder;
+use sciffer_rs::analyzers::TrendingAnalyzer;
+use sciffer_rs::extracters::topic::ArxivTopicData;
+use sciffer_rs::taxonomy::mapper::TaxonomyMapperBuilder;
+use sciffer_rs::taxonomy::Taxonomy;
+
+mod common;
+use common::strings;
+
+fn paper(id: &str, fields: &[&str]) -> (Arxiv, ArxivTopicData) {
+    let arxiv = Arxiv {
+        id: id.to_string(),
+        ..Default::default()
+    };
+    let topic = ArxivTopicData {
+        title: id.to_string(),
+        research_field: strings(fields),
+        ..Default::default()
+    };
+    (arxiv, topic)
+}
+
+#[tokio::test]
+async fn test_roll_up_trends() {
+    let data = vec![
+        paper("1", &["program repair"]),
+        paper("2", &["Fault Localization", "debugging"]),
+        paper("3", &["model checking"]),
+        paper("4", &["large language models"]),
+    ];
+    let taxonomy = Arc::new(Taxonomy::from_file("taxonomies/acm_ccs.json").unwrap());
+    let mapper = TaxonomyMapperBuilder::default()
+        .taxonomy(taxonomy.clone())
+        .build()
+        .unwrap();
+    let fields: Vec<String> = data
+        .iter()
+        .flat_map(|(_, x)| x.research_field.clone())
+        .collect();
+    let matches = mapper.map_all(&fields).await.unwrap();
+
+    let leaves = TaxonomyTrendingAnalyzerBuilder::default()
+        .taxonomy(taxonomy.clone())
+        .matches(matches.clone())
+        .depth(3)
+        .build()
+        .unwrap()
+        .top_k(&data, |x| x.research_field.clone(), 10);
+    assert_eq!(leaves[0].keyword, "Software testing and debugging");
+    // one paper with two matching fields counts once
+    assert_eq!(leaves[0].papers.len(), 2);
+
+    let roots = TaxonomyTrendingAnalyzerBuilder::default()
+        .taxonomy(taxonomy.clone())
+        .matches(matches.clone())
+        .depth(0)
+        .build()
+        .unwrap()
+        .top_k(&data, |x| x.research_field.clone(), 10);
+    let roots: Vec<(&str, usize)> = roots
+        .iter()
+        .map(|t| (t.keyword.as_str(), t.papers.len()))
+        .collect();
+    assert_eq!(
+        roots,
+        vec![
+            ("Software and its engineering", 3),
+            ("Computing methodologies", 1)
+        ]
+    );
+
+    let all = TaxonomyTrendingAnalyzerBuilder::default()
+        .taxonomy(taxonomy)
+        .matches(matches)
+        .build()
+        .unwrap()
+        .top_k(&data, |x| x.research_field.clone(), 10);
+    let verification = all
+        .iter()
+        .find(|t| t.keyword == "Software verification and validation")
+        .unwrap();
+    assert_eq!(verification.papers.len(), 3);
+}
@@ -0,0 +1,132 @@
+use sciffer_rs::extracters::topic::TopicExtracterBuilder;
+use sciffer_rs::extracters::{Extracter, ExtracterError};
+use sciffer_rs::llms::mock::{MockLlm, MockResponse};
+use sciffer_rs::prompts::PromptTemplate;
+
+mod common;
+use common::titled;
+
+const FIXTURES: &str = "tests/fixtures/mock_llm_topic.json";
+
+fn fixture_llm() -> MockLlm {
+    MockLlm::new().with_fixture_file(FIXTURES).unwrap()
+}
+
+#[tokio::test]
+async fn test_extract_fenced_json() {
+    let llm = fixture_llm();
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm.clone()))
+        .build()
+        .unwrap();
+
+    let res = extracter
+        .extract(&titled(0, "Deep Residual Learning for Image Recognition"))
+        .await
+        .unwrap();
+
+    assert_eq!(res.title, "Deep Residual Learning for Image Recognition");
+    assert_eq!(res.techniques_used, vec!["Residual learning"]);
+    assert_eq!(llm.call_count(), 1);
+    assert!(llm.calls()[0].contains("The abstract of Deep Residual Learning"));
+}
+
+#[tokio::test]
+async fn test_extract_raw_json() {
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(fixture_llm()))
+        .build()
+        .unwrap();
+
+    let res = extracter
+        .extract(&titled(0, "Attention Is All You Need"))
+        .await
+        .unwrap();
+
+    assert_eq!(res.techniques_used, vec!["Attention", "Transformer"]);
+}
+
+#[tokio::test]
+async fn test_extract_malformed_output() {
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(fixture_llm()))
+        .build()
+        .unwrap();
+
+    let res = extracter
+        .extract(&titled(0, "A Paper The Model Chokes On"))
+        .await;
+    assert!(
+        matches!(res, Err(ExtracterError::ParseError(..))),
+        "{:?}",
+        res
+    );
+
+    let res = extracter
+        .extract(&titled(0, "A Paper With Missing Fields"))
+        .await;
+    assert!(
+        matches!(res, Err(ExtracterError::ParseError(..))),
+        "{:?}",
+        res
+    );
+}
+
+#[tokio::test]
+async fn test_extract_llm_error() {
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(fixture_llm()))
+        .build()
+        .unwrap();
+
+    let res = extracter.extract(&titled(0, "A Paper That Errors")).await;
+    assert!(
+        matches!(res, Err(ExtracterError::ChainError(..))),
+        "{:?}",
+        res
+    );
+}
+
+#[tokio::test]
+async fn test_extract_scripted() {
+    let llm = MockLlm::new().with_responses(vec![
+        MockResponse::error("rate limited"),
+        MockResponse::text(
+            r#"{"title": "t", "solved_problem": [], "research_field": [], "techniques_used": ["LLM"]}"#,
+        ),
+    ]);
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm))
+        .build()
+        .unwrap();
+
+    let ctx = titled(0, "Unknown");
+    assert!(extracter.extract(&ctx).await.is_err());
+    assert_eq!(
+        extracter.extract(&ctx).await.unwrap().techniques_used,
+        vec!["LLM"]
+    );
+}
+
+#[tokio::test]
+async fn test_extract_prompt_variant() {
+    let llm = MockLlm::new().with_default(MockResponse::text(
+        r#"{"title": "t", "solved_problem": [], "research_field": [], "techniques_used": []}"#,
+    ));
+    let prompt = PromptTemplate::new("topic-zeroshot", "2", "Summarize {title}: {summary}");
+    let extracter = TopicExtracterBuilder::default()
+        .llm(Box::new(llm.clone()))
+        .prompt(prompt.clone())
+        .model("mock")
+        .build()
+        .unwrap();
+
+    extracter.extract(&titled(0, "Variant")).await.unwrap();
+
+    assert!(llm.calls()[0].ends_with("Summarize Variant: The abstract of Variant."));
+    let provenance = extracter.provenance().unwrap();
+    assert_eq!(provenance.prompt, "topic-zeroshot");
+    assert_eq!(provenance.prompt_version, "2");
+    assert_eq!(provenance.prompt_hash, prompt.hash);
+    assert_eq!(provenance.model, "mock");
+}
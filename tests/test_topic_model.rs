@@ -0,0 +1,94 @@
+use arxiv::Arxiv;
+use chrono::NaiveDate;
+use sciffer_rs::analyzers::topic_model::NmfTopicModelerBuilder;
+use sciffer_rs::analyzers::trend::Granularity;
+
+const THEMES: [&[&str]; 3] = [
+    &[
+        "fuzzing",
+        "crashes",
+        "coverage",
+        "mutation",
+        "seeds",
+        "sanitizers",
+    ],
+    &[
+        "repair",
+        "patches",
+        "bugs",
+        "regression",
+        "fixes",
+        "plausible",
+    ],
+    &[
+        "verification",
+        "proofs",
+        "invariants",
+        "solver",
+        "specifications",
+        "lemmas",
+    ],
+];
+
+/// An abstract of a theme, four of its words picked by `i`.
+fn paper(theme: usize, i: usize, day: &str) -> Arxiv {
+    let words = THEMES[theme];
+    let text: Vec<&str> = (0..4)
+        .map(|j| words[(i + j * (i % 5 + 1)) % words.len()])
+        .collect();
+    Arxiv {
+        title: format!("On {} and {}", text[0], text[1]),
+        summary: format!("We study {} with {}.", text[2], text[3]),
+        published: format!("{}T12:00:00Z", day),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_topic_prevalence() {
+    // fuzzing fades from March to April while repair takes over, the
+    // verification papers are spread evenly
+    let mut themed = Vec::new();
+    for i in 0..12 {
+        let day = ["2025-03-11", "2025-04-11"][i % 2];
+        themed.push((0, paper(0, i, "2025-03-10")));
+        themed.push((1, paper(1, i, "2025-04-10")));
+        themed.push((2, paper(2, i, day)));
+    }
+    for i in 0..3 {
+        themed.push((0, paper(0, i, "2025-04-12")));
+        themed.push((1, paper(1, i, "2025-03-12")));
+    }
+    let papers: Vec<Arxiv> = themed.iter().map(|(_, p)| p.clone()).collect();
+
+    let model = NmfTopicModelerBuilder::default()
+        .topics(3)
+        .build()
+        .unwrap()
+        .fit(&papers);
+    assert_eq!(model.topics.len(), 3);
+    assert_eq!(model.distributions.len(), papers.len());
+
+    // the papers of a theme share their heaviest topic
+    let (fuzzing, repair) = (model.dominant(0).unwrap(), model.dominant(1).unwrap());
+    assert_ne!(fuzzing, repair);
+    for (i, (theme, paper)) in themed.iter().enumerate() {
+        assert_eq!(model.dominant(i), model.dominant(*theme), "{}", paper.title);
+    }
+    assert!(THEMES[0]
+        .iter()
+        .any(|w| model.topics[fuzzing].label.contains(w)));
+
+    let prevalence = model.prevalence(Granularity::Month);
+    assert_eq!(prevalence.len(), 2);
+    assert_eq!(
+        prevalence[0].0,
+        NaiveDate::from_ymd_opt(2025, 3, 1).unwrap()
+    );
+    let (march, april) = (&prevalence[0].1, &prevalence[1].1);
+    assert!(march[fuzzing] > 0.4 && april[fuzzing] < 0.2);
+    assert!(march[repair] < 0.2 && april[repair] > 0.4);
+    for (_, shares) in prevalence.iter() {
+        assert!((shares.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,56 @@
+use arxiv::Arxiv;
+use chrono::{Days, NaiveDate};
+use sciffer_rs::analyzers::trend::{daily_counts_of, Granularity, WindowedTrendAnalyzerBuilder};
+use sciffer_rs::extracters::topic::ArxivTopicData;
+
+mod common;
+use common::extracted;
+
+/// `n` papers published on `day` using `techniques`.
+fn papers(day: NaiveDate, n: usize, techniques: &[&str]) -> Vec<(Arxiv, ArxivTopicData)> {
+    (0..n)
+        .map(|i| {
+            let raw = Arxiv {
+                id: format!("{}-{}-{}", day, techniques.join("+"), i),
+                published: format!("{}T{:02}:00:00Z", day, i % 24),
+                ..Default::default()
+            };
+            extracted(raw, &[], techniques)
+        })
+        .collect()
+}
+
+#[test]
+fn test_windowed_trends() {
+    let start = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+    let mut data = Vec::new();
+    for day in 0..42 {
+        let date = start + Days::new(day);
+        // popular all along, a little less in the last two weeks
+        data.extend(papers(date, 3 - (day / 28) as usize, &["LLM"]));
+        if day % 7 == 0 {
+            data.extend(papers(date, 1, &["agents", "LLM"]));
+        }
+        // agents take off in the last two weeks
+        if day >= 28 {
+            data.extend(papers(date, 2, &["agents"]));
+        }
+    }
+
+    let counts = daily_counts_of(&data, |d| d.techniques_used.clone());
+    let trends = WindowedTrendAnalyzerBuilder::default()
+        .granularity(Granularity::Week)
+        .window(2)
+        .baseline(4)
+        .build()
+        .unwrap()
+        .rank(&counts);
+
+    assert_eq!(trends[0].keyword, "agents");
+    assert_eq!(trends[0].papers, 14 * 2 + 2);
+    assert_eq!(trends[0].baseline_mean, 1.0);
+    assert_eq!(trends[0].growth, Some(14.0));
+    let llm = trends.iter().find(|t| t.keyword == "LLM").unwrap();
+    assert!(llm.growth.unwrap() < 0.0);
+    assert!(llm.z_score < 0.0);
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use arxiv::Arxiv;
+use chrono::NaiveDate;
+use sciffer_rs::analyzers::weighted::{
+    CitationWeight, ConfidenceWeight, WeightedTrendingAnalyzer, WeightedTrendingAnalyzerBuilder,
+};
+use sciffer_rs::analyzers::TrendingAnalyzer;
+use sciffer_rs::config::TrendingConfig;
+use sciffer_rs::extracters::topic::{ArxivTopicData, TopicConfidence};
+
+mod common;
+use common::extracted;
+
+/// Paper `i` of April 1st, by its own author.
+fn paper(i: usize, techniques: &[&str]) -> (Arxiv, ArxivTopicData) {
+    let raw = Arxiv {
+        published: "2025-04-01T12:00:00Z".to_string(),
+        authors: vec![format!("author {}", i)],
+        ..common::paper(i)
+    };
+    extracted(raw, &[], techniques)
+}
+
+/// Ten minor papers on fuzzing, three on LLMs.
+fn data() -> Vec<(Arxiv, ArxivTopicData)> {
+    let mut data: Vec<_> = (0..10).map(|i| paper(i, &["fuzzing"])).collect();
+    data.extend((10..13).map(|i| paper(i, &["LLM"])));
+    data
+}
+
+#[test]
+fn test_unweighted_counts() {
+    let data = data();
+    let ranked = WeightedTrendingAnalyzerBuilder::default()
+        .build()
+        .unwrap()
+        .top_k(&data, |d| d.techniques_used.clone(), 2);
+    assert_eq!(ranked[0].keyword, "fuzzing");
+    assert_eq!(ranked[0].papers.len(), 10);
+    assert_eq!(ranked[1].score, 3.0);
+}
+
+#[test]
+fn test_citation_weighted() {
+    let data = data();
+    let citations: HashMap<String, u32> =
+        (10..13).map(|i| (format!("2504.{:05}", i), 1000)).collect();
+    let ranked = WeightedTrendingAnalyzerBuilder::default()
+        .scorer(CitationWeight::new(citations))
+        .build()
+        .unwrap()
+        .rank(&data, |d| d.techniques_used.clone());
+
+    assert_eq!(ranked[0].keyword, "LLM");
+    assert!((ranked[0].score - 3.0 * (1.0 + 1001f64.ln())).abs() < 1e-9);
+    assert_eq!(ranked[1].score, 10.0);
+    assert_eq!(ranked[0].breakdown.papers, 3);
+    assert_eq!(ranked[0].breakdown.factors[0].0, "citations");
+    assert!(ranked[0]
+        .to_string()
+        .starts_with("LLM: 23.73 (3 papers; citations x7.91)"));
+
+    // the same counts read from the config
+    let config = TrendingConfig {
+        citations: Some("tests/fixtures/citations.json".into()),
+        ..Default::default()
+    };
+    let today = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+    let configured = WeightedTrendingAnalyzer::from_config(&config, today)
+        .unwrap()
+        .rank(&data, |d| d.techniques_used.clone());
+    assert_eq!(configured[0].score, ranked[0].score);
+
+    let config = TrendingConfig {
+        citations: Some("tests/fixtures/missing.json".into()),
+        ..Default::default()
+    };
+    assert!(WeightedTrendingAnalyzer::from_config(&config, today).is_err());
+}
+
+#[test]
+fn test_config_scorers() {
+    let mut data = data();
+    // an old paper, one from the same group as paper 0, a paper at ICSE
+    // with an uncertain keyword listed second
+    data[1].0.published = "2025-03-02T12:00:00Z".to_string();
+    data[2].0.authors = vec!["author 0".to_string(), "someone".to_string()];
+    data[10].0.comment = Some("Accepted at ICSE 2026, 12 pages".to_string());
+    data[10].1.techniques_used = vec!["agents".to_string(), "LLM".to_string()];
+    data[10].1.confidence = Some(TopicConfidence {
+        techniques_used: HashMap::from([("LLM".to_string(), 0.5)]),
+        ..Default::default()
+    });
+
+    let config = TrendingConfig {
+        half_life_days: Some(30.0),
+        confidence: true,
+        position_decay: Some(1.0),
+        venues: HashMap::from([("ICSE".to_string(), 4.0)]),
+        dedup_authors: true,
+        citations: None,
+    };
+    let today = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+    let analyzer = WeightedTrendingAnalyzer::from_config(&config, today).unwrap();
+    let ranked = analyzer.rank(&data, |d| d.techniques_used.clone());
+    let keyword = |k: &str| ranked.iter().find(|w| w.keyword == k).unwrap();
+
+    let fuzzing = keyword("fuzzing");
+    assert_eq!(fuzzing.breakdown.papers, 10);
+    assert_eq!(fuzzing.breakdown.groups, 9);
+    // one paper counts half, papers 0 and 2 once
+    assert!((fuzzing.score - 8.5).abs() < 1e-9);
+    let factors: Vec<&str> = fuzzing
+        .breakdown
+        .factors
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(
+        factors,
+        vec!["venue", "recency", "confidence", "position", "authors"]
+    );
+
+    // 4 for the venue, halved by the confidence and by the position
+    let llm = keyword("LLM");
+    assert!((llm.score - 3.0).abs() < 1e-9);
+    assert_eq!(keyword("agents").score, 4.0);
+    assert_eq!(ranked[0].keyword, "fuzzing");
+
+    // the tally path scores the same, authors included
+    for trend in analyzer.top_k(&data, |d| d.techniques_used.clone(), 10) {
+        assert!((trend.score - keyword(&trend.keyword).score).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_config_validation() {
+    assert!(TrendingConfig::default().validate().is_ok());
+    for (half_life_days, position_decay) in [
+        (Some(0.0), None),
+        (Some(-30.0), None),
+        (Some(f64::NAN), None),
+        (None, Some(-0.5)),
+        (None, Some(f64::INFINITY)),
+    ] {
+        let config = TrendingConfig {
+            half_life_days,
+            position_decay,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+    let config = TrendingConfig {
+        half_life_days: Some(30.0),
+        position_decay: Some(0.0),
+        ..Default::default()
+    };
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_confidence_without_votes() {
+    let data = data();
+    let ranked = WeightedTrendingAnalyzerBuilder::default()
+        .scorer(ConfidenceWeight)
+        .build()
+        .unwrap()
+        .rank(&data, |d| d.techniques_used.clone());
+    assert_eq!(ranked[0].score, 10.0);
+}